@@ -1,3 +1,176 @@
+use serde_json::{Map, Value};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 应用自己的命令，与 `src/lib.rs` 中 `generate_handler!` 注册的命令保持一致
+///
+/// 在这里声明后，每个命令都需要在 `capabilities/` 中通过 `allow-<命令>` 授权给具体的窗口，
+/// 例如关于窗口不能调用 `install_update` 或 `import_profile`。
+const APP_COMMANDS: &[&str] = &[
+    "greet",
+    "set_complete",
+    "set_progress",
+    "set_failed",
+    "splash_retry",
+    "splash_quit",
+    "get_locale",
+    "set_locale",
+    "get_settings",
+    "update_settings",
+    "reset_settings",
+    "get_effective_config",
+    "get_tray_menu",
+    "set_tray_menu",
+    "get_tray_status",
+    "update_tray_status",
+    "get_recent_items",
+    "add_recent_item",
+    "remove_recent_item",
+    "clear_recent_items",
+    "set_quick_actions",
+    "fetch_update",
+    "install_update",
+    "restart_to_update",
+    "get_staged_update",
+    "get_update_health",
+    "confirm_update_health",
+    "rollback_update",
+    "get_updater_settings",
+    "set_updater_settings",
+    "skip_update_version",
+    "window_action",
+    "get_window_visibility",
+    "open_window",
+    "open_route_window",
+    "close_window",
+    "take_launch_args",
+    "take_deep_links",
+    "export_profile",
+    "preview_profile",
+    "import_profile",
+];
+
 fn main() {
-    tauri_build::build()
+    embed_locales();
+    tauri_build::try_build(
+        tauri_build::Attributes::new()
+            .app_manifest(tauri_build::AppManifest::new().commands(APP_COMMANDS)),
+    )
+    .expect("运行 tauri-build 失败")
+}
+
+/// 将前端 `src/locales/<语言>/**/*.json` 中的语言包嵌入到二进制中，
+/// 生成 `$OUT_DIR/locales.rs`，由 `core::locale::catalog` 引入
+fn embed_locales() {
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let root = manifest_dir.join("../src/locales");
+    println!("cargo:rerun-if-changed={}", root.display());
+
+    let mut entries = Vec::new();
+    for dir in sorted_entries(&root) {
+        if !dir.is_dir() {
+            continue;
+        }
+        let tag = dir.file_name().unwrap().to_string_lossy().to_string();
+        let start = entries.len();
+        collect_json(&dir, &dir, &tag, &mut entries);
+        check_catalog(&tag, &entries[start..]);
+    }
+
+    let mut source =
+        String::from("/// 嵌入的语言包：(语言标签, 相对路径（不含扩展名）, 文件内容)\n");
+    source.push_str("pub(crate) static CATALOG_FILES: &[(&str, &str, &str)] = &[\n");
+    for (tag, name, path) in entries {
+        source.push_str(&format!(
+            "    ({:?}, {:?}, include_str!({:?})),\n",
+            tag,
+            name,
+            path.display().to_string()
+        ));
+    }
+    source.push_str("];\n");
+
+    let out = PathBuf::from(env::var("OUT_DIR").unwrap()).join("locales.rs");
+    fs::write(out, source).expect("无法生成 locales.rs");
+}
+
+/// 按 `core::locale::catalog` 的合并方式检查同一语言的所有文件，
+/// 无效的 JSON 或者与已有文案冲突的路径在构建时报错，而不是在运行时才发现
+fn check_catalog(tag: &str, entries: &[(String, String, PathBuf)]) {
+    let mut catalog = Map::new();
+    for (_, name, path) in entries {
+        let content = fs::read_to_string(path)
+            .unwrap_or_else(|error| panic!("无法读取语言包 {}: {}", path.display(), error));
+        let value = if content.trim().is_empty() {
+            Map::new()
+        } else {
+            match serde_json::from_str(&content) {
+                Ok(Value::Object(value)) => value,
+                Ok(_) => panic!("语言包 {} 的根节点必须是对象", path.display()),
+                Err(error) => panic!("语言包 {} 无效: {}", path.display(), error),
+            }
+        };
+
+        let parts: Vec<&str> = name.split('/').collect();
+        let mut node = &mut catalog;
+        if parts != [tag] {
+            for (index, part) in parts.iter().enumerate() {
+                let child = node
+                    .entry(part.to_string())
+                    .or_insert_with(|| Value::Object(Map::new()));
+                node = child.as_object_mut().unwrap_or_else(|| {
+                    panic!(
+                        "语言包 {} 与已有的文案 {} 冲突",
+                        path.display(),
+                        parts[..=index].join(".")
+                    )
+                });
+            }
+        }
+        merge(node, value);
+    }
+}
+
+/// 与运行时相同的深度合并，后者覆盖前者
+fn merge(target: &mut Map<String, Value>, value: Map<String, Value>) {
+    for (key, value) in value {
+        match (target.get_mut(&key), value) {
+            (Some(Value::Object(target)), Value::Object(value)) => merge(target, value),
+            (_, value) => {
+                target.insert(key, value);
+            }
+        }
+    }
+}
+
+fn collect_json(root: &Path, dir: &Path, tag: &str, entries: &mut Vec<(String, String, PathBuf)>) {
+    for path in sorted_entries(dir) {
+        if path.is_dir() {
+            collect_json(root, &path, tag, entries);
+        } else if path.extension().is_some_and(|ext| ext == "json") {
+            let name = path
+                .strip_prefix(root)
+                .unwrap()
+                .with_extension("")
+                .components()
+                .map(|part| part.as_os_str().to_string_lossy().to_string())
+                .collect::<Vec<_>>()
+                .join("/");
+            let path = path.canonicalize().unwrap_or(path);
+            entries.push((tag.to_string(), name, path));
+        }
+    }
+}
+
+fn sorted_entries(dir: &Path) -> Vec<PathBuf> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .collect()
+        })
+        .unwrap_or_default();
+    entries.sort();
+    entries
 }
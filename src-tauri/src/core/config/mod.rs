@@ -0,0 +1,613 @@
+//! # 分层配置
+//!
+//! 接口地址、更新端点、日志级别等运行时选项不再写死在构建产物中，而是按以下顺序合并，后面的覆盖前面的：
+//!
+//! 1. 内置默认值
+//! 2. 系统配置文件，由管理员为整台机器配置（[`system_config_path`]）
+//! 3. 用户配置文件，应用配置目录下的 `config.json`
+//! 4. 环境变量，例如 `TAURI_BUN_VITE_API_BASE_URL`
+//! 5. 命令行参数，例如 `--api-base-url=https://api.example.com`
+//!
+//! 配置文件使用嵌套的 JSON 对象，例如 `{ "api": { "baseUrl": "https://api.example.com" } }`。
+//! 每一项都会单独校验，不合法的值和未知的键被忽略并记录警告，不影响其他配置项。
+//! `get_effective_config` 命令返回最终生效的配置以及每一项的来源。
+//!
+//! 应用运行期间修改配置文件后由 [`watcher`] 重新加载，变化的配置项通过 [`CONFIG_CHANGED_EVENT`] 通知前端。
+
+pub mod watcher;
+
+use crate::core::locale::tr;
+use crate::models::{AppError, AppResult, ErrorCode};
+use crate::utils::logger;
+use log::LevelFilter;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+use url::Url;
+
+/// 用户配置文件名
+const CONFIG_FILE: &str = "config.json";
+
+/// 环境变量前缀
+pub const ENV_PREFIX: &str = "TAURI_BUN_VITE_";
+
+/// 指定系统配置文件路径的环境变量，用于测试或非标准的部署目录
+pub const SYSTEM_CONFIG_ENV: &str = "TAURI_BUN_VITE_SYSTEM_CONFIG";
+
+/// 所有配置项的键名
+pub const KEYS: &[&str] = &[
+    "api.baseUrl",
+    "updater.endpoint",
+    "updater.channel",
+    "log.level",
+    "tray.title",
+];
+
+/// 重新加载后有配置项发生变化时发出的事件，内容为 [`ConfigChange`]
+pub const CONFIG_CHANGED_EVENT: &str = "config://changed";
+
+/// 更新通道的可选值，与 `UpdateChannel` 保持一致
+const CHANNELS: &[&str] = &["stable", "beta", "nightly"];
+
+/// 配置项的来源，优先级从低到高
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConfigSource {
+    Default,
+    System,
+    User,
+    Env,
+    Cli,
+}
+
+/// 日志级别
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+    Trace,
+}
+
+impl From<LogLevel> for LevelFilter {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Error => LevelFilter::Error,
+            LogLevel::Warn => LevelFilter::Warn,
+            LogLevel::Info => LevelFilter::Info,
+            LogLevel::Debug => LevelFilter::Debug,
+            LogLevel::Trace => LevelFilter::Trace,
+        }
+    }
+}
+
+/// 后端接口
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ApiConfig {
+    /// 接口地址，为空时使用前端构建时的 `VITE_API_BASE_URL`
+    pub base_url: Option<String>,
+}
+
+/// 应用更新
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct UpdaterConfig {
+    /// 更新端点，设置后代替用户在更新设置中配置的端点
+    pub endpoint: Option<String>,
+    /// 更新通道，设置后代替用户选择的通道
+    pub channel: Option<String>,
+}
+
+/// 日志
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LogConfig {
+    pub level: LogLevel,
+}
+
+/// 系统托盘
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TrayConfig {
+    /// 托盘提示文字的标题，为空时使用语言包中的文字
+    pub title: Option<String>,
+}
+
+/// 合并后的运行时配置
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AppConfig {
+    pub api: ApiConfig,
+    pub updater: UpdaterConfig,
+    pub log: LogConfig,
+    pub tray: TrayConfig,
+}
+
+/// 一层配置，例如一个配置文件或全部环境变量
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigLayer {
+    pub source: ConfigSource,
+    /// 每一项的键名、取值和具体出处（文件路径、环境变量名或命令行参数）
+    pub values: Vec<(String, Value, String)>,
+    /// 读取这一层时产生的警告
+    pub warnings: Vec<String>,
+}
+
+impl ConfigLayer {
+    fn new(source: ConfigSource) -> Self {
+        Self {
+            source,
+            values: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// 从配置文件中读取，文件不存在时返回 `None`
+    pub fn from_file(source: ConfigSource, path: &Path) -> Option<Self> {
+        let content = fs::read(path).ok()?;
+        let origin = path.display().to_string();
+        match serde_json::from_slice::<Value>(&content) {
+            Ok(document) => Some(Self::from_json(source, &origin, &document)),
+            Err(error) => {
+                let mut layer = Self::new(source);
+                layer.warnings.push(tr(
+                    "errors.invalidConfigFile",
+                    &[("path", origin.as_str()), ("reason", &error.to_string())],
+                ));
+                Some(layer)
+            }
+        }
+    }
+
+    /// 从嵌套的 JSON 对象中读取，`null` 表示不设置
+    pub fn from_json(source: ConfigSource, origin: &str, document: &Value) -> Self {
+        let mut layer = Self::new(source);
+        let Value::Object(fields) = document else {
+            layer.warnings.push(tr(
+                "errors.invalidConfigFile",
+                &[("path", origin), ("reason", "not an object")],
+            ));
+            return layer;
+        };
+        let mut flattened = Vec::new();
+        flatten("", fields, &mut flattened);
+        for (key, value) in flattened {
+            if value.is_null() {
+                continue;
+            }
+            layer.push(key, value, origin.to_string());
+        }
+        layer
+    }
+
+    /// 从 `TAURI_BUN_VITE_` 开头的环境变量中读取，其他环境变量被忽略
+    pub fn from_env<I>(vars: I) -> Self
+    where
+        I: IntoIterator<Item = (String, String)>,
+    {
+        let mut layer = Self::new(ConfigSource::Env);
+        for (name, value) in vars {
+            if let Some(key) = KEYS.iter().find(|key| env_name(key) == name) {
+                layer.push(key.to_string(), Value::String(value), name);
+            }
+        }
+        layer
+    }
+
+    /// 从 `--api-base-url=...` 形式的命令行参数中读取，`argv` 的第一项是可执行文件路径
+    ///
+    /// 只支持 `--名称=值` 的形式，避免把选项的值当作要打开的文件
+    pub fn from_args(argv: &[String]) -> Self {
+        let mut layer = Self::new(ConfigSource::Cli);
+        for arg in argv.iter().skip(1) {
+            let Some((name, value)) = arg.strip_prefix("--").and_then(|arg| arg.split_once('='))
+            else {
+                continue;
+            };
+            if let Some(key) = KEYS.iter().find(|key| flag_name(key) == name) {
+                layer.push(
+                    key.to_string(),
+                    Value::String(value.to_string()),
+                    arg.clone(),
+                );
+            }
+        }
+        layer
+    }
+
+    /// 校验后加入一项配置，不合法的值和未知的键记录为警告
+    fn push(&mut self, key: String, value: Value, origin: String) {
+        if !KEYS.contains(&key.as_str()) {
+            self.warnings.push(tr(
+                "errors.unknownConfigKey",
+                &[("key", key.as_str()), ("origin", origin.as_str())],
+            ));
+            return;
+        }
+        match validate(&key, &value) {
+            Ok(value) => self.values.push((key, value, origin)),
+            Err(reason) => self.warnings.push(tr(
+                "errors.invalidConfigValue",
+                &[
+                    ("key", key.as_str()),
+                    ("origin", origin.as_str()),
+                    ("reason", reason.as_str()),
+                ],
+            )),
+        }
+    }
+}
+
+/// 把嵌套对象展开为 `api.baseUrl` 这样的键名
+fn flatten(prefix: &str, fields: &Map<String, Value>, output: &mut Vec<(String, Value)>) {
+    for (name, value) in fields {
+        let key = if prefix.is_empty() {
+            name.clone()
+        } else {
+            format!("{}.{}", prefix, name)
+        };
+        match value {
+            Value::Object(children) => flatten(&key, children, output),
+            value => output.push((key, value.clone())),
+        }
+    }
+}
+
+/// 配置项对应的环境变量名，例如 `api.baseUrl` 对应 `TAURI_BUN_VITE_API_BASE_URL`
+pub fn env_name(key: &str) -> String {
+    let mut name = ENV_PREFIX.to_string();
+    for c in key.chars() {
+        match c {
+            '.' => name.push('_'),
+            c if c.is_ascii_uppercase() => {
+                name.push('_');
+                name.push(c);
+            }
+            c => name.push(c.to_ascii_uppercase()),
+        }
+    }
+    name
+}
+
+/// 配置项对应的命令行参数名，例如 `api.baseUrl` 对应 `api-base-url`
+pub fn flag_name(key: &str) -> String {
+    let mut name = String::new();
+    for c in key.chars() {
+        match c {
+            '.' => name.push('-'),
+            c if c.is_ascii_uppercase() => {
+                name.push('-');
+                name.push(c.to_ascii_lowercase());
+            }
+            c => name.push(c),
+        }
+    }
+    name
+}
+
+/// 校验并规范化一项配置的值
+fn validate(key: &str, value: &Value) -> Result<Value, String> {
+    let Some(text) = value.as_str().map(str::trim) else {
+        return Err("expected a string".to_string());
+    };
+    if text.is_empty() {
+        return Err("must not be empty".to_string());
+    }
+    match key {
+        "api.baseUrl" => {
+            if !text.starts_with('/') {
+                http_url(text)?;
+            }
+        }
+        // 端点中的 {{target}} 等占位符由更新插件替换
+        "updater.endpoint" => http_url(&text.replace("{{", "").replace("}}", ""))?,
+        "updater.channel" => one_of(text, CHANNELS)?,
+        "log.level" => one_of(text, &["error", "warn", "info", "debug", "trace"])?,
+        _ => {}
+    }
+    Ok(Value::String(text.to_string()))
+}
+
+fn http_url(text: &str) -> Result<(), String> {
+    let url = Url::parse(text).map_err(|error| error.to_string())?;
+    match url.scheme() {
+        "http" | "https" => Ok(()),
+        scheme => Err(format!("unsupported scheme {}", scheme)),
+    }
+}
+
+fn one_of(text: &str, allowed: &[&str]) -> Result<(), String> {
+    if allowed.contains(&text) {
+        Ok(())
+    } else {
+        Err(format!("expected one of {}", allowed.join(", ")))
+    }
+}
+
+/// 一项生效的配置及其来源
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigEntry {
+    pub key: String,
+    /// 生效的值，没有设置时为 `null`
+    pub value: Value,
+    pub source: ConfigSource,
+    /// 具体出处：文件路径、环境变量名或命令行参数，内置默认值为空
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub origin: Option<String>,
+}
+
+/// 合并后的配置
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EffectiveConfig {
+    pub config: AppConfig,
+    /// 按 [`KEYS`] 的顺序列出每一项的值和来源
+    pub entries: Vec<ConfigEntry>,
+    pub warnings: Vec<String>,
+}
+
+impl EffectiveConfig {
+    /// 某一项配置的来源
+    pub fn source_of(&self, key: &str) -> Option<ConfigSource> {
+        self.entries
+            .iter()
+            .find(|entry| entry.key == key)
+            .map(|entry| entry.source)
+    }
+}
+
+/// 与之前的配置相比取值发生变化的配置项
+pub fn changed_keys(old: &EffectiveConfig, new: &EffectiveConfig) -> Vec<String> {
+    new.entries
+        .iter()
+        .filter(|entry| {
+            !old.entries
+                .iter()
+                .any(|old| old.key == entry.key && old.value == entry.value)
+        })
+        .map(|entry| entry.key.clone())
+        .collect()
+}
+
+/// 按优先级合并各层配置，后面的层覆盖前面的层
+pub fn resolve(layers: &[ConfigLayer]) -> EffectiveConfig {
+    let mut layers: Vec<&ConfigLayer> = layers.iter().collect();
+    layers.sort_by_key(|layer| layer.source);
+
+    let defaults = serde_json::to_value(AppConfig::default()).unwrap_or_default();
+    let mut document = defaults.clone();
+    let mut entries = Vec::new();
+    let mut warnings = Vec::new();
+    for key in KEYS {
+        let mut entry = ConfigEntry {
+            key: key.to_string(),
+            value: lookup(&defaults, key),
+            source: ConfigSource::Default,
+            origin: None,
+        };
+        for layer in &layers {
+            for (name, value, origin) in &layer.values {
+                if name == key {
+                    entry.value = value.clone();
+                    entry.source = layer.source;
+                    entry.origin = Some(origin.clone());
+                }
+            }
+        }
+        assign(&mut document, key, entry.value.clone());
+        entries.push(entry);
+    }
+    for layer in &layers {
+        warnings.extend(layer.warnings.iter().cloned());
+    }
+
+    let config = serde_json::from_value(document).unwrap_or_else(|error| {
+        // 每一项都已经校验过，这里不应该失败
+        warnings.push(error.to_string());
+        AppConfig::default()
+    });
+    EffectiveConfig {
+        config,
+        entries,
+        warnings,
+    }
+}
+
+fn lookup(document: &Value, key: &str) -> Value {
+    key.split('.')
+        .try_fold(document, |value, name| value.get(name))
+        .cloned()
+        .unwrap_or(Value::Null)
+}
+
+fn assign(document: &mut Value, key: &str, value: Value) {
+    let mut target = document;
+    for name in key.split('.') {
+        target = &mut target[name];
+    }
+    *target = value;
+}
+
+/// 系统配置文件的路径：可以由 [`SYSTEM_CONFIG_ENV`] 指定，否则为
+///
+/// - Windows：`%ProgramData%\tauri-bun-vite\config.json`
+/// - macOS：`/Library/Application Support/<identifier>/config.json`
+/// - Linux：`/etc/tauri-bun-vite/config.json`
+pub fn system_config_path<R: Runtime>(app: &AppHandle<R>) -> Option<PathBuf> {
+    if let Ok(path) = std::env::var(SYSTEM_CONFIG_ENV) {
+        return Some(PathBuf::from(path));
+    }
+    let config = app.config();
+    let name = config.product_name.as_deref().unwrap_or("tauri-bun-vite");
+    system_config_dir(name, &config.identifier).map(|dir| dir.join(CONFIG_FILE))
+}
+
+#[cfg(windows)]
+fn system_config_dir(name: &str, _identifier: &str) -> Option<PathBuf> {
+    let data = std::env::var("ProgramData").unwrap_or_else(|_| r"C:\ProgramData".to_string());
+    Some(PathBuf::from(data).join(name))
+}
+
+#[cfg(target_os = "macos")]
+fn system_config_dir(_name: &str, identifier: &str) -> Option<PathBuf> {
+    Some(PathBuf::from("/Library/Application Support").join(identifier))
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn system_config_dir(name: &str, _identifier: &str) -> Option<PathBuf> {
+    Some(PathBuf::from("/etc").join(name))
+}
+
+#[cfg(not(any(windows, unix)))]
+fn system_config_dir(_name: &str, _identifier: &str) -> Option<PathBuf> {
+    None
+}
+
+/// 用户配置文件的路径
+pub fn user_config_path<R: Runtime>(app: &AppHandle<R>) -> Option<PathBuf> {
+    app.path()
+        .app_config_dir()
+        .ok()
+        .map(|dir| dir.join(CONFIG_FILE))
+}
+
+/// 按优先级合并各层配置，`changed` 中的配置文件有任何不合法的值或未知的键时返回错误
+///
+/// 用于重新加载：启动时跳过不合法的配置项，运行中的修改则整体拒绝，继续使用之前的配置。
+/// 没有修改的配置文件中原有的问题在启动时已经跳过，不会导致另一个文件的修改被拒绝
+pub fn resolve_strict(
+    layers: &[ConfigLayer],
+    changed: &[ConfigSource],
+) -> AppResult<EffectiveConfig> {
+    let problems: Vec<&str> = layers
+        .iter()
+        .filter(|layer| changed.contains(&layer.source))
+        .flat_map(|layer| layer.warnings.iter().map(String::as_str))
+        .collect();
+    if !problems.is_empty() {
+        let reason = problems.join("; ");
+        return Err(AppError::new(
+            ErrorCode::InvalidConfig,
+            tr("errors.configRejected", &[("reason", reason.as_str())]),
+        )
+        .with_details(reason));
+    }
+    Ok(resolve(layers))
+}
+
+/// 读取全部配置层
+pub fn layers<R: Runtime>(app: &AppHandle<R>) -> Vec<ConfigLayer> {
+    let mut layers = Vec::new();
+    if let Some(layer) =
+        system_config_path(app).and_then(|path| ConfigLayer::from_file(ConfigSource::System, &path))
+    {
+        layers.push(layer);
+    }
+    if let Some(layer) =
+        user_config_path(app).and_then(|path| ConfigLayer::from_file(ConfigSource::User, &path))
+    {
+        layers.push(layer);
+    }
+    layers.push(ConfigLayer::from_env(std::env::vars()));
+    let argv: Vec<String> = std::env::args().collect();
+    layers.push(ConfigLayer::from_args(&argv));
+    layers
+}
+
+/// 读取全部配置层并合并
+pub fn load<R: Runtime>(app: &AppHandle<R>) -> EffectiveConfig {
+    let effective = resolve(&layers(app));
+    for warning in &effective.warnings {
+        log::warn!("配置警告: {}", warning);
+    }
+    effective
+}
+
+/// 由 Tauri 管理的生效配置
+pub struct ConfigState(pub Mutex<EffectiveConfig>);
+
+impl ConfigState {
+    pub fn new(config: EffectiveConfig) -> Self {
+        Self(Mutex::new(config))
+    }
+
+    pub fn get(&self) -> EffectiveConfig {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+/// 读取配置并交给 Tauri 管理
+pub fn init<R: Runtime>(app: &AppHandle<R>) {
+    let effective = load(app);
+    logger::set_level(effective.config.log.level.into());
+    app.manage(ConfigState::new(effective));
+}
+
+/// 重新加载后发生的变化
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigChange {
+    /// 取值发生变化的配置项
+    pub changed: Vec<String>,
+    pub config: AppConfig,
+}
+
+/// 配置文件被修改后重新读取全部配置层，把变化的配置项应用到正在运行的模块，返回变化的配置项
+///
+/// `changed` 是被修改的配置文件的来源，其中有无效的内容时返回错误，继续使用之前的配置
+pub fn reload<R: Runtime>(app: &AppHandle<R>, changed: &[ConfigSource]) -> AppResult<Vec<String>> {
+    let effective = resolve_strict(&layers(app), changed)?;
+    let changed = {
+        let state = app.state::<ConfigState>();
+        let mut current = state.0.lock().unwrap();
+        let changed = changed_keys(&current, &effective);
+        *current = effective.clone();
+        changed
+    };
+    if !changed.is_empty() {
+        apply(app, effective.config, changed.clone())?;
+    }
+    Ok(changed)
+}
+
+/// 应用变化的配置项：
+///
+/// - `log.level` 立即修改日志输出的级别
+/// - `tray.title` 立即更新托盘的提示文字
+/// - `updater.endpoint` 和 `updater.channel` 在下次检查更新时通过 `updater::config::effective` 读取
+/// - `api.baseUrl` 由前端收到 [`CONFIG_CHANGED_EVENT`] 后修改请求地址
+fn apply<R: Runtime>(app: &AppHandle<R>, config: AppConfig, changed: Vec<String>) -> AppResult<()> {
+    if changed.iter().any(|key| key == "log.level") {
+        logger::set_level(config.log.level.into());
+    }
+    #[cfg(desktop)]
+    if changed.iter().any(|key| key == "tray.title") {
+        let title = config.tray.title.clone();
+        if let Err(error) = crate::core::tray::status::update(app, |status| status.title = title) {
+            log::warn!("更新托盘标题失败: {}", error);
+        }
+    }
+    app.emit(CONFIG_CHANGED_EVENT, ConfigChange { changed, config })?;
+    Ok(())
+}
+
+/// 当前生效的配置，未初始化时为默认配置
+pub fn current<R: Runtime>(app: &AppHandle<R>) -> AppConfig {
+    app.try_state::<ConfigState>()
+        .map(|state| state.0.lock().unwrap().config.clone())
+        .unwrap_or_default()
+}
+
+/// 查询生效的配置以及每一项的来源
+#[tauri::command]
+pub fn get_effective_config(state: State<'_, ConfigState>) -> EffectiveConfig {
+    state.get()
+}
@@ -0,0 +1,150 @@
+//! # 配置文件热重载
+//!
+//! 管理员在已部署的机器上修改配置文件后不需要重启应用。后台任务每隔 [`POLL_INTERVAL`]
+//! 检查一次系统配置文件、用户配置文件和设置文件，内容变化后重新读取并校验：
+//!
+//! - 配置文件由 [`config::reload`] 重新合并，变化的配置项应用到日志级别、托盘等模块，
+//!   并发出 [`CONFIG_CHANGED_EVENT`](super::CONFIG_CHANGED_EVENT)
+//! - 设置文件由 [`settings::reload`] 重新读取，变化后发出 `settings-changed`
+//! - 修改无效时继续使用之前的配置，发出 [`CONFIG_REJECTED_EVENT`] 并弹出系统提示
+//!
+//! 托盘只更新配置项 `tray.title` 对应的提示文字。菜单项的文字来自 `tray-menu.json`
+//! 和语言包，修改 `tray-menu.json` 后需要重启应用或通过 `set_tray_menu` 命令生效。
+//!
+//! 配置文件很小，这里直接比较文件内容，而不是依赖系统的文件通知：
+//! 编辑器整体替换文件、网络驱动器上的文件同样可以被发现。
+
+use crate::core::config::{self, ConfigSource};
+use crate::core::locale::tr;
+use crate::core::settings;
+use crate::models::AppError;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::async_runtime::spawn;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+use tauri_plugin_dialog::{Dialog, DialogExt, MessageDialogKind};
+use tokio::time::{sleep, Duration};
+
+/// 检查文件变化的间隔
+pub const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// 修改被拒绝时发出的事件，内容为 [`ConfigRejection`]
+pub const CONFIG_REJECTED_EVENT: &str = "config://rejected";
+
+/// 被监视文件的类型，决定变化后如何重新加载
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchedFile {
+    /// 分层配置中的系统配置文件（[`ConfigSource::System`]）或用户配置文件（[`ConfigSource::User`]）
+    Config(ConfigSource),
+    /// 用户偏好设置
+    Settings,
+}
+
+/// 被监视文件的最近一次内容，文件不存在时为 `None`
+#[derive(Debug, Default)]
+pub struct FileSnapshots {
+    files: Vec<(PathBuf, WatchedFile, Option<Vec<u8>>)>,
+}
+
+impl FileSnapshots {
+    /// 开始监视文件，以当前内容作为初始状态
+    pub fn watch(&mut self, path: PathBuf, kind: WatchedFile) {
+        let content = fs::read(&path).ok();
+        self.files.push((path, kind, content));
+    }
+
+    /// 重新读取所有文件，返回内容发生变化（包括被创建或删除）的文件
+    pub fn poll(&mut self) -> Vec<(PathBuf, WatchedFile)> {
+        let mut changed = Vec::new();
+        for (path, kind, snapshot) in &mut self.files {
+            let content = fs::read(&*path).ok();
+            if content != *snapshot {
+                *snapshot = content;
+                changed.push((path.clone(), *kind));
+            }
+        }
+        changed
+    }
+}
+
+/// 被拒绝的修改
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigRejection {
+    /// 发生变化的文件
+    pub path: String,
+    pub error: AppError,
+}
+
+/// 开始在后台监视配置文件和设置文件，需要在配置和设置加载之后调用
+pub fn watch<R: Runtime>(app: &AppHandle<R>) {
+    let mut snapshots = FileSnapshots::default();
+    if let Some(path) = config::system_config_path(app) {
+        snapshots.watch(path, WatchedFile::Config(ConfigSource::System));
+    }
+    if let Some(path) = config::user_config_path(app) {
+        snapshots.watch(path, WatchedFile::Config(ConfigSource::User));
+    }
+    if let Ok(path) = settings::config_path(app) {
+        snapshots.watch(path, WatchedFile::Settings);
+    }
+    let app = app.clone();
+    spawn(async move {
+        loop {
+            sleep(POLL_INTERVAL).await;
+            check(&app, &mut snapshots);
+        }
+    });
+}
+
+/// 检查一次文件变化，重新加载发生变化的配置或设置
+///
+/// 同一次检查中多个配置文件发生变化时只重新加载一次
+pub fn check<R: Runtime>(app: &AppHandle<R>, snapshots: &mut FileSnapshots) {
+    let changed = snapshots.poll();
+    let (paths, sources): (Vec<&Path>, Vec<ConfigSource>) = changed
+        .iter()
+        .filter_map(|(path, kind)| match kind {
+            WatchedFile::Config(source) => Some((path.as_path(), *source)),
+            WatchedFile::Settings => None,
+        })
+        .unzip();
+    if let Some(path) = paths.first() {
+        match config::reload(app, &sources) {
+            Ok(keys) if !keys.is_empty() => log::info!("配置已重新加载: {}", keys.join(", ")),
+            Ok(_) => {}
+            Err(error) => reject(app, path, error),
+        }
+    }
+    if let Some((path, _)) = changed
+        .iter()
+        .find(|(_, kind)| *kind == WatchedFile::Settings)
+    {
+        match settings::reload(app) {
+            Ok(true) => log::info!("设置已重新加载"),
+            Ok(false) => {}
+            Err(error) => reject(app, path, error),
+        }
+    }
+}
+
+/// 通知前端和用户修改被拒绝，之前的配置仍然有效
+fn reject<R: Runtime>(app: &AppHandle<R>, path: &Path, error: AppError) {
+    log::warn!("{:?} 的修改无效，继续使用之前的配置: {}", path, error);
+    let rejection = ConfigRejection {
+        path: path.display().to_string(),
+        error,
+    };
+    if let Err(error) = app.emit(CONFIG_REJECTED_EVENT, &rejection) {
+        log::warn!("发送配置事件失败: {}", error);
+    }
+    // 对话框插件未注册时（例如在测试中）只发出事件
+    if app.try_state::<Dialog<R>>().is_some() {
+        app.dialog()
+            .message(rejection.error.message())
+            .title(tr("errors.configRejectedTitle", &[]))
+            .kind(MessageDialogKind::Warning)
+            .show(|_| {});
+    }
+}
@@ -0,0 +1,207 @@
+//! # 深度链接
+//!
+//! 应用注册了 [`SCHEME`] 协议，例如 `tauri-bun-vite://orders/123`。链接在 Rust 中解析为 [`DeepLink`]，
+//! 未知或格式错误的链接会被拒绝。设置和关于链接打开对应的独立窗口，其他链接显示主窗口后通过
+//! [`NAVIGATE_EVENT`] 交给主窗口的前端路由跳转。
+//!
+//! 链接有三个来源：
+//!
+//! - 冷启动：由深度链接插件的 `get_current` 读取（Windows 和 Linux 上来自命令行参数）
+//! - 应用运行时在 macOS 上打开链接：由深度链接插件的 `on_open_url` 回调传入
+//! - 应用运行时在 Windows 和 Linux 上打开链接：系统启动新进程，由单实例插件把参数转发过来
+//!
+//! 主窗口的前端加载完成之前无法响应事件，这期间收到的链接会先暂存，由前端通过 [`take_deep_links`] 取走。
+
+use crate::core::locale::tr;
+use crate::core::tray::recent::{NavigatePayload, NAVIGATE_EVENT};
+use crate::core::window::{self, registry, MAIN_WINDOW};
+use crate::models::{AppError, AppResult, ErrorCode};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+use url::Url;
+
+/// 应用注册的协议，与 tauri.conf.json5 中 plugins.deep-link 的配置保持一致
+pub const SCHEME: &str = "tauri-bun-vite";
+
+/// 支持的深度链接
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum DeepLink {
+    /// `tauri-bun-vite://` 首页
+    Home,
+    /// `tauri-bun-vite://dashboard` 和 `tauri-bun-vite://dashboard/workbench`
+    Dashboard { workbench: bool },
+    /// `tauri-bun-vite://orders/123` 订单详情
+    Order { id: u64 },
+    /// `tauri-bun-vite://settings`
+    Settings,
+    /// `tauri-bun-vite://about`
+    About,
+}
+
+impl DeepLink {
+    /// 解析链接，协议不是 [`SCHEME`]、路径未知或参数不合法时返回错误
+    pub fn parse(link: &str) -> AppResult<DeepLink> {
+        let url = Url::parse(link.trim()).map_err(|error| invalid(link).with_details(error))?;
+        if url.scheme() != SCHEME {
+            return Err(invalid(link).with_details(format!("unsupported scheme {}", url.scheme())));
+        }
+        // `tauri-bun-vite://orders/123` 中 orders 是主机名，123 是路径
+        let segments: Vec<&str> = url
+            .host_str()
+            .into_iter()
+            .chain(url.path().split('/'))
+            .filter(|segment| !segment.is_empty())
+            .collect();
+        match segments.as_slice() {
+            [] => Ok(DeepLink::Home),
+            ["dashboard"] => Ok(DeepLink::Dashboard { workbench: false }),
+            ["dashboard", "workbench"] => Ok(DeepLink::Dashboard { workbench: true }),
+            ["orders", id] => match id.parse::<u64>() {
+                Ok(id) if id > 0 => Ok(DeepLink::Order { id }),
+                _ => Err(invalid(link).with_details(format!("invalid order id {}", id))),
+            },
+            ["settings"] => Ok(DeepLink::Settings),
+            ["about"] => Ok(DeepLink::About),
+            _ => Err(AppError::new(
+                ErrorCode::InvalidDeepLink,
+                tr("errors.unknownDeepLink", &[("link", link)]),
+            )),
+        }
+    }
+
+    /// 对应的前端路由
+    pub fn route(&self) -> String {
+        match self {
+            DeepLink::Home => "/".to_string(),
+            DeepLink::Dashboard { workbench: false } => "/dashboard".to_string(),
+            DeepLink::Dashboard { workbench: true } => "/dashboard/workbench".to_string(),
+            DeepLink::Order { id } => format!("/orders/{}", id),
+            DeepLink::Settings => "/settings".to_string(),
+            DeepLink::About => "/about".to_string(),
+        }
+    }
+
+    /// 在独立窗口中打开的链接对应的窗口类型，其他链接在主窗口中跳转
+    pub fn window_kind(&self) -> Option<&'static str> {
+        match self {
+            DeepLink::Settings => Some("settings"),
+            DeepLink::About => Some("about"),
+            _ => None,
+        }
+    }
+}
+
+fn invalid(link: &str) -> AppError {
+    AppError::new(
+        ErrorCode::InvalidDeepLink,
+        tr("errors.invalidDeepLink", &[("link", link)]),
+    )
+}
+
+/// 是否是本应用的深度链接，只比较协议，不校验路径
+pub fn is_deep_link(link: &str) -> bool {
+    link.trim()
+        .get(..SCHEME.len() + 1)
+        .is_some_and(|prefix| prefix.eq_ignore_ascii_case(&format!("{}:", SCHEME)))
+}
+
+/// 主窗口的前端就绪之前收到的深度链接
+#[derive(Debug, Default)]
+pub struct DeepLinkQueue {
+    ready: bool,
+    pending: Vec<DeepLink>,
+}
+
+impl DeepLinkQueue {
+    /// 加入一个链接；前端已经就绪时返回链接，由调用方直接跳转
+    pub fn push(&mut self, link: DeepLink) -> Option<DeepLink> {
+        if self.ready {
+            Some(link)
+        } else {
+            self.pending.push(link);
+            None
+        }
+    }
+
+    /// 前端就绪，取走暂存的链接，之后的链接不再暂存
+    pub fn take(&mut self) -> Vec<DeepLink> {
+        self.ready = true;
+        std::mem::take(&mut self.pending)
+    }
+}
+
+/// 由 Tauri 管理的深度链接队列
+#[derive(Default)]
+pub struct DeepLinkState(pub Mutex<DeepLinkQueue>);
+
+/// 注册深度链接队列，处理冷启动时的链接，并监听应用运行时打开的链接
+pub fn init<R: Runtime>(app: &AppHandle<R>) {
+    use tauri_plugin_deep_link::DeepLinkExt;
+
+    app.manage(DeepLinkState::default());
+
+    // 开发时应用没有安装，需要在运行时注册协议
+    #[cfg(all(debug_assertions, any(windows, target_os = "linux")))]
+    if let Err(error) = app.deep_link().register_all() {
+        log::warn!("注册深度链接协议失败: {}", error);
+    }
+
+    match app.deep_link().get_current() {
+        Ok(Some(urls)) => {
+            let urls: Vec<String> = urls.iter().map(Url::to_string).collect();
+            open_urls(app, &urls);
+        }
+        Ok(None) => {}
+        Err(error) => log::warn!("读取启动时的深度链接失败: {}", error),
+    }
+
+    let handle = app.clone();
+    app.deep_link().on_open_url(move |event| {
+        let urls: Vec<String> = event.urls().iter().map(Url::to_string).collect();
+        open_urls(&handle, &urls);
+    });
+}
+
+/// 解析并打开链接，不合法的链接只记录日志
+pub fn open_urls<R: Runtime>(app: &AppHandle<R>, urls: &[String]) {
+    for url in urls {
+        match DeepLink::parse(url) {
+            Ok(link) => dispatch(app, link),
+            Err(error) => log::warn!("忽略深度链接 {}: {}", url, error),
+        }
+    }
+}
+
+/// 打开链接：设置和关于链接打开独立窗口，其他链接跳转到对应的路由，主窗口的前端就绪之前先暂存
+pub fn dispatch<R: Runtime>(app: &AppHandle<R>, link: DeepLink) {
+    if let Some(kind) = link.window_kind() {
+        registry::open_kind(app, kind);
+        return;
+    }
+    let Some(state) = app.try_state::<DeepLinkState>() else {
+        return;
+    };
+    let ready = state.0.lock().unwrap().push(link);
+    if let Some(link) = ready {
+        navigate(app, &link);
+    }
+}
+
+fn navigate<R: Runtime>(app: &AppHandle<R>, link: &DeepLink) {
+    window::show_main(app);
+    let payload = NavigatePayload {
+        route: link.route(),
+    };
+    if let Err(error) = app.emit_to(MAIN_WINDOW, NAVIGATE_EVENT, payload) {
+        log::warn!("打开深度链接失败: {}", error);
+    }
+}
+
+/// 主窗口的前端开始监听跳转事件后调用，取走暂存的链接对应的路由
+#[tauri::command]
+pub fn take_deep_links(state: State<'_, DeepLinkState>) -> Vec<String> {
+    let pending = state.0.lock().unwrap().take();
+    pending.iter().map(DeepLink::route).collect()
+}
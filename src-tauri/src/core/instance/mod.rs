@@ -0,0 +1,170 @@
+//! # 单实例启动参数
+//!
+//! 应用已经运行时再次启动（例如在资源管理器中双击文件、在终端中执行 `tauri-bun-vite ./a.json`），
+//! 单实例插件会把新进程的命令行参数和工作目录交给已有的实例，新进程随即退出。
+//!
+//! 这里把参数整理为 [`LaunchArgs`]：相对路径按照新进程的工作目录解析为绝对路径，
+//! URL 和命令行选项单独列出。已有实例会显示并聚焦主窗口，然后发出 [`SECOND_INSTANCE_EVENT`] 事件。
+//!
+//! 前端加载完成之前收到的参数会先暂存，前端通过 `take_launch_args` 取走暂存的参数，
+//! 之后的参数直接通过事件发送。
+//!
+//! 本应用协议的深度链接（见 [`deeplink`]）由 Rust 解析并跳转，不会出现在 [`LaunchArgs::urls`] 中。
+
+use crate::core::{deeplink, window};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+use url::Url;
+
+/// 再次启动应用时发出的事件
+pub const SECOND_INSTANCE_EVENT: &str = "app://second-instance";
+
+/// 一次启动的参数
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchArgs {
+    /// 原始的命令行参数，不包含可执行文件路径
+    pub args: Vec<String>,
+    /// 启动进程的工作目录
+    pub cwd: String,
+    /// 参数中的文件路径，已解析为绝对路径
+    pub files: Vec<String>,
+    /// 参数中的 URL，`file://` URL 会被转换为文件路径
+    pub urls: Vec<String>,
+    /// 以 `-` 开头的命令行选项
+    pub flags: Vec<String>,
+}
+
+impl LaunchArgs {
+    /// 整理命令行参数，`argv` 的第一项是可执行文件路径
+    pub fn parse(argv: &[String], cwd: &str) -> Self {
+        let args: Vec<String> = argv.iter().skip(1).cloned().collect();
+        let mut launch = LaunchArgs {
+            cwd: cwd.to_string(),
+            ..LaunchArgs::default()
+        };
+        for arg in &args {
+            let arg = arg.trim();
+            if arg.is_empty() {
+                continue;
+            }
+            if arg.starts_with('-') && arg.len() > 1 {
+                launch.flags.push(arg.to_string());
+            } else if let Some(url) = parse_url(arg) {
+                match url.to_file_path() {
+                    Ok(path) if url.scheme() == "file" => {
+                        launch.files.push(path.to_string_lossy().into_owned())
+                    }
+                    _ => launch.urls.push(url.to_string()),
+                }
+            } else {
+                launch.files.push(resolve_path(arg, cwd));
+            }
+        }
+        launch.args = args;
+        launch
+    }
+
+    /// 取出本应用协议的深度链接
+    pub fn take_deep_links(&mut self) -> Vec<String> {
+        let (links, urls): (Vec<String>, Vec<String>) = std::mem::take(&mut self.urls)
+            .into_iter()
+            .partition(|url| deeplink::is_deep_link(url));
+        self.urls = urls;
+        links
+    }
+
+    /// 没有需要前端处理的文件、URL 或选项，例如只是再次启动应用，或者只有已经处理的深度链接
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty() && self.urls.is_empty() && self.flags.is_empty()
+    }
+}
+
+/// 只把带有 `://` 的参数当作 URL，避免把 Windows 的 `C:\a.json` 识别为 `c:` 协议
+fn parse_url(arg: &str) -> Option<Url> {
+    if !arg.contains("://") {
+        return None;
+    }
+    Url::parse(arg).ok().filter(|url| url.scheme().len() > 1)
+}
+
+/// 相对路径按照启动进程的工作目录解析
+fn resolve_path(arg: &str, cwd: &str) -> String {
+    let path = Path::new(arg);
+    if path.is_absolute() || cwd.is_empty() {
+        return arg.to_string();
+    }
+    Path::new(cwd).join(path).to_string_lossy().into_owned()
+}
+
+/// 前端加载完成之前收到的启动参数
+#[derive(Debug, Default)]
+pub struct LaunchQueue {
+    ready: bool,
+    pending: Vec<LaunchArgs>,
+}
+
+impl LaunchQueue {
+    /// 加入一次启动的参数；前端已经就绪时返回参数，由调用方直接发送
+    pub fn push(&mut self, launch: LaunchArgs) -> Option<LaunchArgs> {
+        if self.ready {
+            Some(launch)
+        } else {
+            self.pending.push(launch);
+            None
+        }
+    }
+
+    /// 前端就绪，取走暂存的参数，之后的参数不再暂存
+    pub fn take(&mut self) -> Vec<LaunchArgs> {
+        self.ready = true;
+        std::mem::take(&mut self.pending)
+    }
+}
+
+/// 由 Tauri 管理的启动参数队列
+#[derive(Default)]
+pub struct LaunchState(pub Mutex<LaunchQueue>);
+
+/// 注册启动参数队列，并暂存本次启动的参数
+pub fn init<R: Runtime>(app: &AppHandle<R>) {
+    let cwd = std::env::current_dir()
+        .map(|dir| dir.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let argv: Vec<String> = std::env::args().collect();
+    let mut launch = LaunchArgs::parse(&argv, &cwd);
+    // 冷启动时的深度链接由深度链接插件读取
+    launch.take_deep_links();
+    let mut queue = LaunchQueue::default();
+    if !launch.is_empty() {
+        queue.push(launch);
+    }
+    app.manage(LaunchState(Mutex::new(queue)));
+}
+
+/// 处理再次启动：显示并聚焦主窗口，然后把参数转发给前端
+pub fn handle_second_instance<R: Runtime>(app: &AppHandle<R>, argv: Vec<String>, cwd: String) {
+    window::show_main(app);
+    let mut launch = LaunchArgs::parse(&argv, &cwd);
+    deeplink::open_urls(app, &launch.take_deep_links());
+    if launch.is_empty() {
+        return;
+    }
+    let Some(state) = app.try_state::<LaunchState>() else {
+        return;
+    };
+    let ready = state.0.lock().unwrap().push(launch);
+    if let Some(launch) = ready {
+        if let Err(error) = app.emit(SECOND_INSTANCE_EVENT, launch) {
+            log::warn!("转发启动参数失败: {}", error);
+        }
+    }
+}
+
+/// 取走前端就绪之前收到的启动参数，包括本次启动的参数
+#[tauri::command]
+pub fn take_launch_args(state: State<'_, LaunchState>) -> Vec<LaunchArgs> {
+    state.0.lock().unwrap().take()
+}
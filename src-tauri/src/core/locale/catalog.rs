@@ -0,0 +1,233 @@
+//! # 语言包
+//!
+//! Rust 端与前端共用 `src/locales/<语言>/` 下的 JSON 语言包，构建时由 `build.rs` 嵌入二进制。
+//! 文件的组织方式与前端 `src/locales/index.ts` 一致：`<语言>.json` 合并到根节点，
+//! 其他文件按路径嵌套，例如 `tray.json` 中的 `quit` 对应 `tray.quit`，
+//! `pages/home.json` 中的 `title` 对应 `pages.home.title`。
+//!
+//! 文案的格式与 vue-i18n 相同：
+//!
+//! - 插值：`"你好，{name}！"`，`{'{'}` 形式的字面量会原样输出
+//! - 复数：`"没有订单 | 1 个订单 | {count} 个订单"`，两个选项时为单数和复数，
+//!   三个选项时为零、单数和复数，`{count}` 和 `{n}` 会被替换为数量
+//!
+//! 查找顺序为指定语言、英文，都没有时返回键名本身，保证界面上不会出现空白。
+
+use super::Locale;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::OnceLock;
+
+include!(concat!(env!("OUT_DIR"), "/locales.rs"));
+
+/// 回退语言
+const FALLBACK: Locale = Locale::EnUs;
+
+/// 没有 AppHandle 的代码（例如错误类型）使用的当前语言，在 `Locale::ALL` 中的位置
+static ACTIVE: AtomicUsize = AtomicUsize::new(0);
+
+/// 设置没有 AppHandle 的代码使用的语言，切换语言时自动调用
+pub fn set_active(locale: Locale) {
+    let index = Locale::ALL.iter().position(|l| *l == locale).unwrap_or(0);
+    ACTIVE.store(index, Ordering::Relaxed);
+}
+
+/// 没有 AppHandle 的代码使用的语言
+pub fn active() -> Locale {
+    Locale::ALL
+        .get(ACTIVE.load(Ordering::Relaxed))
+        .copied()
+        .unwrap_or(FALLBACK)
+}
+
+/// 嵌入的所有语言包，按语言合并为一棵树
+fn catalogs() -> &'static HashMap<Locale, Value> {
+    static CATALOGS: OnceLock<HashMap<Locale, Value>> = OnceLock::new();
+    CATALOGS.get_or_init(|| {
+        let mut catalogs: HashMap<Locale, Value> = Locale::ALL
+            .iter()
+            .map(|locale| (*locale, Value::Object(Map::new())))
+            .collect();
+        for (tag, name, content) in CATALOG_FILES {
+            // 目录名必须是完整的语言标签，避免 `zh` 之类的目录被当作 zh-CN
+            let Some(catalog) = Locale::ALL
+                .iter()
+                .find(|locale| locale.tag() == *tag)
+                .and_then(|locale| catalogs.get_mut(locale))
+            else {
+                continue;
+            };
+            let value = match parse(content) {
+                Ok(value) => value,
+                Err(error) => {
+                    log::warn!("语言包 {}/{}.json 无效: {}", tag, name, error);
+                    continue;
+                }
+            };
+            insert(catalog, tag, name, value);
+        }
+        catalogs
+    })
+}
+
+/// 解析语言包文件，空文件视为空对象
+fn parse(content: &str) -> serde_json::Result<Value> {
+    if content.trim().is_empty() {
+        return Ok(Value::Object(Map::new()));
+    }
+    serde_json::from_str(content)
+}
+
+fn insert(catalog: &mut Value, tag: &str, name: &str, value: Value) {
+    let path: Vec<&str> = name.split('/').collect();
+    if path == [tag] {
+        merge(catalog, value);
+        return;
+    }
+    // 路径冲突在构建时已经由 build.rs 检查，这里只跳过，不会出现
+    let mut node = catalog;
+    for part in &path {
+        let Some(object) = node.as_object_mut() else {
+            log::warn!("语言包 {}/{}.json 与已有的文案冲突", tag, name);
+            return;
+        };
+        node = object
+            .entry(part.to_string())
+            .or_insert_with(|| Value::Object(Map::new()));
+    }
+    merge(node, value);
+}
+
+/// 深度合并两个 JSON 对象，后者覆盖前者
+fn merge(target: &mut Value, value: Value) {
+    match (target, value) {
+        (Value::Object(target), Value::Object(value)) => {
+            for (key, value) in value {
+                merge(
+                    target
+                        .entry(key)
+                        .or_insert_with(|| Value::Object(Map::new())),
+                    value,
+                );
+            }
+        }
+        (target, value) => *target = value,
+    }
+}
+
+fn lookup(locale: Locale, key: &str) -> Option<&'static str> {
+    key.split('.')
+        .try_fold(catalogs().get(&locale)?, |value, part| value.get(part))
+        .and_then(Value::as_str)
+}
+
+/// 查找指定语言的原始文案，键名以点分隔，例如 `tray.quit`；缺失时回退到英文
+pub fn text(locale: Locale, key: &str) -> Option<&'static str> {
+    lookup(locale, key).or_else(|| lookup(FALLBACK, key))
+}
+
+/// 翻译并替换插值参数，没有对应文案时返回键名
+pub fn translate(locale: Locale, key: &str, args: &[(&str, &str)]) -> String {
+    match text(locale, key) {
+        Some(message) => interpolate(message, args),
+        None => key.to_string(),
+    }
+}
+
+/// 按数量选择复数形式后翻译，`{count}` 和 `{n}` 会被替换为数量
+pub fn translate_plural(locale: Locale, key: &str, count: u64, args: &[(&str, &str)]) -> String {
+    let Some(message) = text(locale, key) else {
+        return key.to_string();
+    };
+    let count_text = count.to_string();
+    let mut all_args = vec![("count", count_text.as_str()), ("n", count_text.as_str())];
+    all_args.extend_from_slice(args);
+    interpolate(select_plural(message, count), &all_args)
+}
+
+/// 使用 [`active`] 语言翻译
+pub fn tr(key: &str, args: &[(&str, &str)]) -> String {
+    translate(active(), key, args)
+}
+
+/// 使用 [`active`] 语言按数量翻译
+pub fn tr_plural(key: &str, count: u64, args: &[(&str, &str)]) -> String {
+    translate_plural(active(), key, count, args)
+}
+
+/// 按 vue-i18n 的规则选择复数形式
+pub fn select_plural(message: &str, count: u64) -> &str {
+    let choices: Vec<&str> = message.split('|').map(str::trim).collect();
+    let index = match choices.len() {
+        1 => 0,
+        2 => usize::from(count != 1),
+        _ => (count as usize).min(2),
+    };
+    choices[index.min(choices.len() - 1)]
+}
+
+/// 替换 `{name}` 形式的插值，没有提供的参数原样保留
+pub fn interpolate(message: &str, args: &[(&str, &str)]) -> String {
+    let mut result = String::with_capacity(message.len());
+    let mut rest = message;
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        // 字面量中可能包含 `}`，需要找到引号后面的右括号
+        let closing = if rest[start + 1..].starts_with('\'') {
+            rest[start + 2..].find("'}").map(|end| end + 3)
+        } else {
+            rest[start..].find('}')
+        };
+        let Some(end) = closing else {
+            rest = &rest[start..];
+            break;
+        };
+        let name = rest[start + 1..start + end].trim();
+        let literal = name
+            .strip_prefix('\'')
+            .and_then(|name| name.strip_suffix('\''));
+        match (literal, args.iter().find(|(key, _)| *key == name)) {
+            (Some(literal), _) => result.push_str(literal),
+            (None, Some((_, value))) => result.push_str(value),
+            (None, None) => result.push_str(&rest[start..start + end + 1]),
+        }
+        rest = &rest[start + end + 1..];
+    }
+    result.push_str(rest);
+    result
+}
+
+/// 所有字符串文案的键名，用于检查各语言的语言包是否一致
+pub fn keys(locale: Locale) -> Vec<String> {
+    fn collect(prefix: &str, value: &Value, keys: &mut Vec<String>) {
+        match value {
+            Value::Object(map) => {
+                for (key, value) in map {
+                    let path = if prefix.is_empty() {
+                        key.clone()
+                    } else {
+                        format!("{}.{}", prefix, key)
+                    };
+                    collect(&path, value, keys);
+                }
+            }
+            _ => keys.push(prefix.to_string()),
+        }
+    }
+    let mut keys = Vec::new();
+    if let Some(catalog) = catalogs().get(&locale) {
+        collect("", catalog, &mut keys);
+    }
+    keys.sort();
+    keys
+}
+
+/// 所有嵌入的语言包文件是否都是合法的 JSON，返回无效的文件
+pub fn invalid_files() -> Vec<String> {
+    CATALOG_FILES
+        .iter()
+        .filter(|(_, _, content)| parse(content).is_err())
+        .map(|(tag, name, _)| format!("{}/{}.json", tag, name))
+        .collect()
+}
@@ -0,0 +1,185 @@
+//! # 界面语言
+//!
+//! 管理应用当前使用的语言，支持的语言与前端 `src/locales` 下的目录一一对应。
+//! 切换语言时会保存用户的选择，并发送 [`LOCALE_CHANGED_EVENT`] 事件：
+//! 前端的 vue-i18n 据此切换语言，托盘等 Rust 端的界面据此重新生成文字。
+//!
+//! 用户的选择以 JSON 格式保存在应用配置目录下的 `locale.json` 中，
+//! 没有保存过时根据系统的 `LC_ALL`/`LANG` 环境变量选择语言。
+//!
+//! Rust 端的文案与前端共用同一份语言包，见 [`catalog`]。
+
+pub mod catalog;
+
+pub use catalog::{text, tr, tr_plural, translate, translate_plural};
+
+use crate::models::{AppError, AppResult};
+use crate::utils::fs::write_atomic;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+/// 需要按语言设置标题的窗口及其标题的翻译键
+const WINDOW_TITLES: &[(&str, &str)] = &[
+    ("main", "common.windows.main"),
+    ("splashscreen", "common.windows.splashscreen"),
+];
+
+/// 语言配置文件名
+const CONFIG_FILE: &str = "locale.json";
+
+/// 切换语言后发送给前端和 Rust 端监听者的事件
+pub const LOCALE_CHANGED_EVENT: &str = "locale://changed";
+
+/// 支持的语言
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Locale {
+    #[default]
+    #[serde(rename = "en-US")]
+    EnUs,
+    #[serde(rename = "zh-CN")]
+    ZhCn,
+}
+
+impl Locale {
+    /// 所有支持的语言
+    pub const ALL: [Locale; 2] = [Locale::EnUs, Locale::ZhCn];
+
+    /// BCP 47 语言标签，与 `src/locales` 下的目录名一致
+    pub fn tag(self) -> &'static str {
+        match self {
+            Locale::EnUs => "en-US",
+            Locale::ZhCn => "zh-CN",
+        }
+    }
+
+    /// 语言自身的名称，用于语言选择菜单
+    pub fn native_name(self) -> &'static str {
+        match self {
+            Locale::EnUs => "English",
+            Locale::ZhCn => "简体中文",
+        }
+    }
+
+    /// 解析语言标签，兼容 `zh`、`zh_CN.UTF-8` 等形式
+    pub fn from_tag(tag: &str) -> Option<Locale> {
+        let language = tag
+            .split(['-', '_', '.'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match language.as_str() {
+            "en" => Some(Locale::EnUs),
+            "zh" => Some(Locale::ZhCn),
+            _ => None,
+        }
+    }
+
+    /// 根据系统环境变量选择语言
+    pub fn from_env() -> Locale {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find_map(|value| Locale::from_tag(&value))
+            .unwrap_or_default()
+    }
+}
+
+/// 持久化的语言配置
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LocaleConfig {
+    locale: Locale,
+}
+
+/// 切换语言事件的内容
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocaleChangedPayload {
+    pub locale: Locale,
+}
+
+/// 由 Tauri 管理的当前语言
+pub struct LocaleState(pub Mutex<Locale>);
+
+impl LocaleState {
+    pub fn new(locale: Locale) -> Self {
+        Self(Mutex::new(locale))
+    }
+
+    pub fn get(&self) -> Locale {
+        *self.0.lock().unwrap()
+    }
+}
+
+fn config_path<R: Runtime>(app: &AppHandle<R>) -> AppResult<PathBuf> {
+    Ok(app.path().app_config_dir()?.join(CONFIG_FILE))
+}
+
+/// 读取用户保存的语言，没有保存过或无法解析时根据系统环境选择
+pub fn load<R: Runtime>(app: &AppHandle<R>) -> Locale {
+    config_path(app)
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str::<LocaleConfig>(&content).ok())
+        .map(|config| config.locale)
+        .unwrap_or_else(Locale::from_env)
+}
+
+fn save<R: Runtime>(app: &AppHandle<R>, locale: Locale) -> AppResult<()> {
+    let path = config_path(app)?;
+    let content =
+        serde_json::to_string_pretty(&LocaleConfig { locale }).map_err(AppError::internal)?;
+    write_atomic(&path, content)?;
+    Ok(())
+}
+
+/// 加载用户保存的语言并交给 Tauri 管理，需要在创建托盘和窗口标题之前调用
+pub fn init<R: Runtime>(app: &AppHandle<R>) -> Locale {
+    let locale = load(app);
+    app.manage(LocaleState::new(locale));
+    catalog::set_active(locale);
+    apply_window_titles(app, locale);
+    locale
+}
+
+/// 当前使用的语言
+pub fn current<R: Runtime>(app: &AppHandle<R>) -> Locale {
+    app.try_state::<LocaleState>()
+        .map(|state| state.get())
+        .unwrap_or_default()
+}
+
+/// 切换语言：保存用户的选择并通知所有监听者
+pub fn set_current<R: Runtime>(app: &AppHandle<R>, locale: Locale) -> AppResult<()> {
+    save(app, locale)?;
+    *app.state::<LocaleState>().0.lock().unwrap() = locale;
+    catalog::set_active(locale);
+    apply_window_titles(app, locale);
+    app.emit(LOCALE_CHANGED_EVENT, LocaleChangedPayload { locale })?;
+    Ok(())
+}
+
+/// 按语言设置窗口标题，窗口不存在时忽略
+fn apply_window_titles<R: Runtime>(app: &AppHandle<R>, locale: Locale) {
+    for (label, key) in WINDOW_TITLES {
+        if let Some(window) = app.get_webview_window(label) {
+            let _ = window.set_title(&translate(locale, key, &[]));
+        }
+    }
+}
+
+/// 查询当前语言
+#[tauri::command]
+pub fn get_locale(state: State<'_, LocaleState>) -> Locale {
+    state.get()
+}
+
+/// 切换语言，前端切换语言时调用，保证托盘等 Rust 端界面同步
+#[tauri::command]
+pub fn set_locale<R: Runtime>(app: AppHandle<R>, locale: Locale) -> AppResult<Locale> {
+    set_current(&app, locale)?;
+    Ok(locale)
+}
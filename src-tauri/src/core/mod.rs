@@ -1,5 +1,18 @@
 mod barcodescanner;
+pub mod config;
+#[cfg(desktop)]
+pub mod deeplink;
+#[cfg(desktop)]
+pub mod instance;
+pub mod locale;
 mod nfc;
-mod splashscreen;
+#[cfg(desktop)]
+pub mod profile;
+pub mod settings;
+pub(crate) mod splashscreen;
+pub mod startup;
 pub mod tray;
-pub(crate) mod updater;
+#[cfg(desktop)]
+pub mod updater;
+#[cfg(desktop)]
+pub mod window;
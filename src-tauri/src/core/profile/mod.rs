@@ -0,0 +1,578 @@
+//! # 设置档案的导入和导出
+//!
+//! 把一台已经配置好的工作站的偏好设置迁移到另一台机器上。设置档案是一个 TOML 文件，
+//! 按分组（[`ProfileSection`]）保存界面外观、界面语言、更新通道、窗口位置和托盘菜单快捷键：
+//!
+//! ```toml
+//! format = 1
+//! appVersion = "0.1.0"
+//! exportedAt = 1760688000
+//! checksum = "sha256:9f2c..."
+//!
+//! [appearance]
+//! layout = "sidebar"
+//! themeColor = "teal"
+//! darkMode = true
+//!
+//! [language]
+//! locale = "zh-CN"
+//!
+//! [shortcuts]
+//! open = "CmdOrCtrl+O"
+//! ```
+//!
+//! - 导出时可以只选择部分分组，`checksum` 是各分组内容的 SHA-256 校验和，
+//!   用于发现损坏或被手工修改过的文件，不能证明文件的来源
+//! - 导入前先由 `preview_profile` 读取并校验文件，列出每个分组中将要变化的字段；
+//!   用户选择分组后由 `import_profile` 应用预览时读取的同一份档案，预览之后文件被修改也不会影响导入。
+//!   每个被选中的分组整体替换当前的设置
+//! - 文件通过对话框插件选择，导入和导出的命令都是异步的，不会阻塞主线程
+
+use crate::core::locale::{self, tr, Locale};
+use crate::core::settings::{self, AppearanceSettings, SettingsState};
+use crate::core::tray::{refresh_tray_menu, TrayMenuState, TRAY_ID};
+use crate::core::updater::{UpdateChannel, UpdaterState};
+use crate::core::window::geometry::{self, GeometryState, GeometryStore, WindowGeometry};
+use crate::models::{AppError, AppResult, ErrorCode};
+use crate::utils::fs::write_atomic;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_dialog::DialogExt;
+
+/// 当前的设置档案格式版本
+pub const FORMAT_VERSION: u32 = 1;
+
+/// 设置档案的扩展名
+pub const FILE_EXTENSION: &str = "toml";
+
+/// 校验和的前缀，表示使用的算法
+const CHECKSUM_PREFIX: &str = "sha256:";
+
+/// 最多保留的等待导入的预览，更早的预览需要重新打开文件
+const MAX_PREVIEWS: usize = 8;
+
+/// 设置档案中的分组
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ProfileSection {
+    /// 布局、主题颜色和暗黑模式
+    Appearance,
+    /// 界面语言
+    Language,
+    /// 更新通道
+    Updates,
+    /// 窗口的位置和大小
+    Windows,
+    /// 托盘菜单的快捷键
+    Shortcuts,
+}
+
+impl ProfileSection {
+    /// 所有分组
+    pub const ALL: [ProfileSection; 5] = [
+        ProfileSection::Appearance,
+        ProfileSection::Language,
+        ProfileSection::Updates,
+        ProfileSection::Windows,
+        ProfileSection::Shortcuts,
+    ];
+}
+
+/// 界面语言分组
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LanguageProfile {
+    pub locale: Locale,
+}
+
+/// 更新分组
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdatesProfile {
+    pub channel: UpdateChannel,
+}
+
+/// 设置档案中各分组的内容，没有导出的分组为空
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileData {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub appearance: Option<AppearanceSettings>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<LanguageProfile>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updates: Option<UpdatesProfile>,
+    /// 以窗口标签为键的位置和大小
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub windows: Option<BTreeMap<String, WindowGeometry>>,
+    /// 以托盘菜单项 id 为键的快捷键
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shortcuts: Option<BTreeMap<String, String>>,
+}
+
+impl ProfileData {
+    /// 档案中包含的分组
+    pub fn sections(&self) -> Vec<ProfileSection> {
+        ProfileSection::ALL
+            .into_iter()
+            .filter(|section| !self.section_value(*section).is_null())
+            .collect()
+    }
+
+    /// 只保留指定的分组
+    pub fn only(mut self, sections: &[ProfileSection]) -> Self {
+        let keep = |section| sections.contains(&section);
+        if !keep(ProfileSection::Appearance) {
+            self.appearance = None;
+        }
+        if !keep(ProfileSection::Language) {
+            self.language = None;
+        }
+        if !keep(ProfileSection::Updates) {
+            self.updates = None;
+        }
+        if !keep(ProfileSection::Windows) {
+            self.windows = None;
+        }
+        if !keep(ProfileSection::Shortcuts) {
+            self.shortcuts = None;
+        }
+        self
+    }
+
+    /// 分组的内容，没有该分组时为 `null`
+    pub fn section_value(&self, section: ProfileSection) -> Value {
+        let value = match section {
+            ProfileSection::Appearance => serde_json::to_value(&self.appearance),
+            ProfileSection::Language => serde_json::to_value(self.language),
+            ProfileSection::Updates => serde_json::to_value(self.updates),
+            ProfileSection::Windows => serde_json::to_value(&self.windows),
+            ProfileSection::Shortcuts => serde_json::to_value(&self.shortcuts),
+        };
+        value.unwrap_or_default()
+    }
+
+    /// 各分组内容的 SHA-256 校验和，例如 `sha256:9f2c...`
+    ///
+    /// 以紧凑的 JSON 作为规范形式计算，与 TOML 文件中的空白、注释和键的顺序无关
+    pub fn checksum(&self) -> String {
+        let canonical = serde_json::to_vec(self).unwrap_or_default();
+        let digest = Sha256::digest(&canonical);
+        let hex: String = digest.iter().map(|byte| format!("{:02x}", byte)).collect();
+        format!("{}{}", CHECKSUM_PREFIX, hex)
+    }
+}
+
+/// 设置档案文件
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Profile {
+    /// 档案的格式版本
+    pub format: u32,
+    /// 导出档案的应用版本
+    pub app_version: String,
+    /// 导出时间，Unix 时间戳（秒）
+    pub exported_at: u64,
+    /// [`ProfileData::checksum`]
+    pub checksum: String,
+    #[serde(flatten)]
+    pub data: ProfileData,
+}
+
+impl Profile {
+    pub fn new(data: ProfileData, app_version: &str, exported_at: u64) -> Self {
+        Self {
+            format: FORMAT_VERSION,
+            app_version: app_version.to_string(),
+            exported_at,
+            checksum: data.checksum(),
+            data,
+        }
+    }
+
+    /// 序列化为 TOML
+    pub fn to_toml(&self) -> AppResult<String> {
+        toml::to_string_pretty(self).map_err(AppError::internal)
+    }
+
+    /// 解析 TOML 格式的设置档案，并校验格式版本和校验和
+    pub fn parse(content: &str) -> AppResult<Self> {
+        let profile: Self = toml::from_str(content).map_err(|error| {
+            AppError::new(
+                ErrorCode::InvalidProfile,
+                tr("errors.invalidProfile", &[("reason", error.message())]),
+            )
+            .with_details(error)
+        })?;
+        if profile.format > FORMAT_VERSION {
+            return Err(AppError::new(
+                ErrorCode::InvalidProfile,
+                tr(
+                    "errors.unsupportedProfileFormat",
+                    &[
+                        ("version", &profile.format.to_string()),
+                        ("supported", &FORMAT_VERSION.to_string()),
+                    ],
+                ),
+            ));
+        }
+        let expected = profile.data.checksum();
+        if profile.checksum != expected {
+            return Err(AppError::new(
+                ErrorCode::InvalidProfile,
+                tr("errors.profileChecksumMismatch", &[]),
+            )
+            .with_details(format!("expected {}, found {}", expected, profile.checksum)));
+        }
+        Ok(profile)
+    }
+
+    /// 读取并校验设置档案文件
+    pub fn read(path: &Path) -> AppResult<Self> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+}
+
+/// 收集当前设置中指定的分组，对应的模块尚未初始化时跳过该分组
+pub fn collect<R: Runtime>(app: &AppHandle<R>, sections: &[ProfileSection]) -> ProfileData {
+    let mut data = ProfileData::default();
+    for section in sections {
+        match section {
+            ProfileSection::Appearance => {
+                data.appearance = app
+                    .try_state::<SettingsState>()
+                    .map(|state| state.get().appearance)
+            }
+            ProfileSection::Language => {
+                data.language = Some(LanguageProfile {
+                    locale: locale::current(app),
+                })
+            }
+            ProfileSection::Updates => {
+                data.updates = app.try_state::<UpdaterState>().map(|state| UpdatesProfile {
+                    channel: state.get().channel,
+                })
+            }
+            ProfileSection::Windows => {
+                // 仍然打开的窗口使用当前的位置，只读取不修改已保存的记录
+                data.windows = geometry::snapshot(app).map(|store| store.0)
+            }
+            ProfileSection::Shortcuts => {
+                data.shortcuts = app
+                    .try_state::<TrayMenuState>()
+                    .map(|state| state.get().accelerators())
+            }
+        }
+    }
+    data
+}
+
+/// 把当前设置中指定的分组导出到文件
+pub fn export_to<R: Runtime>(
+    app: &AppHandle<R>,
+    path: &Path,
+    sections: &[ProfileSection],
+) -> AppResult<Profile> {
+    let exported_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    let version = app.package_info().version.to_string();
+    let profile = Profile::new(collect(app, sections), &version, exported_at);
+    write_atomic(path, profile.to_toml()?)?;
+    Ok(profile)
+}
+
+/// 一个字段的变化，`null` 表示没有该字段
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldChange {
+    /// 分组内的字段路径，例如 `themeColor`、`main.width`
+    pub field: String,
+    pub current: Value,
+    pub imported: Value,
+}
+
+/// 一个分组导入后的变化，`changes` 为空表示与当前设置相同
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SectionPreview {
+    pub section: ProfileSection,
+    pub changes: Vec<FieldChange>,
+}
+
+/// 导入前的预览
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfilePreview {
+    /// 预览的标识，导入时用来找到预览时读取的档案
+    pub id: u64,
+    pub path: String,
+    pub app_version: String,
+    pub exported_at: u64,
+    /// 档案中包含的每个分组
+    pub sections: Vec<SectionPreview>,
+}
+
+/// 比较档案与当前设置，列出档案中每个分组将要变化的字段
+pub fn diff(current: &ProfileData, imported: &ProfileData) -> Vec<SectionPreview> {
+    imported
+        .sections()
+        .into_iter()
+        .map(|section| {
+            let mut before = BTreeMap::new();
+            let mut after = BTreeMap::new();
+            flatten("", current.section_value(section), &mut before);
+            flatten("", imported.section_value(section), &mut after);
+            let fields: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+            let changes = fields
+                .into_iter()
+                .filter_map(|field| {
+                    let current = before.get(field).cloned().unwrap_or_default();
+                    let imported = after.get(field).cloned().unwrap_or_default();
+                    (current != imported).then(|| FieldChange {
+                        field: field.clone(),
+                        current,
+                        imported,
+                    })
+                })
+                .collect();
+            SectionPreview { section, changes }
+        })
+        .collect()
+}
+
+/// 把嵌套对象展开为 `main.width` 这样的字段路径，`null` 字段被忽略
+fn flatten(prefix: &str, value: Value, output: &mut BTreeMap<String, Value>) {
+    match value {
+        Value::Object(fields) => {
+            for (name, value) in fields {
+                let path = if prefix.is_empty() {
+                    name
+                } else {
+                    format!("{}.{}", prefix, name)
+                };
+                flatten(&path, value, output);
+            }
+        }
+        Value::Null => {}
+        value => {
+            output.insert(prefix.to_string(), value);
+        }
+    }
+}
+
+/// 已经预览、等待用户选择分组后导入的档案
+#[derive(Debug, Default)]
+pub struct PendingProfiles {
+    next_id: u64,
+    profiles: BTreeMap<u64, ProfileData>,
+}
+
+impl PendingProfiles {
+    /// 保存预览的档案，返回预览的标识；超过 [`MAX_PREVIEWS`] 时丢弃最早的预览
+    pub fn insert(&mut self, data: ProfileData) -> u64 {
+        self.next_id += 1;
+        self.profiles.insert(self.next_id, data);
+        while self.profiles.len() > MAX_PREVIEWS {
+            self.profiles.pop_first();
+        }
+        self.next_id
+    }
+
+    /// 取走预览的档案，每个预览只能导入一次
+    pub fn take(&mut self, id: u64) -> Option<ProfileData> {
+        self.profiles.remove(&id)
+    }
+}
+
+/// 由 Tauri 管理的等待导入的档案
+#[derive(Default)]
+pub struct PendingProfilesState(pub Mutex<PendingProfiles>);
+
+/// 注册等待导入的档案
+pub fn init<R: Runtime>(app: &AppHandle<R>) {
+    app.manage(PendingProfilesState::default());
+}
+
+/// 读取设置档案并与当前设置比较，档案保存到 [`PendingProfilesState`] 中等待导入
+pub fn preview<R: Runtime>(app: &AppHandle<R>, path: &Path) -> AppResult<ProfilePreview> {
+    let profile = Profile::read(path)?;
+    let current = collect(app, &profile.data.sections());
+    let sections = diff(&current, &profile.data);
+    let id = app
+        .state::<PendingProfilesState>()
+        .0
+        .lock()
+        .unwrap()
+        .insert(profile.data);
+    Ok(ProfilePreview {
+        id,
+        path: path.display().to_string(),
+        app_version: profile.app_version,
+        exported_at: profile.exported_at,
+        sections,
+    })
+}
+
+/// 导入预览过的档案中被选中的分组，返回实际应用的分组
+pub fn import<R: Runtime>(
+    app: &AppHandle<R>,
+    id: u64,
+    sections: &[ProfileSection],
+) -> AppResult<Vec<ProfileSection>> {
+    let data = app
+        .state::<PendingProfilesState>()
+        .0
+        .lock()
+        .unwrap()
+        .take(id)
+        .ok_or_else(|| {
+            AppError::new(
+                ErrorCode::InvalidProfile,
+                tr("errors.profilePreviewExpired", &[]),
+            )
+            .with_details(format!("unknown preview {}", id))
+        })?;
+    apply(app, data, sections)
+}
+
+/// 应用档案中被选中的分组，返回实际应用的分组
+///
+/// 分组依次应用，某个分组失败时之前的分组已经生效
+pub fn apply<R: Runtime>(
+    app: &AppHandle<R>,
+    data: ProfileData,
+    sections: &[ProfileSection],
+) -> AppResult<Vec<ProfileSection>> {
+    let data = data.only(sections);
+    let applied = data.sections();
+    if let Some(appearance) = data.appearance {
+        let mut current = app.state::<SettingsState>().get();
+        current.appearance = appearance;
+        settings::replace(app, current)?;
+    }
+    if let Some(language) = data.language {
+        if language.locale != locale::current(app) {
+            locale::set_current(app, language.locale)?;
+        }
+    }
+    if let Some(updates) = data.updates {
+        let state = app.state::<UpdaterState>();
+        let mut current = state.get();
+        current.channel = updates.channel;
+        current.save(app)?;
+        *state.0.lock().unwrap() = current;
+    }
+    if let Some(windows) = data.windows {
+        let store = GeometryStore(windows);
+        store.save(app)?;
+        *app.state::<GeometryState>().0.lock().unwrap() = store;
+        // 已经打开的窗口立即移动到导入的位置，其他窗口下次打开时恢复
+        for window in app.webview_windows().values() {
+            if let Err(error) = geometry::restore(window) {
+                log::warn!("恢复窗口 {} 的位置失败: {}", window.label(), error);
+            }
+        }
+    }
+    if let Some(shortcuts) = data.shortcuts {
+        apply_shortcuts(app, &shortcuts)?;
+    }
+    Ok(applied)
+}
+
+/// 替换托盘菜单的快捷键，当前菜单中没有的菜单项被忽略
+fn apply_shortcuts<R: Runtime>(
+    app: &AppHandle<R>,
+    shortcuts: &BTreeMap<String, String>,
+) -> AppResult<()> {
+    let state = app.state::<TrayMenuState>();
+    let previous = state.get();
+    let mut definition = previous.clone();
+    for id in previous.accelerators().keys() {
+        definition.set_accelerator(id, None);
+    }
+    for (id, accelerator) in shortcuts {
+        if !definition.set_accelerator(id, Some(accelerator.clone())) {
+            log::warn!("托盘菜单中没有菜单项 {}，忽略它的快捷键", id);
+        }
+    }
+    // 没有托盘时也要先校验，无效的快捷键不能保存到文件中
+    definition.validate()?;
+    *state.0.lock().unwrap() = definition.clone();
+    if app.tray_by_id(TRAY_ID).is_some() {
+        if let Err(error) = refresh_tray_menu(app) {
+            // 快捷键无法解析时保留原来的菜单
+            *state.0.lock().unwrap() = previous;
+            return Err(error);
+        }
+    }
+    definition.save(app)
+}
+
+/// 默认的文件名，例如 `tauri-bun-vite-settings.toml`
+fn default_file_name<R: Runtime>(app: &AppHandle<R>) -> String {
+    format!("{}-settings.{}", app.package_info().name, FILE_EXTENSION)
+}
+
+/// 选择保存位置后导出指定的分组，返回文件路径，用户取消时返回 `None`
+#[tauri::command]
+pub async fn export_profile<R: Runtime>(
+    app: AppHandle<R>,
+    sections: Vec<ProfileSection>,
+) -> AppResult<Option<String>> {
+    let Some(path) = app
+        .dialog()
+        .file()
+        .set_title(tr("common.profile.exportTitle", &[]))
+        .add_filter(tr("common.profile.filter", &[]), &[FILE_EXTENSION])
+        .set_file_name(default_file_name(&app))
+        .blocking_save_file()
+    else {
+        return Ok(None);
+    };
+    let path = path.into_path().map_err(AppError::internal)?;
+    export_to(&app, &path, &sections)?;
+    Ok(Some(path.display().to_string()))
+}
+
+/// 预览设置档案，没有指定路径时先选择文件，用户取消时返回 `None`
+#[tauri::command]
+pub async fn preview_profile<R: Runtime>(
+    app: AppHandle<R>,
+    path: Option<String>,
+) -> AppResult<Option<ProfilePreview>> {
+    let path = match path {
+        Some(path) => path.into(),
+        None => {
+            let Some(path) = app
+                .dialog()
+                .file()
+                .set_title(tr("common.profile.importTitle", &[]))
+                .add_filter(tr("common.profile.filter", &[]), &[FILE_EXTENSION])
+                .blocking_pick_file()
+            else {
+                return Ok(None);
+            };
+            path.into_path().map_err(AppError::internal)?
+        }
+    };
+    preview(&app, &path).map(Some)
+}
+
+/// 导入 `preview_profile` 预览过的档案中被选中的分组，返回实际应用的分组
+#[tauri::command]
+pub async fn import_profile<R: Runtime>(
+    app: AppHandle<R>,
+    id: u64,
+    sections: Vec<ProfileSection>,
+) -> AppResult<Vec<ProfileSection>> {
+    import(&app, id, &sections)
+}
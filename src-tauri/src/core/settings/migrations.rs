@@ -0,0 +1,70 @@
+//! # 设置文件的版本迁移
+//!
+//! 设置文件的 `version` 字段记录结构版本，没有该字段的文件是版本 1。
+//! [`MIGRATIONS`] 中的每个函数把文档从一个版本升级到下一个版本，加载时依次执行，直到 [`SCHEMA_VERSION`]。
+//!
+//! 修改 [`Settings`](super::Settings) 的结构时：
+//!
+//! 1. 将 [`SCHEMA_VERSION`] 加一
+//! 2. 在 [`MIGRATIONS`] 末尾添加从上一个版本升级的函数
+//! 3. 在 `tests/fixtures/settings` 中添加旧版本的文件，并在 `tests/settings_tests.rs` 中验证迁移结果
+
+use serde_json::{Map, Value};
+
+/// 当前的设置文件结构版本
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// 版本号字段
+pub const VERSION_KEY: &str = "version";
+
+/// 把文档从某个版本升级到下一个版本
+pub type Migration = fn(Map<String, Value>) -> Result<Map<String, Value>, String>;
+
+/// 按版本顺序排列的迁移函数，第 i 项把版本 i + 1 升级到版本 i + 2
+pub const MIGRATIONS: &[Migration] = &[v1_to_v2];
+
+/// 文档的结构版本，没有版本号的文档是版本 1
+pub fn version_of(document: &Map<String, Value>) -> Result<u32, String> {
+    match document.get(VERSION_KEY) {
+        None => Ok(1),
+        Some(value) => value
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .filter(|version| *version >= 1)
+            .ok_or_else(|| format!("invalid version {}", value)),
+    }
+}
+
+/// 把文档升级到 [`SCHEMA_VERSION`]，返回升级后的文档和原来的版本
+///
+/// 比当前版本更新的文档来自更新版本的应用，无法降级，返回错误
+pub fn migrate(document: Value) -> Result<(Map<String, Value>, u32), String> {
+    let Value::Object(mut document) = document else {
+        return Err("settings must be an object".to_string());
+    };
+    let from = version_of(&document)?;
+    if from > SCHEMA_VERSION {
+        return Err(format!(
+            "version {} is newer than the supported version {}",
+            from, SCHEMA_VERSION
+        ));
+    }
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(from as usize - 1) {
+        document = migration(document)
+            .map_err(|error| format!("migration from version {} failed: {}", index + 1, error))?;
+    }
+    document.insert(VERSION_KEY.to_string(), Value::from(SCHEMA_VERSION));
+    Ok((document, from))
+}
+
+/// 版本 2：布局、主题颜色和暗黑模式移动到 `appearance` 分组中，为以后按分组导入导出做准备
+fn v1_to_v2(mut document: Map<String, Value>) -> Result<Map<String, Value>, String> {
+    let mut appearance = Map::new();
+    for key in ["layout", "themeColor", "darkMode"] {
+        if let Some(value) = document.remove(key) {
+            appearance.insert(key.to_string(), value);
+        }
+    }
+    document.insert("appearance".to_string(), Value::Object(appearance));
+    Ok(document)
+}
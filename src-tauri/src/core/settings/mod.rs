@@ -0,0 +1,372 @@
+//! # 用户偏好设置
+//!
+//! 布局、主题颜色、暗黑模式以及关闭/最小化到托盘等偏好由 Rust 统一保存，所有窗口共用同一份设置，
+//! 不会因为 WebView 数据目录被清理而丢失。
+//!
+//! - 设置以 JSON 格式保存在应用配置目录下的 `settings.json` 中，写入时先写临时文件再重命名
+//! - 文件带有结构版本号，旧版本的文件在加载时由 [`migrations`] 逐步升级，升级前的文件备份为
+//!   `settings.json.v<版本>.<时间戳>.bak`
+//! - 更新版本的应用保存的文件保持原样，只读取当前版本认识的字段；之后保存设置时先备份原文件
+//! - 无法解析的文件重命名为 `settings.json.corrupt.<时间戳>.bak` 后恢复为默认设置
+//! - 前端通过 `get_settings` 读取设置，通过 `update_settings` 只修改需要修改的字段
+//! - 设置变化后向所有窗口发出 [`SETTINGS_CHANGED_EVENT`] 事件
+//! - 应用运行期间直接修改设置文件后由 [`reload`] 重新读取，无效的修改被拒绝，不会重置设置文件
+
+pub mod migrations;
+
+use crate::core::locale::tr;
+use crate::models::{AppError, AppResult};
+use crate::utils::fs::write_atomic;
+use migrations::{SCHEMA_VERSION, VERSION_KEY};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+/// 设置文件名
+const CONFIG_FILE: &str = "settings.json";
+
+/// 设置变化后发出的事件，内容为完整的设置
+pub const SETTINGS_CHANGED_EVENT: &str = "settings-changed";
+
+/// 页面布局
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Layout {
+    #[default]
+    Classic,
+    Horizontal,
+    Sidebar,
+}
+
+/// 主题颜色
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ThemeColor {
+    #[default]
+    Blue,
+    Green,
+    Red,
+    Purple,
+    Orange,
+    Teal,
+    Pink,
+}
+
+/// 界面外观
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+pub struct AppearanceSettings {
+    pub layout: Layout,
+    pub theme_color: ThemeColor,
+    pub dark_mode: bool,
+}
+
+/// 窗口行为
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+pub struct WindowSettings {
+    /// 点击主窗口的关闭按钮时隐藏到托盘，而不是退出应用
+    pub close_to_tray: bool,
+    /// 最小化主窗口时隐藏到托盘，而不是最小化到任务栏
+    pub minimize_to_tray: bool,
+}
+
+/// 用户偏好设置
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+pub struct Settings {
+    pub appearance: AppearanceSettings,
+    pub window: WindowSettings,
+}
+
+/// 加载设置文件的结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoadStatus {
+    /// 文件不存在，使用默认设置
+    Missing,
+    /// 文件是当前版本
+    Loaded,
+    /// 文件从旧版本升级，升级前的文件已备份
+    Migrated { from: u32, backup: PathBuf },
+    /// 文件来自更新版本的应用，保持原样，只读取了当前版本认识的字段
+    Newer { version: u32 },
+    /// 文件无法解析，已备份并恢复为默认设置
+    Reset { reason: String, backup: PathBuf },
+}
+
+impl Settings {
+    /// 从应用配置目录加载设置，必要时升级或重置设置文件
+    pub fn load<R: Runtime>(app: &AppHandle<R>) -> Self {
+        let Ok(path) = config_path(app) else {
+            return Self::default();
+        };
+        let (settings, status) = Self::load_file(&path);
+        match status {
+            LoadStatus::Migrated { from, backup } => {
+                log::info!("设置已从版本 {} 升级，原文件备份为 {:?}", from, backup)
+            }
+            LoadStatus::Reset { reason, backup } => log::warn!(
+                "设置 {:?} 无法解析，已恢复为默认设置，原文件备份为 {:?}: {}",
+                path,
+                backup,
+                reason
+            ),
+            LoadStatus::Newer { version } => log::info!(
+                "设置 {:?} 的版本 {} 比当前支持的版本 {} 更新，只读取认识的字段",
+                path,
+                version,
+                SCHEMA_VERSION
+            ),
+            LoadStatus::Missing | LoadStatus::Loaded => {}
+        }
+        settings
+    }
+
+    /// 加载设置文件：旧版本的文件升级后写回，更新版本的文件不做修改，
+    /// 无法解析的文件备份后重置为默认设置
+    pub fn load_file(path: &Path) -> (Self, LoadStatus) {
+        let Ok(content) = fs::read(path) else {
+            return (Self::default(), LoadStatus::Missing);
+        };
+        if let Some((document, version)) = newer_document(&content) {
+            return (Self::from_newer(document), LoadStatus::Newer { version });
+        }
+        match Self::parse(&content) {
+            Ok((settings, from)) if from == SCHEMA_VERSION => (settings, LoadStatus::Loaded),
+            Ok((settings, from)) => {
+                let backup = backup_path(path, &format!("v{}", from));
+                // 写回失败时升级后的设置仍然可以使用，下次保存时再写入
+                if let Err(error) = fs::copy(path, &backup)
+                    .map_err(AppError::from)
+                    .and_then(|_| settings.save_file(path))
+                {
+                    log::warn!("无法写回升级后的设置 {:?}: {}", path, error);
+                }
+                (settings, LoadStatus::Migrated { from, backup })
+            }
+            Err(reason) => {
+                let backup = backup_path(path, "corrupt");
+                let settings = Self::default();
+                if let Err(error) = fs::rename(path, &backup)
+                    .map_err(AppError::from)
+                    .and_then(|_| settings.save_file(path))
+                {
+                    log::warn!("无法重置设置文件 {:?}: {}", path, error);
+                }
+                (settings, LoadStatus::Reset { reason, backup })
+            }
+        }
+    }
+
+    /// 解析设置文件的内容，必要时升级到当前版本，返回设置和文件原来的版本
+    pub fn parse(content: &[u8]) -> Result<(Self, u32), String> {
+        serde_json::from_slice::<Value>(content)
+            .map_err(|error| error.to_string())
+            .and_then(migrations::migrate)
+            .and_then(|(document, from)| Self::from_document(document).map(|s| (s, from)))
+    }
+
+    /// 从当前版本的文档中读取设置，忽略版本号字段
+    fn from_document(mut document: Map<String, Value>) -> Result<Self, String> {
+        document.remove(VERSION_KEY);
+        serde_json::from_value(Value::Object(document)).map_err(|error| error.to_string())
+    }
+
+    /// 从更新版本的文档中读取当前版本认识的字段，取值不合法时使用默认设置
+    fn from_newer(document: Map<String, Value>) -> Self {
+        let Ok(mut known) = serde_json::to_value(Self::default()) else {
+            return Self::default();
+        };
+        overlay_known(&mut known, &Value::Object(document));
+        serde_json::from_value(known).unwrap_or_default()
+    }
+
+    /// 保存设置到应用配置目录
+    pub fn save<R: Runtime>(&self, app: &AppHandle<R>) -> AppResult<()> {
+        self.save_file(&config_path(app)?)
+    }
+
+    /// 保存设置到文件，带有当前的结构版本号。更新版本的应用保存的文件先备份，避免丢失新版本的字段
+    pub fn save_file(&self, path: &Path) -> AppResult<()> {
+        if let Some((_, version)) = fs::read(path).ok().and_then(|c| newer_document(&c)) {
+            fs::copy(path, backup_path(path, &format!("v{}", version)))?;
+        }
+        let mut document = serde_json::to_value(self).map_err(AppError::internal)?;
+        if let Value::Object(fields) = &mut document {
+            fields.insert(VERSION_KEY.to_string(), Value::from(SCHEMA_VERSION));
+        }
+        let content = serde_json::to_string_pretty(&document).map_err(AppError::internal)?;
+        write_atomic(path, content)?;
+        Ok(())
+    }
+
+    /// 在当前设置的基础上修改 `patch` 中出现的字段，例如 `{ "appearance": { "darkMode": true } }`，
+    /// 字段名未知或取值不合法时返回错误
+    pub fn patch(&self, patch: &Value) -> AppResult<Settings> {
+        if !patch.is_object() {
+            return Err(invalid("patch must be an object"));
+        }
+        let mut merged = serde_json::to_value(self).map_err(AppError::internal)?;
+        merge(&mut merged, patch);
+        serde_json::from_value(merged).map_err(|error| invalid(&error.to_string()))
+    }
+}
+
+/// 递归合并对象，`patch` 中的非对象值直接替换原来的值
+fn merge(target: &mut Value, patch: &Value) {
+    match (target, patch) {
+        (Value::Object(target), Value::Object(patch)) => {
+            for (key, value) in patch {
+                match target.get_mut(key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        target.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (target, patch) => *target = patch.clone(),
+    }
+}
+
+/// 只用 `source` 中当前结构认识的字段覆盖 `target`，忽略未知的字段
+fn overlay_known(target: &mut Value, source: &Value) {
+    match (target, source) {
+        (Value::Object(target), Value::Object(source)) => {
+            for (key, value) in target.iter_mut() {
+                if let Some(source) = source.get(key) {
+                    overlay_known(value, source);
+                }
+            }
+        }
+        (target, source) => *target = source.clone(),
+    }
+}
+
+/// 文件来自更新版本的应用时返回文档和版本号
+fn newer_document(content: &[u8]) -> Option<(Map<String, Value>, u32)> {
+    let Ok(Value::Object(document)) = serde_json::from_slice::<Value>(content) else {
+        return None;
+    };
+    let version = migrations::version_of(&document).ok()?;
+    (version > SCHEMA_VERSION).then_some((document, version))
+}
+
+/// 与设置文件同目录的备份文件，例如 `settings.json.v1.1760659200000.bak`。
+/// 文件名带有毫秒时间戳，同名文件已经存在时再加上序号，不会覆盖之前的备份
+fn backup_path(path: &Path, kind: &str) -> PathBuf {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis())
+        .unwrap_or_default();
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let mut backup = path.with_file_name(format!("{}.{}.{}.bak", file_name, kind, millis));
+    let mut index = 1;
+    while backup.exists() {
+        backup = path.with_file_name(format!("{}.{}.{}-{}.bak", file_name, kind, millis, index));
+        index += 1;
+    }
+    backup
+}
+
+fn invalid(reason: &str) -> AppError {
+    AppError::invalid_argument(tr("errors.invalidSettings", &[("reason", reason)]))
+        .with_details(reason)
+}
+
+/// 设置文件的路径
+pub fn config_path<R: Runtime>(app: &AppHandle<R>) -> AppResult<PathBuf> {
+    Ok(app.path().app_config_dir()?.join(CONFIG_FILE))
+}
+
+/// 由 Tauri 管理的设置
+pub struct SettingsState(pub Mutex<Settings>);
+
+impl SettingsState {
+    pub fn new(settings: Settings) -> Self {
+        Self(Mutex::new(settings))
+    }
+
+    pub fn get(&self) -> Settings {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+/// 加载设置并交给 Tauri 管理
+pub fn init<R: Runtime>(app: &AppHandle<R>) {
+    app.manage(SettingsState::new(Settings::load(app)));
+}
+
+/// 替换全部设置：保存后通知所有窗口。设置没有变化时不写文件也不发出事件
+pub fn replace<R: Runtime>(app: &AppHandle<R>, settings: Settings) -> AppResult<Settings> {
+    let state = app.state::<SettingsState>();
+    let mut current = state.0.lock().unwrap();
+    if settings == *current {
+        return Ok(settings);
+    }
+    settings.save(app)?;
+    *current = settings.clone();
+    drop(current);
+    app.emit(SETTINGS_CHANGED_EVENT, &settings)?;
+    Ok(settings)
+}
+
+/// 修改设置：校验后保存，并通知所有窗口
+pub fn update<R: Runtime>(app: &AppHandle<R>, patch: &Value) -> AppResult<Settings> {
+    let settings = app.state::<SettingsState>().get().patch(patch)?;
+    replace(app, settings)
+}
+
+/// 恢复默认设置，原来的设置文件备份为 `settings.json.reset.<时间戳>.bak`
+pub fn reset<R: Runtime>(app: &AppHandle<R>) -> AppResult<Settings> {
+    let path = config_path(app)?;
+    if path.exists() {
+        fs::copy(&path, backup_path(&path, "reset"))?;
+    }
+    let settings = Settings::default();
+    settings.save(app)?;
+    *app.state::<SettingsState>().0.lock().unwrap() = settings.clone();
+    app.emit(SETTINGS_CHANGED_EVENT, &settings)?;
+    Ok(settings)
+}
+
+/// 设置文件被直接修改后重新读取，设置发生变化时通知所有窗口并返回 `true`
+///
+/// 与启动时的加载不同，无法解析的文件不会被备份和重置，而是返回错误并继续使用当前的设置。
+/// 应用自己保存的设置与当前设置相同，不会重复发出事件
+pub fn reload<R: Runtime>(app: &AppHandle<R>) -> AppResult<bool> {
+    let Ok(content) = fs::read(config_path(app)?) else {
+        return Ok(false);
+    };
+    let (settings, _) = Settings::parse(&content).map_err(|reason| invalid(&reason))?;
+    let state = app.state::<SettingsState>();
+    let mut current = state.0.lock().unwrap();
+    if settings == *current {
+        return Ok(false);
+    }
+    *current = settings.clone();
+    drop(current);
+    app.emit(SETTINGS_CHANGED_EVENT, &settings)?;
+    Ok(true)
+}
+
+#[tauri::command]
+pub fn get_settings(state: State<'_, SettingsState>) -> Settings {
+    state.get()
+}
+
+/// 修改部分设置，例如 `{ "appearance": { "darkMode": true } }`，返回修改后的完整设置
+#[tauri::command]
+pub fn update_settings<R: Runtime>(app: AppHandle<R>, patch: Value) -> AppResult<Settings> {
+    update(&app, &patch)
+}
+
+/// 恢复默认设置
+#[tauri::command]
+pub fn reset_settings<R: Runtime>(app: AppHandle<R>) -> AppResult<Settings> {
+    reset(&app)
+}
@@ -1 +1,187 @@
+//! # 启动画面
+//!
+//! 负责启动画面窗口的完整生命周期：向 `splashscreen.html` 推送每个启动任务的进度，
+//! 在任务失败或超时时显示带有重试/退出按钮的错误状态，并保证启动画面的最短和最长显示时间。
 
+use crate::core::locale::tr;
+use crate::core::startup::{self, StartupOrchestrator};
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::async_runtime::spawn;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::time::sleep;
+
+/// 启动画面窗口标签，与 tauri.conf.json5 中的配置保持一致
+pub const SPLASH_WINDOW: &str = "splashscreen";
+
+/// 启动任务进度事件
+pub const PROGRESS_EVENT: &str = "splash://progress";
+/// 启动失败事件，启动画面收到后显示错误状态
+pub const FAILED_EVENT: &str = "splash://failed";
+/// 重试事件，启动画面收到后重新执行前端初始化任务
+pub const RETRY_EVENT: &str = "splash://retry";
+
+/// 启动画面的显示时间限制
+#[derive(Debug, Clone, Copy)]
+pub struct SplashConfig {
+    /// 最短显示时间，避免启动画面一闪而过
+    pub min_display: Duration,
+    /// 最长显示时间，超过后即使没有任务报告失败也显示错误状态
+    pub max_display: Duration,
+}
+
+impl Default for SplashConfig {
+    fn default() -> Self {
+        Self {
+            min_display: Duration::from_millis(1500),
+            max_display: Duration::from_secs(120),
+        }
+    }
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProgressPayload {
+    task: String,
+    percent: u8,
+    message: Option<String>,
+    /// 按任务权重计算的整体进度百分比
+    overall: u8,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FailurePayload {
+    task: Option<String>,
+    reason: String,
+}
+
+struct SplashState {
+    config: SplashConfig,
+    shown_at: Instant,
+    /// 每次重试递增，用于让上一轮的最长显示时间计时器失效
+    attempt: u32,
+    finished: bool,
+}
+
+/// 由 Tauri 管理的启动画面状态
+pub struct SplashScreen(Mutex<SplashState>);
+
+/// 初始化启动画面，开始计算显示时间
+pub fn init(app: &AppHandle, config: SplashConfig) {
+    app.manage(SplashScreen(Mutex::new(SplashState {
+        config,
+        shown_at: Instant::now(),
+        attempt: 0,
+        finished: false,
+    })));
+    arm_max_display(app.clone(), 0, config.max_display);
+}
+
+/// 超过最长显示时间后仍未完成启动，则显示错误状态
+fn arm_max_display(app: AppHandle, attempt: u32, max_display: Duration) {
+    spawn(async move {
+        sleep(max_display).await;
+        let expired = {
+            let state = app.state::<SplashScreen>();
+            let splash = state.0.lock().unwrap();
+            !splash.finished && splash.attempt == attempt
+        };
+        if !expired {
+            return;
+        }
+        let failure = app
+            .state::<Mutex<StartupOrchestrator>>()
+            .lock()
+            .unwrap()
+            .failure()
+            .map(|(task, reason)| (task.to_string(), reason));
+        match failure {
+            Some((task, reason)) => show_failure(&app, Some(&task), &reason),
+            None => show_failure(&app, None, &tr("errors.startupTooSlow", &[])),
+        }
+    });
+}
+
+/// 向启动画面推送任务进度
+pub fn emit_progress(
+    app: &AppHandle,
+    task: &str,
+    percent: u8,
+    message: Option<&str>,
+    overall: f32,
+) {
+    let payload = ProgressPayload {
+        task: task.to_string(),
+        percent,
+        message: message.map(str::to_string),
+        overall: (overall * 100.0).round().clamp(0.0, 100.0) as u8,
+    };
+    let _ = app.emit_to(SPLASH_WINDOW, PROGRESS_EVENT, payload);
+}
+
+/// 在启动画面上显示错误状态，由用户选择重试或退出
+pub fn show_failure(app: &AppHandle, task: Option<&str>, reason: &str) {
+    let finished = app.state::<SplashScreen>().0.lock().unwrap().finished;
+    if finished {
+        return;
+    }
+    log::warn!("启动失败: {:?} {}", task, reason);
+    let payload = FailurePayload {
+        task: task.map(str::to_string),
+        reason: reason.to_string(),
+    };
+    let _ = app.emit_to(SPLASH_WINDOW, FAILED_EVENT, payload);
+}
+
+/// 所有必需任务完成后，在满足最短显示时间的前提下关闭启动画面并显示主窗口
+pub fn finish(app: &AppHandle) {
+    let remaining = {
+        let state = app.state::<SplashScreen>();
+        let mut splash = state.0.lock().unwrap();
+        if splash.finished {
+            return;
+        }
+        splash.finished = true;
+        splash
+            .config
+            .min_display
+            .saturating_sub(splash.shown_at.elapsed())
+    };
+
+    let app = app.clone();
+    spawn(async move {
+        sleep(remaining).await;
+        // 使用桌面端条件编译
+        #[cfg(desktop)]
+        {
+            // 关闭启动画面并显示主窗口
+            if let Some(splash_window) = app.get_webview_window(SPLASH_WINDOW) {
+                let _ = splash_window.close();
+            }
+            crate::core::window::show_main(&app);
+        }
+    });
+}
+
+/// 启动画面上的“重试”按钮：重置失败的任务并重新执行
+#[tauri::command]
+pub fn splash_retry(app: AppHandle) {
+    let (attempt, max_display) = {
+        let state = app.state::<SplashScreen>();
+        let mut splash = state.0.lock().unwrap();
+        splash.attempt += 1;
+        (splash.attempt, splash.config.max_display)
+    };
+    let tasks = startup::retry(&app);
+    log::info!("重试启动任务: {:?}", tasks);
+    arm_max_display(app.clone(), attempt, max_display);
+    let _ = app.emit_to(SPLASH_WINDOW, RETRY_EVENT, tasks);
+}
+
+/// 启动画面上的“退出”按钮
+#[tauri::command]
+pub fn splash_quit(app: AppHandle) {
+    app.exit(1);
+}
@@ -0,0 +1,618 @@
+//! # 启动任务编排
+//!
+//! 任意模块都可以向 [`StartupOrchestrator`] 注册带名称的启动任务（可声明依赖、超时时间和进度权重），
+//! 前端或后端在任务结束后上报完成状态。只有当所有必需任务都完成后，才会关闭启动画面并显示主窗口。
+//!
+//! 编排器本身不依赖任何窗口，所有与时间相关的方法都接收 `now` 参数，方便在测试中直接驱动。
+
+use crate::core::locale::tr;
+use crate::core::splashscreen;
+use crate::models::AppResult;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::async_runtime::spawn;
+use tauri::{AppHandle, Manager};
+use tokio::time::sleep;
+
+/// 前端初始化任务名称，由 `splashscreen.html` 通过 `set_complete` 上报
+pub const FRONTEND_TASK: &str = "frontend";
+/// 后端初始化任务名称，由 `setup` 钩子中的异步任务上报
+pub const BACKEND_TASK: &str = "backend";
+
+/// 等待依赖任务完成时的轮询间隔
+const DEPENDENCY_POLL_INTERVAL: Duration = Duration::from_millis(50);
+/// 超时检查的轮询间隔
+const WATCHDOG_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum StartupError {
+    #[error("未知的启动任务: {0}")]
+    UnknownTask(String),
+    #[error("启动任务已注册: {0}")]
+    DuplicateTask(String),
+    #[error("启动任务 {task} 依赖未注册的任务 {dependency}")]
+    UnknownDependency { task: String, dependency: String },
+    #[error("启动任务 {task} 的依赖 {dependency} 尚未完成")]
+    DependencyNotReady { task: String, dependency: String },
+    #[error("启动任务 {task} 的依赖 {dependency} 执行失败")]
+    DependencyFailed { task: String, dependency: String },
+    #[error("启动任务 {0} 已经结束")]
+    AlreadyFinished(String),
+}
+
+pub type Result<T> = std::result::Result<T, StartupError>;
+
+/// 启动任务的描述信息
+#[derive(Debug, Clone)]
+pub struct StartupTask {
+    name: String,
+    depends_on: Vec<String>,
+    timeout: Option<Duration>,
+    weight: u32,
+    required: bool,
+}
+
+impl StartupTask {
+    /// 创建一个必需的启动任务，默认权重为 1，没有超时时间和依赖
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            depends_on: Vec::new(),
+            timeout: None,
+            weight: 1,
+            required: true,
+        }
+    }
+
+    /// 声明该任务依赖的其他任务，依赖全部完成后该任务才能开始
+    pub fn depends_on<I, S>(mut self, tasks: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.depends_on.extend(tasks.into_iter().map(Into::into));
+        self
+    }
+
+    /// 任务从开始到完成允许的最长时间
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// 任务在整体进度中所占的权重，最小为 1
+    pub fn weight(mut self, weight: u32) -> Self {
+        self.weight = weight.max(1);
+        self
+    }
+
+    /// 可选任务不会阻塞启动画面的关闭
+    pub fn optional(mut self) -> Self {
+        self.required = false;
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// 启动任务的运行状态
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", content = "reason", rename_all = "camelCase")]
+pub enum TaskStatus {
+    Pending,
+    Running,
+    Done,
+    Failed(String),
+    TimedOut,
+}
+
+impl TaskStatus {
+    /// 任务是否已经结束（无论成功与否）
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            TaskStatus::Done | TaskStatus::Failed(_) | TaskStatus::TimedOut
+        )
+    }
+}
+
+#[derive(Debug)]
+struct TaskEntry {
+    task: StartupTask,
+    status: TaskStatus,
+    percent: u8,
+    started_at: Option<Instant>,
+    /// 任务被重置重试的次数，用于忽略重试之前的执行迟到的结果
+    attempt: u32,
+}
+
+/// 启动任务编排器，需要包装在 `Mutex` 中交由 Tauri 管理
+#[derive(Debug, Default)]
+pub struct StartupOrchestrator {
+    entries: Vec<TaskEntry>,
+}
+
+impl StartupOrchestrator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 注册一个启动任务，任务名称必须唯一
+    pub fn register(&mut self, task: StartupTask) -> Result<()> {
+        if self.entry(&task.name).is_some() {
+            return Err(StartupError::DuplicateTask(task.name));
+        }
+        self.entries.push(TaskEntry {
+            task,
+            status: TaskStatus::Pending,
+            percent: 0,
+            started_at: None,
+            attempt: 0,
+        });
+        Ok(())
+    }
+
+    /// 校验所有任务的依赖都已注册，应在所有模块注册完成后调用
+    pub fn validate(&self) -> Result<()> {
+        for entry in &self.entries {
+            for dependency in &entry.task.depends_on {
+                if self.entry(dependency).is_none() {
+                    return Err(StartupError::UnknownDependency {
+                        task: entry.task.name.clone(),
+                        dependency: dependency.clone(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 查询任务状态
+    pub fn status(&self, name: &str) -> Result<&TaskStatus> {
+        self.entry(name)
+            .map(|entry| &entry.status)
+            .ok_or_else(|| StartupError::UnknownTask(name.to_string()))
+    }
+
+    /// 任务是否为必需任务
+    pub fn is_required(&self, name: &str) -> Result<bool> {
+        self.entry(name)
+            .map(|entry| entry.task.required)
+            .ok_or_else(|| StartupError::UnknownTask(name.to_string()))
+    }
+
+    /// 任务当前的重试次数，每次 [`StartupOrchestrator::reset_failed`] 后加一
+    pub fn attempt(&self, name: &str) -> Result<u32> {
+        self.entry(name)
+            .map(|entry| entry.attempt)
+            .ok_or_else(|| StartupError::UnknownTask(name.to_string()))
+    }
+
+    /// 检查任务的依赖是否全部完成；若有依赖失败则返回错误
+    pub fn dependencies_ready(&self, name: &str) -> Result<bool> {
+        let entry = self
+            .entry(name)
+            .ok_or_else(|| StartupError::UnknownTask(name.to_string()))?;
+        let mut ready = true;
+        for dependency in &entry.task.depends_on {
+            match self.status(dependency) {
+                Ok(TaskStatus::Done) => {}
+                Ok(TaskStatus::Failed(_)) | Ok(TaskStatus::TimedOut) => {
+                    return Err(StartupError::DependencyFailed {
+                        task: name.to_string(),
+                        dependency: dependency.clone(),
+                    });
+                }
+                Ok(_) => ready = false,
+                Err(_) => {
+                    return Err(StartupError::UnknownDependency {
+                        task: name.to_string(),
+                        dependency: dependency.clone(),
+                    });
+                }
+            }
+        }
+        Ok(ready)
+    }
+
+    /// 标记任务开始执行，超时时间从此刻开始计算
+    pub fn start(&mut self, name: &str, now: Instant) -> Result<()> {
+        if let Some(dependency) = self.first_unfinished_dependency(name)? {
+            return Err(StartupError::DependencyNotReady {
+                task: name.to_string(),
+                dependency,
+            });
+        }
+        let entry = self.entry_mut(name)?;
+        if entry.status.is_finished() {
+            return Err(StartupError::AlreadyFinished(name.to_string()));
+        }
+        entry.status = TaskStatus::Running;
+        entry.started_at = Some(now);
+        Ok(())
+    }
+
+    /// 标记任务完成。返回值表示是否所有必需任务都已完成
+    ///
+    /// 未调用 [`StartupOrchestrator::start`] 的任务（例如前端上报的任务）也可以直接完成，
+    /// 但它的依赖必须已经全部完成。重复完成同一个任务不会改变状态，返回 `false`，
+    /// 避免再次触发关闭启动画面。
+    pub fn complete(&mut self, name: &str) -> Result<bool> {
+        if self.status(name)? == &TaskStatus::Done {
+            return Ok(false);
+        }
+        if let Some(dependency) = self.first_unfinished_dependency(name)? {
+            return Err(StartupError::DependencyNotReady {
+                task: name.to_string(),
+                dependency,
+            });
+        }
+        let entry = self.entry_mut(name)?;
+        if entry.status.is_finished() {
+            return Err(StartupError::AlreadyFinished(name.to_string()));
+        }
+        entry.status = TaskStatus::Done;
+        entry.percent = 100;
+        Ok(self.is_complete())
+    }
+
+    /// 上报运行中任务的进度百分比，返回更新后的整体进度
+    pub fn report(&mut self, name: &str, percent: u8) -> Result<f32> {
+        let entry = self.entry_mut(name)?;
+        if entry.status.is_finished() {
+            return Err(StartupError::AlreadyFinished(name.to_string()));
+        }
+        entry.percent = percent.min(100);
+        Ok(self.progress())
+    }
+
+    /// 标记任务失败
+    pub fn fail(&mut self, name: &str, reason: impl Into<String>) -> Result<()> {
+        let entry = self.entry_mut(name)?;
+        if entry.status.is_finished() {
+            return Err(StartupError::AlreadyFinished(name.to_string()));
+        }
+        entry.status = TaskStatus::Failed(reason.into());
+        Ok(())
+    }
+
+    /// 将所有超过超时时间的运行中任务标记为超时，并返回其中必需任务的名称
+    ///
+    /// 可选任务超时不影响启动，因此不会出现在返回值中。
+    pub fn check_timeouts(&mut self, now: Instant) -> Vec<String> {
+        let mut timed_out = Vec::new();
+        for entry in &mut self.entries {
+            if entry.status != TaskStatus::Running {
+                continue;
+            }
+            if let (Some(timeout), Some(started_at)) = (entry.task.timeout, entry.started_at) {
+                if now.saturating_duration_since(started_at) >= timeout {
+                    entry.status = TaskStatus::TimedOut;
+                    if entry.task.required {
+                        timed_out.push(entry.task.name.clone());
+                    }
+                }
+            }
+        }
+        timed_out
+    }
+
+    /// 将失败或超时的任务重置为等待状态以便重试，返回被重置的任务名称
+    ///
+    /// 被重置的任务重试次数加一，之前仍在执行的旧任务结束时上报的结果会被忽略。
+    pub fn reset_failed(&mut self) -> Vec<String> {
+        let mut reset = Vec::new();
+        for entry in &mut self.entries {
+            if matches!(entry.status, TaskStatus::Failed(_) | TaskStatus::TimedOut) {
+                entry.status = TaskStatus::Pending;
+                entry.percent = 0;
+                entry.started_at = None;
+                entry.attempt += 1;
+                reset.push(entry.task.name.clone());
+            }
+        }
+        reset
+    }
+
+    /// 所有必需任务是否都已完成
+    pub fn is_complete(&self) -> bool {
+        self.entries
+            .iter()
+            .filter(|entry| entry.task.required)
+            .all(|entry| entry.status == TaskStatus::Done)
+    }
+
+    /// 是否有必需任务失败或超时
+    pub fn has_failed(&self) -> bool {
+        self.entries.iter().any(|entry| {
+            entry.task.required
+                && matches!(entry.status, TaskStatus::Failed(_) | TaskStatus::TimedOut)
+        })
+    }
+
+    /// 第一个失败或超时的必需任务及其原因
+    pub fn failure(&self) -> Option<(&str, String)> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.task.required)
+            .find_map(|entry| match &entry.status {
+                TaskStatus::Failed(reason) => Some((entry.task.name.as_str(), reason.clone())),
+                TaskStatus::TimedOut => {
+                    Some((entry.task.name.as_str(), tr("errors.taskTimedOut", &[])))
+                }
+                _ => None,
+            })
+    }
+
+    /// 按权重计算的整体进度（包含运行中任务上报的进度），取值范围 0.0 ~ 1.0
+    pub fn progress(&self) -> f32 {
+        let total: u32 = self.entries.iter().map(|entry| entry.task.weight).sum();
+        if total == 0 {
+            return 1.0;
+        }
+        let done: u32 = self
+            .entries
+            .iter()
+            .map(|entry| entry.task.weight * u32::from(entry.percent))
+            .sum();
+        done as f32 / (total * 100) as f32
+    }
+
+    /// 按注册顺序列出所有任务及其状态
+    pub fn tasks(&self) -> impl Iterator<Item = (&str, &TaskStatus)> {
+        self.entries
+            .iter()
+            .map(|entry| (entry.task.name.as_str(), &entry.status))
+    }
+
+    fn entry(&self, name: &str) -> Option<&TaskEntry> {
+        self.entries.iter().find(|entry| entry.task.name == name)
+    }
+
+    fn entry_mut(&mut self, name: &str) -> Result<&mut TaskEntry> {
+        self.entries
+            .iter_mut()
+            .find(|entry| entry.task.name == name)
+            .ok_or_else(|| StartupError::UnknownTask(name.to_string()))
+    }
+
+    fn first_unfinished_dependency(&self, name: &str) -> Result<Option<String>> {
+        if self.dependencies_ready(name)? {
+            return Ok(None);
+        }
+        let entry = self
+            .entry(name)
+            .ok_or_else(|| StartupError::UnknownTask(name.to_string()))?;
+        Ok(entry
+            .task
+            .depends_on
+            .iter()
+            .find(|dependency| !matches!(self.status(dependency), Ok(TaskStatus::Done)))
+            .cloned())
+    }
+}
+
+/// 后端启动任务的执行器，保存下来以便在启动失败后重试
+type TaskFuture = Pin<Box<dyn Future<Output = std::result::Result<(), String>> + Send>>;
+type TaskRunner = Arc<dyn Fn(AppHandle) -> TaskFuture + Send + Sync>;
+
+/// 已注册的后端启动任务执行器，由 Tauri 管理
+#[derive(Default)]
+pub struct TaskRunners(Mutex<HashMap<String, TaskRunner>>);
+
+/// 向 Tauri 管理的编排器注册一个启动任务
+pub fn register_task(app: &AppHandle, task: StartupTask) -> Result<()> {
+    app.state::<Mutex<StartupOrchestrator>>()
+        .lock()
+        .unwrap()
+        .register(task)
+}
+
+/// 以非阻塞方式执行一个后端启动任务，执行器会被保存下来，重试时再次调用
+pub fn spawn_task<F, Fut>(app: &AppHandle, name: &str, runner: F)
+where
+    F: Fn(AppHandle) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = std::result::Result<(), String>> + Send + 'static,
+{
+    let runner: TaskRunner = Arc::new(move |app| Box::pin(runner(app)));
+    app.state::<TaskRunners>()
+        .0
+        .lock()
+        .unwrap()
+        .insert(name.to_string(), runner.clone());
+    spawn_runner(app.clone(), name.to_string(), runner);
+}
+
+fn spawn_runner(app: AppHandle, name: String, runner: TaskRunner) {
+    spawn(async move {
+        let task = runner(app.clone());
+        let _ = run_task(app, &name, task).await;
+    });
+}
+
+/// 在依赖全部完成后执行一个后端启动任务，并根据执行结果上报状态
+pub async fn run_task<F, E>(app: AppHandle, name: &str, task: F) -> Result<()>
+where
+    F: Future<Output = std::result::Result<(), E>>,
+    E: std::fmt::Display,
+{
+    let state = app.state::<Mutex<StartupOrchestrator>>();
+    let attempt = state.lock().unwrap().attempt(name)?;
+
+    // 等待依赖任务完成
+    loop {
+        let ready = state.lock().unwrap().dependencies_ready(name);
+        match ready {
+            Ok(true) => break,
+            Ok(false) => sleep(DEPENDENCY_POLL_INTERVAL).await,
+            Err(error) => {
+                mark_failed(&app, name, Some(attempt), &error.to_string());
+                return Err(error);
+            }
+        }
+    }
+
+    {
+        let mut orchestrator = state.lock().unwrap();
+        if orchestrator.attempt(name)? != attempt {
+            // 等待依赖期间任务已经被重试，由新的执行负责
+            return Ok(());
+        }
+        orchestrator.start(name, Instant::now())?;
+    }
+    log::info!("启动任务 {} 开始执行...", name);
+
+    match task.await {
+        Ok(()) => mark_complete(&app, name, Some(attempt)),
+        Err(error) => {
+            mark_failed(&app, name, Some(attempt), &error.to_string());
+            Ok(())
+        }
+    }
+}
+
+/// 上报启动任务的进度，并转发给启动画面
+pub fn report_progress(
+    app: &AppHandle,
+    name: &str,
+    percent: u8,
+    message: Option<&str>,
+) -> Result<()> {
+    let overall = app
+        .state::<Mutex<StartupOrchestrator>>()
+        .lock()
+        .unwrap()
+        .report(name, percent)?;
+    splashscreen::emit_progress(app, name, percent.min(100), message, overall);
+    Ok(())
+}
+
+/// 标记任务完成，若所有必需任务均已完成则交由启动画面关闭自身并显示主窗口
+///
+/// `attempt` 为后端执行开始时的重试次数，任务在此期间被重试过时忽略这次过期的结果。
+fn mark_complete(app: &AppHandle, task: &str, attempt: Option<u32>) -> Result<()> {
+    let (all_done, overall) = {
+        let state = app.state::<Mutex<StartupOrchestrator>>();
+        let mut orchestrator = state.lock().unwrap();
+        if attempt.is_some_and(|attempt| orchestrator.attempt(task) != Ok(attempt)) {
+            log::warn!("忽略启动任务 {} 重试之前的执行结果", task);
+            return Ok(());
+        }
+        if orchestrator.status(task)? == &TaskStatus::Done {
+            // 重复上报（例如多个窗口都上报了前端任务）时什么也不做
+            return Ok(());
+        }
+        let all_done = orchestrator.complete(task)?;
+        (all_done, orchestrator.progress())
+    };
+    log::info!("设置任务 {} 已完成!", task);
+    splashscreen::emit_progress(app, task, 100, None, overall);
+
+    if all_done {
+        log::info!("所有设置任务已完成!");
+        splashscreen::finish(app);
+    }
+    Ok(())
+}
+
+/// 标记任务失败，必需任务失败时在启动画面上显示错误状态
+fn mark_failed(app: &AppHandle, task: &str, attempt: Option<u32>, reason: &str) {
+    log::warn!("启动任务 {} 执行失败: {}", task, reason);
+    let required = {
+        let state = app.state::<Mutex<StartupOrchestrator>>();
+        let mut orchestrator = state.lock().unwrap();
+        if attempt.is_some_and(|attempt| orchestrator.attempt(task) != Ok(attempt)) {
+            return;
+        }
+        // 任务可能已经被看门狗标记为超时，此时忽略重复的结束状态
+        match orchestrator.fail(task, reason) {
+            Ok(()) => orchestrator.is_required(task).unwrap_or(false),
+            Err(_) => false,
+        }
+    };
+    if required {
+        splashscreen::show_failure(app, Some(task), reason);
+    }
+}
+
+/// 启动超时看门狗，周期性地将超时的任务标记为超时，直到所有必需任务结束
+pub async fn watch_timeouts(app: AppHandle) {
+    loop {
+        sleep(WATCHDOG_INTERVAL).await;
+        let (timed_out, finished) = {
+            let state = app.state::<Mutex<StartupOrchestrator>>();
+            let mut orchestrator = state.lock().unwrap();
+            let timed_out = orchestrator.check_timeouts(Instant::now());
+            let finished = orchestrator.is_complete() || orchestrator.has_failed();
+            (timed_out, finished)
+        };
+        for task in timed_out {
+            log::warn!("启动任务 {} 执行超时!", task);
+            splashscreen::show_failure(&app, Some(&task), &tr("errors.taskTimedOut", &[]));
+        }
+        if finished {
+            break;
+        }
+    }
+}
+
+/// 重置所有失败的启动任务并重新执行，返回被重置的任务名称
+pub fn retry(app: &AppHandle) -> Vec<String> {
+    let reset = app
+        .state::<Mutex<StartupOrchestrator>>()
+        .lock()
+        .unwrap()
+        .reset_failed();
+    let runners = app.state::<TaskRunners>().0.lock().unwrap().clone();
+    for name in &reset {
+        match runners.get(name) {
+            Some(runner) => spawn_runner(app.clone(), name.clone(), runner.clone()),
+            // 没有后端执行器的任务（例如前端任务）重新开始计时，等待其再次上报
+            None => {
+                let _ = app
+                    .state::<Mutex<StartupOrchestrator>>()
+                    .lock()
+                    .unwrap()
+                    .start(name, Instant::now());
+            }
+        }
+    }
+    spawn(watch_timeouts(app.clone()));
+    reset
+}
+
+// 用于设置设置任务状态的自定义任务
+#[tauri::command]
+pub async fn set_complete(app: AppHandle, task: String) -> AppResult<()> {
+    // 未知的任务名称会以 UNKNOWN_TASK 错误码返回给前端
+    Ok(mark_complete(&app, &task, None)?)
+}
+
+/// 前端上报启动任务的进度
+#[tauri::command]
+pub async fn set_progress(
+    app: AppHandle,
+    task: String,
+    percent: u8,
+    message: Option<String>,
+) -> AppResult<()> {
+    Ok(report_progress(&app, &task, percent, message.as_deref())?)
+}
+
+/// 前端上报启动任务失败，必需任务失败时立即显示错误状态，不需要等到任务超时
+#[tauri::command]
+pub async fn set_failed(app: AppHandle, task: String, reason: String) -> AppResult<()> {
+    // 未知的任务名称会以 UNKNOWN_TASK 错误码返回给前端
+    app.state::<Mutex<StartupOrchestrator>>()
+        .lock()
+        .unwrap()
+        .status(&task)?;
+    mark_failed(&app, &task, None, &reason);
+    Ok(())
+}
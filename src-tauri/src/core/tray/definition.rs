@@ -0,0 +1,480 @@
+//! # 托盘菜单定义
+//!
+//! 托盘菜单由可序列化的树描述，可以从应用配置目录下的 `tray-menu.json` 加载，
+//! 也可以在运行时由前端通过 `set_tray_menu` 命令替换：
+//!
+//! ```json
+//! {
+//!   "items": [
+//!     { "type": "item", "id": "open", "text": "打开", "icon": "app", "accelerator": "CmdOrCtrl+O" },
+//!     { "type": "separator" },
+//!     { "type": "submenu", "text": "语言", "items": [
+//!       { "type": "check", "id": "language.en-US", "text": "English", "checked": true }
+//!     ] },
+//!     { "type": "item", "id": "quit", "text": "退出" }
+//!   ]
+//! }
+//! ```
+//!
+//! `icon` 为 `app` 时使用应用图标，否则视为资源目录下的 PNG 文件路径。
+//! `textKey` 为 `src/locales` 中的翻译键，存在时按当前语言显示文字，`text` 作为后备。
+//! id 以 `language.` 开头的勾选项组成语言单选组，勾选状态始终与当前语言一致。
+
+use crate::core::locale::{self, tr, Locale};
+use crate::models::{AppError, AppResult};
+use crate::utils::fs::write_atomic;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager, Runtime};
+
+/// 托盘菜单配置文件名
+const CONFIG_FILE: &str = "tray-menu.json";
+
+/// 语言选项的 id 前缀，完整的 id 为前缀加语言标签，例如 `language.zh-CN`
+pub const LANGUAGE_PREFIX: &str = "language.";
+
+/// 菜单树中的一个节点
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum MenuNode {
+    #[serde(rename_all = "camelCase")]
+    Item {
+        id: String,
+        text: String,
+        /// 文字的翻译键，例如 `tray.quit`，存在时 `text` 只作为缺少翻译时的后备
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        text_key: Option<String>,
+        #[serde(default = "enabled")]
+        enabled: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        accelerator: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        icon: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Check {
+        id: String,
+        text: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        text_key: Option<String>,
+        #[serde(default)]
+        checked: bool,
+        #[serde(default = "enabled")]
+        enabled: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        accelerator: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Submenu {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+        text: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        text_key: Option<String>,
+        #[serde(default = "enabled")]
+        enabled: bool,
+        items: Vec<MenuNode>,
+    },
+    Separator,
+}
+
+fn enabled() -> bool {
+    true
+}
+
+impl MenuNode {
+    pub fn item(id: &str, text: &str) -> Self {
+        MenuNode::Item {
+            id: id.to_string(),
+            text: text.to_string(),
+            text_key: None,
+            enabled: true,
+            accelerator: None,
+            icon: None,
+        }
+    }
+
+    pub fn check(id: &str, text: &str, checked: bool) -> Self {
+        MenuNode::Check {
+            id: id.to_string(),
+            text: text.to_string(),
+            text_key: None,
+            checked,
+            enabled: true,
+            accelerator: None,
+        }
+    }
+
+    pub fn submenu(text: &str, items: Vec<MenuNode>) -> Self {
+        MenuNode::Submenu {
+            id: None,
+            text: text.to_string(),
+            text_key: None,
+            enabled: true,
+            items,
+        }
+    }
+
+    /// 设置文字的翻译键
+    pub fn localized(mut self, key: &str) -> Self {
+        match &mut self {
+            MenuNode::Item { text_key, .. }
+            | MenuNode::Check { text_key, .. }
+            | MenuNode::Submenu { text_key, .. } => *text_key = Some(key.to_string()),
+            MenuNode::Separator => {}
+        }
+        self
+    }
+
+    /// 设置是否可用
+    pub fn with_enabled(mut self, value: bool) -> Self {
+        match &mut self {
+            MenuNode::Item { enabled, .. }
+            | MenuNode::Check { enabled, .. }
+            | MenuNode::Submenu { enabled, .. } => *enabled = value,
+            MenuNode::Separator => {}
+        }
+        self
+    }
+
+    /// 菜单定义中是否可用
+    pub fn is_enabled(&self) -> bool {
+        match self {
+            MenuNode::Item { enabled, .. }
+            | MenuNode::Check { enabled, .. }
+            | MenuNode::Submenu { enabled, .. } => *enabled,
+            MenuNode::Separator => false,
+        }
+    }
+
+    /// 按照指定语言显示的文字
+    pub fn display_text(&self, locale: Locale) -> &str {
+        match self {
+            MenuNode::Item { text, text_key, .. }
+            | MenuNode::Check { text, text_key, .. }
+            | MenuNode::Submenu { text, text_key, .. } => text_key
+                .as_deref()
+                .and_then(|key| locale::text(locale, key))
+                .unwrap_or(text.as_str()),
+            MenuNode::Separator => "",
+        }
+    }
+
+    pub fn id(&self) -> Option<&str> {
+        match self {
+            MenuNode::Item { id, .. } | MenuNode::Check { id, .. } => Some(id),
+            MenuNode::Submenu { id, .. } => id.as_deref(),
+            MenuNode::Separator => None,
+        }
+    }
+}
+
+/// 完整的托盘菜单定义
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrayMenuDefinition {
+    pub items: Vec<MenuNode>,
+}
+
+impl Default for TrayMenuDefinition {
+    fn default() -> Self {
+        let open = MenuNode::Item {
+            id: "open".to_string(),
+            text: "打开 Coco".to_string(),
+            text_key: Some("tray.open".to_string()),
+            enabled: true,
+            accelerator: None,
+            icon: Some("app".to_string()),
+        };
+        // 语言选项的文字使用语言自身的名称，不需要翻译
+        let languages = Locale::ALL
+            .iter()
+            .map(|locale| {
+                MenuNode::check(
+                    &format!("{}{}", LANGUAGE_PREFIX, locale.tag()),
+                    locale.native_name(),
+                    false,
+                )
+            })
+            .collect();
+        Self {
+            items: vec![
+                open,
+                MenuNode::Separator,
+                MenuNode::item("hide", "隐藏 Coco").localized("tray.hide"),
+                MenuNode::item("about", "关于 Coco").localized("tray.about"),
+                // 这两项的文字和是否可用由托盘状态决定，见 `tray::status`
+                MenuNode::item("orders", "待处理订单")
+                    .localized("tray.orders")
+                    .with_enabled(false),
+                MenuNode::item("update", "暂无可用更新")
+                    .localized("tray.noUpdate")
+                    .with_enabled(false),
+                MenuNode::submenu(
+                    "Dashboard",
+                    vec![
+                        MenuNode::item("dashboard", "仪表盘").localized("tray.dashboard"),
+                        MenuNode::item("settings", "设置").localized("tray.settings"),
+                    ],
+                )
+                .localized("tray.dashboardMenu"),
+                // 最近使用的占位子菜单，构建菜单时替换为实际的列表，见 `tray::recent`
+                MenuNode::Submenu {
+                    id: Some("recent".to_string()),
+                    text: "最近使用".to_string(),
+                    text_key: Some("tray.recent".to_string()),
+                    enabled: true,
+                    items: vec![MenuNode::item("recent.empty", "没有最近使用的内容")
+                        .localized("tray.recentEmpty")
+                        .with_enabled(false)],
+                },
+                MenuNode::submenu("语言切换", languages).localized("tray.language"),
+                MenuNode::item("settings", "设置").localized("tray.settings"),
+                MenuNode::Separator,
+                MenuNode::item("quit", "退出 Coco").localized("tray.quit"),
+            ],
+        }
+    }
+}
+
+impl TrayMenuDefinition {
+    /// 校验菜单定义：文字不能为空，子菜单不能为空，同一个 id 只能对应一种菜单项
+    ///
+    /// 同一个普通菜单项可以出现在多个位置（例如“设置”），但勾选项的 id 必须唯一，
+    /// 否则无法确定勾选状态。
+    pub fn validate(&self) -> AppResult<()> {
+        if self.items.is_empty() {
+            return Err(AppError::invalid_argument(tr("errors.emptyMenu", &[])));
+        }
+        let mut items = HashSet::new();
+        let mut checks = HashSet::new();
+        validate_nodes(&self.items, &mut items, &mut checks)
+    }
+
+    /// 按深度优先的顺序遍历所有节点
+    pub fn nodes(&self) -> Vec<&MenuNode> {
+        fn collect<'a>(nodes: &'a [MenuNode], result: &mut Vec<&'a MenuNode>) {
+            for node in nodes {
+                result.push(node);
+                if let MenuNode::Submenu { items, .. } = node {
+                    collect(items, result);
+                }
+            }
+        }
+        let mut result = Vec::new();
+        collect(&self.items, &mut result);
+        result
+    }
+
+    /// 修改勾选项的勾选状态，返回是否找到了该勾选项
+    pub fn set_checked(&mut self, target: &str, value: bool) -> bool {
+        fn visit(nodes: &mut [MenuNode], target: &str, value: bool) -> bool {
+            let mut found = false;
+            for node in nodes {
+                match node {
+                    MenuNode::Check { id, checked, .. } if id == target => {
+                        *checked = value;
+                        found = true;
+                    }
+                    MenuNode::Submenu { items, .. } => found |= visit(items, target, value),
+                    _ => {}
+                }
+            }
+            found
+        }
+        visit(&mut self.items, target, value)
+    }
+
+    /// 让语言单选组的勾选状态与指定语言一致
+    pub fn sync_language(&mut self, locale: Locale) {
+        for other in Locale::ALL {
+            let id = format!("{}{}", LANGUAGE_PREFIX, other.tag());
+            self.set_checked(&id, other == locale);
+        }
+    }
+
+    /// 勾选项当前的勾选状态
+    pub fn is_checked(&self, target: &str) -> Option<bool> {
+        self.nodes().into_iter().find_map(|node| match node {
+            MenuNode::Check { id, checked, .. } if id == target => Some(*checked),
+            _ => None,
+        })
+    }
+
+    /// 所有设置了快捷键的菜单项，以 id 为键
+    pub fn accelerators(&self) -> BTreeMap<String, String> {
+        self.nodes()
+            .into_iter()
+            .filter_map(|node| match node {
+                MenuNode::Item {
+                    id,
+                    accelerator: Some(accelerator),
+                    ..
+                }
+                | MenuNode::Check {
+                    id,
+                    accelerator: Some(accelerator),
+                    ..
+                } => Some((id.clone(), accelerator.clone())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// 修改菜单项的快捷键，`None` 表示清除，返回是否找到了该菜单项
+    pub fn set_accelerator(&mut self, target: &str, value: Option<String>) -> bool {
+        fn visit(nodes: &mut [MenuNode], target: &str, value: &Option<String>) -> bool {
+            let mut found = false;
+            for node in nodes {
+                match node {
+                    MenuNode::Item {
+                        id, accelerator, ..
+                    }
+                    | MenuNode::Check {
+                        id, accelerator, ..
+                    } if id == target => {
+                        *accelerator = value.clone();
+                        found = true;
+                    }
+                    MenuNode::Submenu { items, .. } => found |= visit(items, target, value),
+                    _ => {}
+                }
+            }
+            found
+        }
+        visit(&mut self.items, target, &value)
+    }
+
+    /// 从应用配置目录加载托盘菜单，文件不存在或无效时使用默认菜单
+    pub fn load<R: Runtime>(app: &AppHandle<R>) -> Self {
+        let Ok(path) = config_path(app) else {
+            return Self::default();
+        };
+        let Ok(content) = fs::read_to_string(&path) else {
+            return Self::default();
+        };
+        match serde_json::from_str::<Self>(&content)
+            .map_err(|error| {
+                AppError::invalid_argument(tr("errors.invalidMenu", &[])).with_details(error)
+            })
+            .and_then(|definition| definition.validate().map(|_| definition))
+        {
+            Ok(definition) => definition,
+            Err(error) => {
+                log::warn!("托盘菜单 {:?} 无效，使用默认菜单: {:?}", path, error);
+                Self::default()
+            }
+        }
+    }
+
+    /// 校验后保存到应用配置目录下的 `tray-menu.json`，下次启动时使用
+    pub fn save<R: Runtime>(&self, app: &AppHandle<R>) -> AppResult<()> {
+        self.validate()?;
+        let content = serde_json::to_string_pretty(self).map_err(AppError::internal)?;
+        write_atomic(&config_path(app)?, content)?;
+        Ok(())
+    }
+}
+
+fn validate_nodes(
+    nodes: &[MenuNode],
+    items: &mut HashSet<String>,
+    checks: &mut HashSet<String>,
+) -> AppResult<()> {
+    for node in nodes {
+        match node {
+            MenuNode::Item {
+                id,
+                text,
+                accelerator,
+                ..
+            } => {
+                validate_label(id, text)?;
+                validate_accelerator(id, accelerator.as_deref())?;
+                if checks.contains(id) {
+                    return Err(duplicate(id));
+                }
+                items.insert(id.clone());
+            }
+            MenuNode::Check {
+                id,
+                text,
+                accelerator,
+                ..
+            } => {
+                validate_label(id, text)?;
+                validate_accelerator(id, accelerator.as_deref())?;
+                if items.contains(id) || !checks.insert(id.clone()) {
+                    return Err(duplicate(id));
+                }
+            }
+            MenuNode::Submenu {
+                id,
+                text,
+                items: children,
+                ..
+            } => {
+                if text.trim().is_empty() {
+                    return Err(AppError::invalid_argument(tr(
+                        "errors.emptySubmenuText",
+                        &[],
+                    )));
+                }
+                if children.is_empty() {
+                    return Err(AppError::invalid_argument(tr(
+                        "errors.emptySubmenu",
+                        &[("text", text.as_str())],
+                    )));
+                }
+                if let Some(id) = id {
+                    if items.contains(id) || checks.contains(id) {
+                        return Err(duplicate(id));
+                    }
+                }
+                validate_nodes(children, items, checks)?;
+            }
+            MenuNode::Separator => {}
+        }
+    }
+    Ok(())
+}
+
+fn validate_label(id: &str, text: &str) -> AppResult<()> {
+    if id.trim().is_empty() {
+        return Err(AppError::invalid_argument(tr("errors.emptyMenuId", &[])));
+    }
+    if text.trim().is_empty() {
+        return Err(AppError::invalid_argument(tr(
+            "errors.emptyMenuText",
+            &[("id", id)],
+        )));
+    }
+    Ok(())
+}
+
+/// 快捷键必须能被解析，否则 Tauri 构建菜单时会直接忽略它
+fn validate_accelerator(id: &str, accelerator: Option<&str>) -> AppResult<()> {
+    let Some(accelerator) = accelerator else {
+        return Ok(());
+    };
+    accelerator
+        .parse::<muda::accelerator::Accelerator>()
+        .map(|_| ())
+        .map_err(|error| {
+            AppError::invalid_argument(tr(
+                "errors.invalidAccelerator",
+                &[("id", id), ("accelerator", accelerator)],
+            ))
+            .with_details(error)
+        })
+}
+
+fn duplicate(id: &str) -> AppError {
+    AppError::invalid_argument(tr("errors.duplicateMenuId", &[("id", id)]))
+}
+
+fn config_path<R: Runtime>(app: &AppHandle<R>) -> AppResult<PathBuf> {
+    Ok(app.path().app_config_dir()?.join(CONFIG_FILE))
+}
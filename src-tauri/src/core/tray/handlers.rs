@@ -0,0 +1,93 @@
+//! # 托盘菜单事件分发
+//!
+//! 菜单项的点击事件不再通过字符串匹配处理，而是按 id 查找注册的处理函数：
+//! 先查找完全匹配的 id，再查找最长的 id 前缀（例如 `language.` 处理所有语言选项）。
+//! 没有对应处理函数的菜单项（例如前端通过 `set_tray_menu` 添加的菜单项）会以
+//! [`TRAY_MENU_EVENT`] 事件转发给前端。
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, Runtime};
+
+/// 没有注册处理函数的菜单项被点击时发送给前端的事件
+pub const TRAY_MENU_EVENT: &str = "tray://menu";
+
+/// 菜单项处理函数，参数为被点击的菜单项 id
+pub type MenuHandler<R> = Arc<dyn Fn(&AppHandle<R>, &str) + Send + Sync>;
+
+/// 转发给前端的菜单事件
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrayMenuPayload {
+    pub id: String,
+}
+
+/// 菜单项处理函数注册表，由 Tauri 管理
+pub struct TrayHandlers<R: Runtime> {
+    exact: Mutex<HashMap<String, MenuHandler<R>>>,
+    prefixes: Mutex<Vec<(String, MenuHandler<R>)>>,
+}
+
+impl<R: Runtime> Default for TrayHandlers<R> {
+    fn default() -> Self {
+        Self {
+            exact: Mutex::new(HashMap::new()),
+            prefixes: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl<R: Runtime> TrayHandlers<R> {
+    /// 注册处理指定 id 的函数，同一个 id 重复注册时后注册的生效
+    pub fn on<F>(&self, id: &str, handler: F)
+    where
+        F: Fn(&AppHandle<R>, &str) + Send + Sync + 'static,
+    {
+        self.exact
+            .lock()
+            .unwrap()
+            .insert(id.to_string(), Arc::new(handler));
+    }
+
+    /// 注册处理所有以 `prefix` 开头的 id 的函数
+    pub fn on_prefix<F>(&self, prefix: &str, handler: F)
+    where
+        F: Fn(&AppHandle<R>, &str) + Send + Sync + 'static,
+    {
+        let mut prefixes = self.prefixes.lock().unwrap();
+        prefixes.retain(|(existing, _)| existing != prefix);
+        prefixes.push((prefix.to_string(), Arc::new(handler)));
+        // 最长的前缀优先匹配
+        prefixes.sort_by(|(a, _), (b, _)| b.len().cmp(&a.len()));
+    }
+
+    /// 查找处理指定 id 的函数
+    pub fn find(&self, id: &str) -> Option<MenuHandler<R>> {
+        if let Some(handler) = self.exact.lock().unwrap().get(id) {
+            return Some(handler.clone());
+        }
+        self.prefixes
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|(prefix, _)| id.starts_with(prefix.as_str()))
+            .map(|(_, handler)| handler.clone())
+    }
+
+    /// 分发菜单事件，返回是否找到了处理函数
+    ///
+    /// 处理函数在锁外调用，所以处理函数中可以再注册新的处理函数或者重建菜单。
+    pub fn dispatch(&self, app: &AppHandle<R>, id: &str) -> bool {
+        match self.find(id) {
+            Some(handler) => {
+                handler(app, id);
+                true
+            }
+            None => {
+                let _ = app.emit(TRAY_MENU_EVENT, TrayMenuPayload { id: id.to_string() });
+                false
+            }
+        }
+    }
+}
@@ -0,0 +1,46 @@
+//! # 托盘语言切换
+//!
+//! 托盘中的语言选项组成单选组：点击后切换应用语言，语言切换后（无论来自托盘还是前端的
+//! `set_locale` 命令）同步勾选状态，并按新的语言重新生成托盘菜单的文字和提示。
+
+use super::definition::LANGUAGE_PREFIX;
+use super::handlers::TrayHandlers;
+use super::tray_menu::{refresh_tray_menu, TrayMenuState};
+use crate::core::locale::{self, Locale, LocaleChangedPayload, LOCALE_CHANGED_EVENT};
+use tauri::{AppHandle, Listener, Manager, Runtime};
+
+/// 注册语言选项的处理函数，并监听语言切换事件
+pub fn register<R: Runtime>(app: &AppHandle<R>, handlers: &TrayHandlers<R>) {
+    handlers.on_prefix(LANGUAGE_PREFIX, |app, id| {
+        let Some(locale) = id.strip_prefix(LANGUAGE_PREFIX).and_then(Locale::from_tag) else {
+            log::warn!("未知的语言选项: {}", id);
+            return;
+        };
+        if let Err(error) = locale::set_current(app, locale) {
+            log::warn!("切换语言失败: {}", error);
+            // 原生菜单已经切换了勾选状态，恢复为当前语言
+            apply(app, locale::current(app));
+        }
+    });
+
+    let handle = app.clone();
+    app.listen(
+        LOCALE_CHANGED_EVENT,
+        move |event| match serde_json::from_str::<LocaleChangedPayload>(event.payload()) {
+            Ok(payload) => apply(&handle, payload.locale),
+            Err(error) => log::warn!("无法解析语言切换事件: {}", error),
+        },
+    );
+}
+
+/// 同步语言选项的勾选状态并重建托盘菜单
+fn apply<R: Runtime>(app: &AppHandle<R>, locale: Locale) {
+    app.state::<TrayMenuState>()
+        .0
+        .lock()
+        .unwrap()
+        .sync_language(locale);
+    if let Err(error) = refresh_tray_menu(app) {
+        log::warn!("更新托盘菜单失败: {}", error);
+    }
+}
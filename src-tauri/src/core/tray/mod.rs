@@ -1,7 +1,30 @@
+#[cfg(desktop)]
+pub mod definition;
+#[cfg(desktop)]
+pub mod handlers;
+#[cfg(desktop)]
+mod language;
+#[cfg(desktop)]
+pub mod recent;
+#[cfg(desktop)]
+pub mod status;
+#[cfg(desktop)]
 mod system_tray;
-mod tray_menu;
+#[cfg(desktop)]
+pub mod tray_menu;
 
+#[cfg(desktop)]
+pub use definition::{MenuNode, TrayMenuDefinition};
+#[cfg(desktop)]
+pub use handlers::TrayHandlers;
+#[cfg(desktop)]
+pub use recent::{QuickAction, RecentItem, RecentKind, RecentList, RecentState};
+#[cfg(desktop)]
+pub use status::{TrayIconVariant, TrayStatus, TrayStatusPatch, TrayStatusState};
 #[cfg(desktop)]
 pub use system_tray::create_system_tray;
 #[cfg(desktop)]
-pub use tray_menu::create_tray_menu;
+pub use tray_menu::{create_tray_menu, refresh_tray_menu, TrayMenuHandle, TrayMenuState};
+
+/// 系统托盘的 id
+pub const TRAY_ID: &str = "tray";
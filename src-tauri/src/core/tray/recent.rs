@@ -0,0 +1,379 @@
+//! # 最近使用和快捷操作
+//!
+//! 托盘的“最近使用”子菜单由两部分组成：固定的快捷操作，以及最近打开的订单、商品、文件等。
+//! 前端打开这些内容时通过 `add_recent_item` 记录，列表以 JSON 格式保存在应用数据目录下的
+//! `recent.json` 中。点击菜单项会显示主窗口，并向主窗口发送 [`NAVIGATE_EVENT`] 事件，由前端路由跳转。
+//!
+//! 菜单定义中 id 为 `recent` 的子菜单是占位符，构建菜单时替换为实际的列表。
+
+use super::definition::{MenuNode, TrayMenuDefinition};
+use super::handlers::TrayHandlers;
+use super::tray_menu::{refresh_tray_menu, TrayMenuState};
+use crate::core::locale::{self, tr, Locale};
+use crate::core::window::{self, MAIN_WINDOW};
+use crate::models::{AppError, AppResult};
+use crate::utils::fs::write_atomic;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+/// 最近使用列表的文件名
+const DATA_FILE: &str = "recent.json";
+
+/// 最多保留的最近使用条目数
+pub const MAX_RECENT_ITEMS: usize = 10;
+
+/// 占位子菜单的 id
+pub const RECENT_MENU_ID: &str = "recent";
+/// 快捷操作菜单项的 id 前缀
+pub const PINNED_PREFIX: &str = "recent.pinned.";
+/// 最近使用菜单项的 id 前缀
+pub const ITEM_PREFIX: &str = "recent.item.";
+/// 清除最近使用的菜单项 id
+pub const CLEAR_ID: &str = "recent.clear";
+/// 列表为空时显示的菜单项 id
+pub const EMPTY_ID: &str = "recent.empty";
+
+/// 请求前端路由跳转的事件
+pub const NAVIGATE_EVENT: &str = "app://navigate";
+
+/// 跳转事件的内容
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NavigatePayload {
+    pub route: String,
+}
+
+/// 最近使用条目的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RecentKind {
+    Order,
+    Product,
+    File,
+    Page,
+}
+
+impl RecentKind {
+    fn label_key(self) -> &'static str {
+        match self {
+            RecentKind::Order => "tray.recentKinds.order",
+            RecentKind::Product => "tray.recentKinds.product",
+            RecentKind::File => "tray.recentKinds.file",
+            RecentKind::Page => "tray.recentKinds.page",
+        }
+    }
+}
+
+/// 最近打开的一个条目
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentItem {
+    /// 唯一标识，例如 `order-1024`，同一条目再次打开时移动到最前面
+    pub id: String,
+    pub kind: RecentKind,
+    pub title: String,
+    /// 前端路由，例如 `/orders/1024`
+    pub route: String,
+}
+
+/// 固定的快捷操作
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuickAction {
+    pub id: String,
+    pub title: String,
+    /// 标题的翻译键，存在时 `title` 只作为后备
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title_key: Option<String>,
+    pub route: String,
+}
+
+impl QuickAction {
+    fn new(id: &str, title: &str, title_key: &str, route: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            title: title.to_string(),
+            title_key: Some(title_key.to_string()),
+            route: route.to_string(),
+        }
+    }
+}
+
+/// 持久化的最近使用列表和快捷操作
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentList {
+    #[serde(default)]
+    pub pinned: Vec<QuickAction>,
+    #[serde(default)]
+    pub items: Vec<RecentItem>,
+}
+
+impl Default for RecentList {
+    fn default() -> Self {
+        Self {
+            pinned: vec![
+                QuickAction::new(
+                    "dashboard",
+                    "控制台",
+                    "tray.quickActions.dashboard",
+                    "/dashboard",
+                ),
+                QuickAction::new(
+                    "workbench",
+                    "工作台",
+                    "tray.quickActions.workbench",
+                    "/dashboard/workbench",
+                ),
+            ],
+            items: Vec::new(),
+        }
+    }
+}
+
+impl RecentList {
+    /// 记录打开的条目：已存在时移动到最前面，超出上限时丢弃最旧的条目
+    pub fn push(&mut self, item: RecentItem) -> AppResult<()> {
+        validate_entry(&item.id, &item.title, &item.route)?;
+        self.items.retain(|existing| existing.id != item.id);
+        self.items.insert(0, item);
+        self.items.truncate(MAX_RECENT_ITEMS);
+        Ok(())
+    }
+
+    /// 删除条目，返回是否找到
+    pub fn remove(&mut self, id: &str) -> bool {
+        let before = self.items.len();
+        self.items.retain(|item| item.id != id);
+        before != self.items.len()
+    }
+
+    /// 替换快捷操作
+    pub fn set_pinned(&mut self, pinned: Vec<QuickAction>) -> AppResult<()> {
+        for (index, action) in pinned.iter().enumerate() {
+            validate_entry(&action.id, &action.title, &action.route)?;
+            if pinned[..index].iter().any(|other| other.id == action.id) {
+                return Err(AppError::invalid_argument(tr(
+                    "errors.duplicateMenuId",
+                    &[("id", action.id.as_str())],
+                )));
+            }
+        }
+        self.pinned = pinned;
+        Ok(())
+    }
+
+    /// 菜单项 id 对应的路由
+    pub fn route_for(&self, menu_id: &str) -> Option<&str> {
+        if let Some(id) = menu_id.strip_prefix(PINNED_PREFIX) {
+            return self
+                .pinned
+                .iter()
+                .find(|action| action.id == id)
+                .map(|action| action.route.as_str());
+        }
+        let id = menu_id.strip_prefix(ITEM_PREFIX)?;
+        self.items
+            .iter()
+            .find(|item| item.id == id)
+            .map(|item| item.route.as_str())
+    }
+
+    /// 生成子菜单中的菜单项：快捷操作、最近使用、清除
+    pub fn menu_nodes(&self, locale: Locale) -> Vec<MenuNode> {
+        let mut nodes: Vec<MenuNode> = self
+            .pinned
+            .iter()
+            .map(|action| {
+                let title = action
+                    .title_key
+                    .as_deref()
+                    .and_then(|key| locale::text(locale, key))
+                    .unwrap_or(action.title.as_str());
+                MenuNode::item(&format!("{}{}", PINNED_PREFIX, action.id), title)
+            })
+            .collect();
+        if !self.items.is_empty() {
+            if !nodes.is_empty() {
+                nodes.push(MenuNode::Separator);
+            }
+            nodes.extend(self.items.iter().map(|item| {
+                let kind = locale::translate(locale, item.kind.label_key(), &[]);
+                MenuNode::item(
+                    &format!("{}{}", ITEM_PREFIX, item.id),
+                    &format!("{} · {}", kind, item.title),
+                )
+            }));
+            nodes.push(MenuNode::Separator);
+            nodes.push(MenuNode::item(CLEAR_ID, "清除最近使用").localized("tray.recentClear"));
+        }
+        if nodes.is_empty() {
+            nodes.push(
+                MenuNode::item(EMPTY_ID, "没有最近使用的内容")
+                    .localized("tray.recentEmpty")
+                    .with_enabled(false),
+            );
+        }
+        nodes
+    }
+
+    /// 用实际的列表替换菜单定义中的占位子菜单
+    pub fn expand(&self, definition: &TrayMenuDefinition, locale: Locale) -> TrayMenuDefinition {
+        fn visit(nodes: &mut [MenuNode], replacement: &[MenuNode]) {
+            for node in nodes {
+                if let MenuNode::Submenu { id, items, .. } = node {
+                    if id.as_deref() == Some(RECENT_MENU_ID) {
+                        *items = replacement.to_vec();
+                    } else {
+                        visit(items, replacement);
+                    }
+                }
+            }
+        }
+        let mut expanded = definition.clone();
+        visit(&mut expanded.items, &self.menu_nodes(locale));
+        expanded
+    }
+
+    /// 从应用数据目录加载，文件不存在或无效时使用默认的快捷操作
+    pub fn load<R: Runtime>(app: &AppHandle<R>) -> Self {
+        data_path(app)
+            .ok()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save<R: Runtime>(&self, app: &AppHandle<R>) -> AppResult<()> {
+        let path = data_path(app)?;
+        let content = serde_json::to_string_pretty(self).map_err(AppError::internal)?;
+        write_atomic(&path, content)?;
+        Ok(())
+    }
+}
+
+fn validate_entry(id: &str, title: &str, route: &str) -> AppResult<()> {
+    if id.trim().is_empty() {
+        return Err(AppError::invalid_argument(tr("errors.emptyMenuId", &[])));
+    }
+    if title.trim().is_empty() {
+        return Err(AppError::invalid_argument(tr(
+            "errors.emptyMenuText",
+            &[("id", id)],
+        )));
+    }
+    if !route.starts_with('/') {
+        return Err(AppError::invalid_argument(tr(
+            "errors.invalidRoute",
+            &[("route", route)],
+        )));
+    }
+    Ok(())
+}
+
+fn data_path<R: Runtime>(app: &AppHandle<R>) -> AppResult<PathBuf> {
+    Ok(app.path().app_data_dir()?.join(DATA_FILE))
+}
+
+/// 由 Tauri 管理的最近使用列表
+pub struct RecentState(pub Mutex<RecentList>);
+
+impl RecentState {
+    pub fn new(list: RecentList) -> Self {
+        Self(Mutex::new(list))
+    }
+
+    pub fn get(&self) -> RecentList {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+/// 构建菜单时使用的最近使用列表，尚未加载时为空
+pub(crate) fn current<R: Runtime>(app: &AppHandle<R>) -> Option<RecentList> {
+    app.try_state::<RecentState>().map(|state| state.get())
+}
+
+/// 注册最近使用菜单项的处理函数
+pub fn register<R: Runtime>(handlers: &TrayHandlers<R>) {
+    let navigate = |app: &AppHandle<R>, id: &str| {
+        let Some(route) = current(app).and_then(|list| list.route_for(id).map(str::to_string))
+        else {
+            log::warn!("未知的最近使用菜单项: {}", id);
+            return;
+        };
+        window::show_main(app);
+        // 只让主窗口跳转，设置、关于等窗口也监听该事件
+        if let Err(error) = app.emit_to(MAIN_WINDOW, NAVIGATE_EVENT, NavigatePayload { route }) {
+            log::warn!("打开最近使用的页面失败: {}", error);
+        }
+    };
+    handlers.on_prefix(PINNED_PREFIX, navigate);
+    handlers.on_prefix(ITEM_PREFIX, navigate);
+    handlers.on(CLEAR_ID, |app, _| {
+        if let Err(error) = modify(app, |list| {
+            list.items.clear();
+            Ok(())
+        }) {
+            log::warn!("清除最近使用失败: {}", error);
+        }
+    });
+}
+
+/// 修改并保存列表，然后重建托盘菜单
+fn modify<R: Runtime, F>(app: &AppHandle<R>, f: F) -> AppResult<RecentList>
+where
+    F: FnOnce(&mut RecentList) -> AppResult<()>,
+{
+    let state = app.state::<RecentState>();
+    let mut list = state.get();
+    f(&mut list)?;
+    list.save(app)?;
+    *state.0.lock().unwrap() = list.clone();
+    // 托盘尚未创建（例如测试中）时只保存列表
+    if app.try_state::<TrayMenuState>().is_some() {
+        refresh_tray_menu(app)?;
+    }
+    Ok(list)
+}
+
+/// 查询最近使用列表和快捷操作
+#[tauri::command]
+pub fn get_recent_items(state: State<'_, RecentState>) -> RecentList {
+    state.get()
+}
+
+/// 记录最近打开的条目
+#[tauri::command]
+pub fn add_recent_item<R: Runtime>(app: AppHandle<R>, item: RecentItem) -> AppResult<RecentList> {
+    modify(&app, |list| list.push(item))
+}
+
+/// 删除最近使用的条目
+#[tauri::command]
+pub fn remove_recent_item<R: Runtime>(app: AppHandle<R>, id: String) -> AppResult<RecentList> {
+    modify(&app, |list| {
+        list.remove(&id);
+        Ok(())
+    })
+}
+
+/// 清除所有最近使用的条目，快捷操作保留
+#[tauri::command]
+pub fn clear_recent_items<R: Runtime>(app: AppHandle<R>) -> AppResult<RecentList> {
+    modify(&app, |list| {
+        list.items.clear();
+        Ok(())
+    })
+}
+
+/// 替换快捷操作
+#[tauri::command]
+pub fn set_quick_actions<R: Runtime>(
+    app: AppHandle<R>,
+    actions: Vec<QuickAction>,
+) -> AppResult<RecentList> {
+    modify(&app, |list| list.set_pinned(actions))
+}
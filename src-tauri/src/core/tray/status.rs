@@ -0,0 +1,478 @@
+//! # 托盘实时状态
+//!
+//! 托盘创建之后仍然需要反映应用的状态：有可用更新时显示“有可用更新”，
+//! 主窗口显示/隐藏时切换“打开”和“隐藏”菜单项，显示待处理订单的数量等。
+//!
+//! [`TrayStatus`] 保存这些状态以及前端对菜单项的覆盖（文字、是否可用），
+//! 修改后由 [`apply`] 直接更新已有的原生菜单项、提示文字和图标，不会重建托盘和菜单。
+//! 切换语言等需要重建菜单时，[`create_tray_menu`](super::create_tray_menu) 同样会应用这些状态。
+
+use super::definition::MenuNode;
+use super::tray_menu::{TrayMenuHandle, TrayMenuState};
+use super::TRAY_ID;
+use crate::core::locale::{self, tr, Locale};
+use crate::models::{AppError, AppResult, ErrorCode};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use tauri::image::Image;
+use tauri::menu::MenuItemKind;
+use tauri::{AppHandle, Manager, Runtime, State};
+
+/// 托盘图标的样式，非默认样式在应用图标的右上角绘制一个圆点
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TrayIconVariant {
+    #[default]
+    Normal,
+    /// 需要用户处理，例如有待处理的订单
+    Attention,
+    /// 有可用的更新
+    Update,
+}
+
+impl TrayIconVariant {
+    /// 圆点的颜色
+    fn badge_color(self) -> Option<[u8; 4]> {
+        match self {
+            TrayIconVariant::Normal => None,
+            TrayIconVariant::Attention => Some([0xE5, 0x39, 0x35, 0xFF]),
+            TrayIconVariant::Update => Some([0x1E, 0x88, 0xE5, 0xFF]),
+        }
+    }
+}
+
+/// 对单个菜单项的覆盖，没有设置的字段使用菜单定义中的值
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemOverride {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+}
+
+impl ItemOverride {
+    /// 用 `other` 中设置了的字段覆盖当前值
+    fn merge(&mut self, other: &ItemOverride) {
+        if other.text.is_some() {
+            self.text = other.text.clone();
+        }
+        if other.enabled.is_some() {
+            self.enabled = other.enabled;
+        }
+    }
+}
+
+/// 托盘的实时状态
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrayStatus {
+    /// 前端指定的图标样式，为空时根据状态自动选择
+    pub icon: Option<TrayIconVariant>,
+    /// 前端指定的提示文字，为空时使用默认的提示文字
+    pub tooltip: Option<String>,
+    /// 配置中指定的标题，代替默认提示文字的第一行
+    pub title: Option<String>,
+    /// 前端对菜单项的覆盖，优先于根据状态计算出的值
+    pub items: BTreeMap<String, ItemOverride>,
+    /// 可用更新的版本号
+    pub update_version: Option<String>,
+    /// 待处理订单的数量
+    pub pending_orders: u32,
+    /// 主窗口是否可见
+    pub main_window_visible: bool,
+}
+
+impl Default for TrayStatus {
+    fn default() -> Self {
+        Self {
+            icon: None,
+            tooltip: None,
+            title: None,
+            items: BTreeMap::new(),
+            update_version: None,
+            pending_orders: 0,
+            main_window_visible: true,
+        }
+    }
+}
+
+impl TrayStatus {
+    /// 实际使用的图标样式：待处理订单优先于可用更新
+    pub fn icon_variant(&self) -> TrayIconVariant {
+        self.icon.unwrap_or(if self.pending_orders > 0 {
+            TrayIconVariant::Attention
+        } else if self.update_version.is_some() {
+            TrayIconVariant::Update
+        } else {
+            TrayIconVariant::Normal
+        })
+    }
+
+    /// 实际显示的提示文字
+    pub fn tooltip_text(&self, locale: Locale) -> String {
+        if let Some(tooltip) = &self.tooltip {
+            return tooltip.clone();
+        }
+        let title = match &self.title {
+            Some(title) => title.clone(),
+            None => locale::translate(locale, "tray.tooltip", &[]),
+        };
+        let mut lines = vec![title];
+        if self.pending_orders > 0 {
+            lines.push(locale::translate_plural(
+                locale,
+                "tray.pendingOrders",
+                u64::from(self.pending_orders),
+                &[],
+            ));
+        }
+        if let Some(version) = &self.update_version {
+            lines.push(locale::translate(
+                locale,
+                "tray.updateAvailable",
+                &[("version", version.as_str())],
+            ));
+        }
+        lines.join("\n")
+    }
+
+    /// 根据状态计算出的菜单项覆盖，再叠加前端的覆盖
+    pub fn item_overrides(&self, locale: Locale) -> BTreeMap<String, ItemOverride> {
+        let mut items = BTreeMap::new();
+        // 主窗口可见时只能隐藏，不可见时只能打开
+        items.insert(
+            "open".to_string(),
+            ItemOverride {
+                text: None,
+                enabled: Some(!self.main_window_visible),
+            },
+        );
+        items.insert(
+            "hide".to_string(),
+            ItemOverride {
+                text: None,
+                enabled: Some(self.main_window_visible),
+            },
+        );
+        let update_text = match &self.update_version {
+            Some(version) => locale::translate(
+                locale,
+                "tray.updateAvailable",
+                &[("version", version.as_str())],
+            ),
+            None => locale::translate(locale, "tray.noUpdate", &[]),
+        };
+        items.insert(
+            "update".to_string(),
+            ItemOverride {
+                text: Some(update_text),
+                enabled: Some(self.update_version.is_some()),
+            },
+        );
+        items.insert(
+            "orders".to_string(),
+            ItemOverride {
+                text: Some(locale::translate_plural(
+                    locale,
+                    "tray.pendingOrders",
+                    u64::from(self.pending_orders),
+                    &[],
+                )),
+                enabled: Some(self.pending_orders > 0),
+            },
+        );
+        for (id, item) in &self.items {
+            items.entry(id.clone()).or_default().merge(item);
+        }
+        items
+    }
+
+    /// 应用前端提交的修改
+    pub fn apply_patch(&mut self, patch: TrayStatusPatch) {
+        if let Some(icon) = patch.icon {
+            self.icon = icon;
+        }
+        if let Some(tooltip) = patch.tooltip {
+            self.tooltip = tooltip;
+        }
+        if let Some(pending_orders) = patch.pending_orders {
+            self.pending_orders = pending_orders;
+        }
+        for item in patch.items {
+            let entry = self.items.entry(item.id.clone()).or_default();
+            if let Some(text) = item.text {
+                entry.text = text;
+            }
+            if let Some(enabled) = item.enabled {
+                entry.enabled = enabled;
+            }
+            if *entry == ItemOverride::default() {
+                self.items.remove(&item.id);
+            }
+        }
+    }
+}
+
+/// 前端对托盘状态的修改，没有出现的字段保持不变，值为 `null` 的字段恢复默认
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrayStatusPatch {
+    #[serde(default, deserialize_with = "nullable")]
+    pub icon: Option<Option<TrayIconVariant>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub tooltip: Option<Option<String>>,
+    #[serde(default)]
+    pub pending_orders: Option<u32>,
+    #[serde(default)]
+    pub items: Vec<TrayItemPatch>,
+}
+
+/// 对单个菜单项的修改
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrayItemPatch {
+    pub id: String,
+    #[serde(default, deserialize_with = "nullable")]
+    pub text: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub enabled: Option<Option<bool>>,
+}
+
+/// 区分字段缺失（外层为 `None`）和字段为 `null`（`Some(None)`）
+fn nullable<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// 由 Tauri 管理的托盘状态，同时记录托盘当前显示的图标样式，样式不变时不重新生成图标
+#[derive(Default)]
+pub struct TrayStatusState(pub Mutex<TrayStatus>, Mutex<TrayIconVariant>);
+
+impl TrayStatusState {
+    /// 托盘创建时使用默认的应用图标，对应 [`TrayIconVariant::Normal`]
+    pub fn new(status: TrayStatus) -> Self {
+        Self(Mutex::new(status), Mutex::new(TrayIconVariant::Normal))
+    }
+
+    pub fn get(&self) -> TrayStatus {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+/// 菜单项实际显示的文字和是否可用
+pub(crate) fn resolve_item(
+    node: &MenuNode,
+    overrides: &BTreeMap<String, ItemOverride>,
+    locale: Locale,
+) -> (String, bool) {
+    let item = node.id().and_then(|id| overrides.get(id));
+    let text = item
+        .and_then(|item| item.text.clone())
+        .unwrap_or_else(|| node.display_text(locale).to_string());
+    let enabled = item
+        .and_then(|item| item.enabled)
+        .unwrap_or_else(|| node.is_enabled());
+    (text, enabled)
+}
+
+/// 当前的菜单项覆盖，托盘状态尚未创建时为空
+pub(crate) fn current_overrides<R: Runtime>(
+    app: &AppHandle<R>,
+    locale: Locale,
+) -> BTreeMap<String, ItemOverride> {
+    app.try_state::<TrayStatusState>()
+        .map(|state| state.get().item_overrides(locale))
+        .unwrap_or_default()
+}
+
+/// 修改托盘状态并立即应用，托盘尚未创建时什么也不做
+pub fn update<R: Runtime, F>(app: &AppHandle<R>, f: F) -> AppResult<()>
+where
+    F: FnOnce(&mut TrayStatus),
+{
+    let Some(state) = app.try_state::<TrayStatusState>() else {
+        return Ok(());
+    };
+    f(&mut state.0.lock().unwrap());
+    apply(app)
+}
+
+/// 显示或清除“有可用更新”
+pub fn set_update_available<R: Runtime>(app: &AppHandle<R>, version: Option<&str>) {
+    let version = version.map(str::to_string);
+    if let Err(error) = update(app, |status| status.update_version = version) {
+        log::warn!("更新托盘状态失败: {}", error);
+    }
+}
+
+/// 设置待处理订单的数量
+pub fn set_pending_orders<R: Runtime>(app: &AppHandle<R>, count: u32) -> AppResult<()> {
+    update(app, |status| status.pending_orders = count)
+}
+
+/// 根据主窗口是否可见切换“打开”和“隐藏”菜单项
+pub fn set_main_window_visible<R: Runtime>(app: &AppHandle<R>, visible: bool) {
+    let unchanged = app
+        .try_state::<TrayStatusState>()
+        .is_some_and(|state| state.get().main_window_visible == visible);
+    if unchanged {
+        return;
+    }
+    if let Err(error) = update(app, |status| status.main_window_visible = visible) {
+        log::warn!("更新托盘状态失败: {}", error);
+    }
+}
+
+/// 将当前状态应用到托盘：更新提示文字、图标以及已有菜单项的文字和是否可用
+pub fn apply<R: Runtime>(app: &AppHandle<R>) -> AppResult<()> {
+    let Some(state) = app.try_state::<TrayStatusState>() else {
+        return Ok(());
+    };
+    let status = state.get();
+    let locale = locale::current(app);
+    let tray = app.tray_by_id(TRAY_ID).ok_or_else(|| {
+        AppError::new(ErrorCode::TrayNotFound, tr("errors.trayNotFound", &[])).with_details(TRAY_ID)
+    })?;
+    tray.set_tooltip(Some(status.tooltip_text(locale)))?;
+    let variant = status.icon_variant();
+    let mut shown = state.1.lock().unwrap();
+    if *shown != variant {
+        tray.set_icon(Some(icon(app, variant)?))?;
+        *shown = variant;
+    }
+    drop(shown);
+
+    let Some(menu) = app
+        .try_state::<TrayMenuHandle<R>>()
+        .and_then(|handle| handle.get())
+    else {
+        return Ok(());
+    };
+    let definition = app.state::<TrayMenuState>().get();
+    let overrides = status.item_overrides(locale);
+    for node in definition.nodes() {
+        let Some(id) = node.id() else {
+            continue;
+        };
+        let (text, enabled) = resolve_item(node, &overrides, locale);
+        for item in find_items(menu.items()?, id) {
+            set_item(&item, &text, enabled)?;
+        }
+    }
+    Ok(())
+}
+
+/// 递归查找指定 id 的所有菜单项，同一个 id 可以出现在多个位置
+fn find_items<R: Runtime>(items: Vec<MenuItemKind<R>>, id: &str) -> Vec<MenuItemKind<R>> {
+    let mut found = Vec::new();
+    for item in items {
+        if let MenuItemKind::Submenu(submenu) = &item {
+            if let Ok(children) = submenu.items() {
+                found.extend(find_items(children, id));
+            }
+        }
+        if item.id().0 == id {
+            found.push(item);
+        }
+    }
+    found
+}
+
+fn set_item<R: Runtime>(item: &MenuItemKind<R>, text: &str, enabled: bool) -> AppResult<()> {
+    match item {
+        MenuItemKind::MenuItem(item) => {
+            item.set_text(text)?;
+            item.set_enabled(enabled)?;
+        }
+        MenuItemKind::Check(item) => {
+            item.set_text(text)?;
+            item.set_enabled(enabled)?;
+        }
+        MenuItemKind::Icon(item) => {
+            item.set_text(text)?;
+            item.set_enabled(enabled)?;
+        }
+        MenuItemKind::Submenu(item) => {
+            item.set_text(text)?;
+            item.set_enabled(enabled)?;
+        }
+        MenuItemKind::Predefined(_) => {}
+    }
+    Ok(())
+}
+
+/// 指定样式的托盘图标
+fn icon<R: Runtime>(app: &AppHandle<R>, variant: TrayIconVariant) -> AppResult<Image<'static>> {
+    let base = app
+        .default_window_icon()
+        .ok_or_else(|| AppError::internal("没有配置应用图标"))?;
+    let (width, height) = (base.width(), base.height());
+    let mut rgba = base.rgba().to_vec();
+    if let Some(color) = variant.badge_color() {
+        draw_badge(&mut rgba, width, height, color);
+    }
+    Ok(Image::new_owned(rgba, width, height))
+}
+
+/// 在 RGBA 图片的右上角绘制带白色描边的圆点
+pub fn draw_badge(rgba: &mut [u8], width: u32, height: u32, color: [u8; 4]) {
+    let size = width.min(height) as f32;
+    let radius = size * 0.22;
+    let border = (size * 0.04).max(1.0);
+    let (cx, cy) = (width as f32 - radius - border, radius + border);
+    for y in 0..height {
+        for x in 0..width {
+            let dx = x as f32 + 0.5 - cx;
+            let dy = y as f32 + 0.5 - cy;
+            let distance = (dx * dx + dy * dy).sqrt();
+            let pixel = if distance <= radius {
+                color
+            } else if distance <= radius + border {
+                [0xFF, 0xFF, 0xFF, 0xFF]
+            } else {
+                continue;
+            };
+            let offset = ((y * width + x) * 4) as usize;
+            if let Some(target) = rgba.get_mut(offset..offset + 4) {
+                target.copy_from_slice(&pixel);
+            }
+        }
+    }
+}
+
+/// 查询托盘状态
+#[tauri::command]
+pub fn get_tray_status(state: State<'_, TrayStatusState>) -> TrayStatus {
+    state.get()
+}
+
+/// 修改托盘状态，例如 `{ "pendingOrders": 3, "items": [{ "id": "dashboard", "enabled": false }] }`
+#[tauri::command]
+pub fn update_tray_status<R: Runtime>(
+    app: AppHandle<R>,
+    menu: State<'_, TrayMenuState>,
+    state: State<'_, TrayStatusState>,
+    patch: TrayStatusPatch,
+) -> AppResult<TrayStatus> {
+    let definition = menu.get();
+    for item in &patch.items {
+        if !definition
+            .nodes()
+            .iter()
+            .any(|node| node.id() == Some(item.id.as_str()))
+        {
+            return Err(AppError::invalid_argument(tr(
+                "errors.unknownMenuItem",
+                &[("id", item.id.as_str())],
+            )));
+        }
+    }
+    state.0.lock().unwrap().apply_patch(patch);
+    apply(&app)?;
+    Ok(state.get())
+}
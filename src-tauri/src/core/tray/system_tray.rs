@@ -1,73 +1,69 @@
-//! # Tauri 应用核心库
-//!
-//! 这是一个 Tauri 桌面应用的核心功能库，提供配置管理、数据存储和系统集成等功能。
-//! 在 Tauri 2.x 中，#[cfg(desktop)] 是一个 条件编译属性（conditional compilation attribute），它用于根据目标平台是否为“桌面平台”来决定是否编译某段代码。
-//!
-//! ## 功能特性
-//!
-//! - 配置文件管理
-//! - 用户偏好设置
-//! - 系统信息获取
-//! - 文件操作工具
-//!
-//! ## 使用示例
+//! # 系统托盘
 //!
+//! 创建系统托盘并注册默认的菜单项处理函数。
+//! 想要创建一个系统托盘，请阅读 https://v2.tauri.org.cn/learn/system-tray/
 
-#[cfg(desktop)]
-use crate::core::tray::tray_menu::create_tray_menu;
-#[cfg(desktop)]
+use super::definition::TrayMenuDefinition;
+use super::handlers::{TrayHandlers, TrayMenuPayload, TRAY_MENU_EVENT};
+use super::language;
+use super::recent::{self, RecentList, RecentState};
+use super::status::{self, TrayStatus, TrayStatusState};
+use super::tray_menu::{create_tray_menu, TrayMenuHandle, TrayMenuState};
+use super::TRAY_ID;
+use crate::core::config;
+use crate::core::locale;
+use crate::core::updater::app_updates::{PendingUpdate, UpdateMetadata};
+use crate::core::updater::{install_staged, UPDATE_AVAILABLE_EVENT};
+use crate::core::window::registry::{ABOUT_WINDOW, SETTINGS_WINDOW};
+use crate::core::window::{
+    self, VisibilityChangedPayload, WindowVisibility, MAIN_WINDOW, VISIBILITY_CHANGED_EVENT,
+};
+use crate::models::{AppError, AppResult};
 use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
-#[cfg(desktop)]
-use tauri::{App, Emitter, Manager};
+use tauri::{AppHandle, Emitter, Listener, Manager, Runtime};
 
-/// 创建系统托盘
-/// 想要创建一个系统托盘，请阅读 https://v2.tauri.org.cn/learn/system-tray/
-#[cfg(desktop)]
-pub fn create_system_tray(app: &mut App) {
-    // 创建托盘菜单，调用下面的方法
-    let menu = create_tray_menu(app);
+/// 创建系统托盘，菜单从应用配置目录下的 `tray-menu.json` 加载
+pub fn create_system_tray<R: Runtime>(app: &AppHandle<R>) -> AppResult<()> {
+    let current = locale::current(app);
+    let mut definition = TrayMenuDefinition::load(app);
+    definition.sync_language(current);
+    app.manage(TrayStatusState::new(TrayStatus {
+        main_window_visible: window::visibility(app, MAIN_WINDOW) == WindowVisibility::Visible,
+        title: config::current(app).tray.title,
+        ..TrayStatus::default()
+    }));
+    app.manage(RecentState::new(RecentList::load(app)));
+    let menu = create_tray_menu(app, &definition)?;
+    app.manage(TrayMenuState::new(definition));
+    app.manage(TrayMenuHandle::<R>::default());
+    app.state::<TrayMenuHandle<R>>().set(menu.clone());
+    app.manage(TrayHandlers::<R>::default());
+    register_default_handlers(&app.state::<TrayHandlers<R>>());
+    language::register(app, &app.state::<TrayHandlers<R>>());
+    recent::register(&app.state::<TrayHandlers<R>>());
 
-    let _tray = TrayIconBuilder::with_id("tray")
-        .icon(app.default_window_icon().unwrap().clone()) // 默认的图片
+    let icon = app
+        .default_window_icon()
+        .cloned()
+        .ok_or_else(|| AppError::internal("没有配置应用图标"))?;
+    TrayIconBuilder::with_id(TRAY_ID)
+        .icon(icon) // 默认的图片
         // .icon(Image::from_bytes(include_bytes!("../icons/light@2x.png")).expect("REASON")) // 自定义的图片，需要给 tauri 添加 image-png 特性
         // tooltip 为此托盘图标设置工具提示。但 linux 不支持使用此功能。
-        .tooltip("Tauri App")
+        .tooltip(app.state::<TrayStatusState>().get().tooltip_text(current))
         .menu(&menu)
-        // 监听菜单事件，每一个配置的前缀为上面的 MenuItem 中的 id
-        .on_menu_event(|app, event| match event.id.as_ref() {
-            "open" => {
-                // 打开事件
-                println!("open menu item was clicked");
-                // handle_open_coco(app);
-                let window = app.get_webview_window("main").unwrap();
-                let _ = window.show();
-            }
-            "hide" => {
-                // 隐藏事件
-                println!("hide menu item was clicked");
-                // handle_hide_coco(app);
-                let window = app.get_webview_window("main").unwrap();
-                let _ = window.hide();
-            }
-            "about" => {
-                // 将同步事件发出到所有 Web 视图
-                let _ = app.emit("open_settings", "about");
-            }
-            "settings" => {
-                // Windows 无法打开第二个窗口，问题: https://github.com/tauri-apps/tauri/issues/11144 https://github.com/tauri-apps/tauri/issues/8196
-                //#[cfg(windows)]
-                let _ = app.emit("open_settings", "");
-
-                // #[cfg(not(windows))]
-                // open_settings(&app);
-            }
-            "quit" => {
-                println!("quit menu item was clicked");
-                app.exit(0);
-            }
-            _ => {
-                println!("menu item {:?} not handled", event.id);
+        // 监听菜单事件，按菜单项 id 分发给注册的处理函数
+        .on_menu_event(|app, event| {
+            let id = event.id.as_ref();
+            // 原生菜单会自动切换勾选状态，同步到菜单定义中
+            {
+                let state = app.state::<TrayMenuState>();
+                let mut definition = state.0.lock().unwrap();
+                if let Some(checked) = definition.is_checked(id) {
+                    definition.set_checked(id, !checked);
+                }
             }
+            app.state::<TrayHandlers<R>>().dispatch(app, id);
         })
         // 监听托盘事件
         .on_tray_icon_event(|tray, event| match event {
@@ -76,35 +72,63 @@ pub fn create_system_tray(app: &mut App) {
                 button_state: MouseButtonState::Up,
                 ..
             } => {
-                println!("left click pressed and released");
-                // 在这个例子中，当点击托盘图标时，将展示并聚焦于主窗口
-                let app = tray.app_handle();
-                if let Some(window) = app.get_webview_window("main") {
-                    let _ = window.unminimize();
-                    let _ = window.show();
-                    let _ = window.set_focus();
-                }
+                // 当点击托盘图标时，将展示并聚焦于主窗口
+                window::show_main(tray.app_handle());
             }
             _ => {
-                println!("unhandled event {event:?}");
+                log::debug!("unhandled event {event:?}");
             }
         })
-        .build(app)
-        .unwrap();
+        .build(app)?;
+
+    // 主窗口显示、隐藏或最小化后同步“打开”和“隐藏”菜单项
+    let handle = app.clone();
+    app.listen(
+        VISIBILITY_CHANGED_EVENT,
+        move |event| match serde_json::from_str::<VisibilityChangedPayload>(event.payload()) {
+            Ok(payload) if payload.label == MAIN_WINDOW => status::set_main_window_visible(
+                &handle,
+                payload.visibility == WindowVisibility::Visible,
+            ),
+            Ok(_) => {}
+            Err(error) => log::warn!("无法解析窗口可见性事件: {}", error),
+        },
+    );
+    Ok(())
 }
 
-// fn handle_open_coco(app: &AppHandle) {
-//     println!("open menu item was clicked");
-//     if let Some(window) = app.get_webview_window("main") {
-//         let _ = window.unminimize();
-//         let _ = window.show();
-//         let _ = window.set_focus();
-//     }
-// }
-//
-// fn handle_hide_coco(app: &AppHandle) {
-//     println!("hide menu item was clicked");
-//     if let Some(window) = app.get_webview_window("main") {
-//         let _ = window.hide();
-//     }
-// }
+/// 注册内置菜单项的处理函数，其他模块可以通过 [`TrayHandlers`] 注册自己的处理函数
+fn register_default_handlers<R: Runtime>(handlers: &TrayHandlers<R>) {
+    handlers.on("open", |app, _| window::show_main(app));
+    handlers.on("hide", |app, _| window::hide_main(app));
+    handlers.on("update", |app, _| {
+        // 打开主窗口并再次通知前端有可用更新，由前端展示更新说明
+        window::show_main(app);
+        let metadata = app
+            .try_state::<PendingUpdate>()
+            .and_then(|pending| pending.0.lock().unwrap().as_ref().map(UpdateMetadata::from));
+        if let Some(metadata) = metadata {
+            let _ = app.emit(UPDATE_AVAILABLE_EVENT, metadata);
+        }
+    });
+    handlers.on("orders", |app, id| {
+        // 订单页面由前端负责，打开主窗口后转发给前端
+        window::show_main(app);
+        let _ = app.emit(TRAY_MENU_EVENT, TrayMenuPayload { id: id.to_string() });
+    });
+    handlers.on("about", |app, _| {
+        window::registry::open_kind(app, ABOUT_WINDOW)
+    });
+    handlers.on("settings", |app, _| {
+        window::registry::open_kind(app, SETTINGS_WINDOW)
+    });
+    handlers.on("quit", |app, _| {
+        log::info!("quit menu item was clicked");
+        window::geometry::persist_all(app);
+        // 正常退出前安装已暂存的更新
+        if let Err(error) = install_staged(app) {
+            log::warn!("安装暂存的更新失败: {}", error);
+        }
+        app.exit(0);
+    });
+}
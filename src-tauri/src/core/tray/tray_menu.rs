@@ -1,96 +1,189 @@
-//! # Tauri 应用核心库
-//!
-//! 这是一个 Tauri 桌面应用的核心功能库，提供配置管理、数据存储和系统集成等功能。
-//! 在 Tauri 2.x 中，#[cfg(desktop)] 是一个 条件编译属性（conditional compilation attribute），它用于根据目标平台是否为“桌面平台”来决定是否编译某段代码。
-//!
-//! ## 功能特性
-//!
-//! - 配置文件管理
-//! - 用户偏好设置
-//! - 系统信息获取
-//! - 文件操作工具
-//!
-//! ## 使用示例
+//! # 托盘菜单
 //!
+//! 根据 [`TrayMenuDefinition`] 构建原生菜单，并提供在运行时查询和替换托盘菜单的命令。
+//! 想要定义和操作系统托盘中的菜单，请阅读 https://v2.tauri.org.cn/learn/window-menu/
 
-#[cfg(desktop)]
+use super::definition::{MenuNode, TrayMenuDefinition};
+use super::recent;
+use super::status::{self, ItemOverride};
+use super::TRAY_ID;
+use crate::core::locale::{self, tr, Locale};
+use crate::models::{AppError, AppResult, ErrorCode};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
 use tauri::image::Image;
-#[cfg(desktop)]
-use tauri::menu::{CheckMenuItem, CheckMenuItemBuilder, IconMenuItem, Menu, SubmenuBuilder};
-#[cfg(desktop)]
-use tauri::{App, Wry};
+use tauri::menu::{
+    CheckMenuItemBuilder, IconMenuItemBuilder, Menu, MenuItemBuilder, MenuItemKind,
+    PredefinedMenuItem, SubmenuBuilder,
+};
+use tauri::path::BaseDirectory;
+use tauri::{AppHandle, Manager, Runtime, State};
+
+/// 当前的托盘菜单定义，由 Tauri 管理
+pub struct TrayMenuState(pub Mutex<TrayMenuDefinition>);
+
+impl TrayMenuState {
+    pub fn new(definition: TrayMenuDefinition) -> Self {
+        Self(Mutex::new(definition))
+    }
+
+    pub fn get(&self) -> TrayMenuDefinition {
+        self.0.lock().unwrap().clone()
+    }
+}
 
-/// 创建系统托盘菜单
-/// 想要定义和操作系统托盘中的菜单，请阅读 https://v2.tauri.org.cn/learn/window-menu/
-#[cfg(desktop)]
-pub fn create_tray_menu(app: &mut App) -> Menu<Wry> {
-    use tauri::menu::{MenuBuilder, MenuItem};
+/// 当前托盘使用的原生菜单，用于在不重建菜单的情况下修改菜单项
+pub struct TrayMenuHandle<R: Runtime>(pub Mutex<Option<Menu<R>>>);
 
-    // 定义具体菜单项
+impl<R: Runtime> Default for TrayMenuHandle<R> {
+    fn default() -> Self {
+        Self(Mutex::new(None))
+    }
+}
+
+impl<R: Runtime> TrayMenuHandle<R> {
+    pub fn get(&self) -> Option<Menu<R>> {
+        self.0.lock().unwrap().clone()
+    }
 
-    // 退出按钮
-    let quit_i = MenuItem::with_id(app, "quit", "退出 Coco", true, None::<&str>).unwrap();
-    // 设置按钮
-    let settings_i = MenuItem::with_id(app, "settings", "设置", true, None::<&str>).unwrap();
-    // 打开按钮
-    let open_i = MenuItem::with_id(app, "open", "打开 Coco", true, None::<&str>).unwrap();
-    // 关于按钮
-    let about_i = MenuItem::with_id(app, "about", "关于 Coco", true, None::<&str>).unwrap();
-    // 隐藏按钮
-    let hide_i = MenuItem::with_id(app, "hide", "隐藏 Coco", true, None::<&str>).unwrap();
-    // ......
-    let dashboard_i = MenuItem::with_id(app, "dashboard", "仪表盘", true, None::<&str>).unwrap();
+    pub fn set(&self, menu: Menu<R>) {
+        *self.0.lock().unwrap() = Some(menu);
+    }
+}
 
-    // 从路径加载图标
-    let icon_image = Image::from_bytes(include_bytes!("../../../icons/icon.png")).unwrap();
+/// 根据菜单定义创建托盘菜单，菜单项的文字和是否可用会叠加托盘状态中的覆盖，
+/// 最近使用的占位子菜单会替换为实际的列表
+pub fn create_tray_menu<R: Runtime, M: Manager<R>>(
+    manager: &M,
+    definition: &TrayMenuDefinition,
+) -> AppResult<Menu<R>> {
+    let locale = locale::current(manager.app_handle());
+    let overrides = status::current_overrides(manager.app_handle(), locale);
+    let expanded =
+        recent::current(manager.app_handle()).map(|list| list.expand(definition, locale));
+    let definition = expanded.as_ref().unwrap_or(definition);
+    let menu = Menu::new(manager)?;
+    for node in &definition.items {
+        menu.append(&build_node(manager, node, &overrides, locale)?)?;
+    }
+    Ok(menu)
+}
 
-    // 创建具有子菜单的菜单项，参考 https://v2.tauri.org.cn/learn/window-menu/
-    let dashboard_submenu = SubmenuBuilder::new(app, "Dashboard")
-        .item(&dashboard_i)
-        .item(&settings_i)
-        .items(&[
-            &CheckMenuItem::new(app, "CheckMenuItem 1", true, true, None::<&str>).unwrap(),
-            &IconMenuItem::new(app, "IconMenuItem 2", true, Some(icon_image), None::<&str>)
-                .unwrap(),
-        ])
-        .build()
-        .expect("TODO: panic message");
+fn build_node<R: Runtime, M: Manager<R>>(
+    manager: &M,
+    node: &MenuNode,
+    overrides: &BTreeMap<String, ItemOverride>,
+    locale: Locale,
+) -> AppResult<MenuItemKind<R>> {
+    let (text, enabled) = status::resolve_item(node, overrides, locale);
+    let item = match node {
+        MenuNode::Item {
+            id,
+            accelerator,
+            icon: Some(icon),
+            ..
+        } => {
+            let mut builder = IconMenuItemBuilder::with_id(id.as_str(), &text)
+                .enabled(enabled)
+                .icon(load_icon(manager, icon)?);
+            if let Some(accelerator) = accelerator {
+                builder = builder.accelerator(accelerator);
+            }
+            MenuItemKind::Icon(builder.build(manager)?)
+        }
+        MenuNode::Item {
+            id,
+            accelerator,
+            icon: None,
+            ..
+        } => {
+            let mut builder = MenuItemBuilder::with_id(id.as_str(), &text).enabled(enabled);
+            if let Some(accelerator) = accelerator {
+                builder = builder.accelerator(accelerator);
+            }
+            MenuItemKind::MenuItem(builder.build(manager)?)
+        }
+        MenuNode::Check {
+            id,
+            checked,
+            accelerator,
+            ..
+        } => {
+            let mut builder = CheckMenuItemBuilder::with_id(id.as_str(), &text)
+                .checked(*checked)
+                .enabled(enabled);
+            if let Some(accelerator) = accelerator {
+                builder = builder.accelerator(accelerator);
+            }
+            MenuItemKind::Check(builder.build(manager)?)
+        }
+        MenuNode::Submenu { id, items, .. } => {
+            let submenu = match id {
+                Some(id) => SubmenuBuilder::with_id(manager, id.as_str(), &text),
+                None => SubmenuBuilder::new(manager, &text),
+            }
+            .enabled(enabled)
+            .build()?;
+            for child in items {
+                submenu.append(&build_node(manager, child, overrides, locale)?)?;
+            }
+            MenuItemKind::Submenu(submenu)
+        }
+        MenuNode::Separator => MenuItemKind::Predefined(PredefinedMenuItem::separator(manager)?),
+    };
+    Ok(item)
+}
 
-    // let icon_item = IconMenuItemBuilder::new("icon")
-    //     .icon(icon_image)
-    //     .build(app).unwrap();
+/// 加载菜单图标，`app` 表示应用图标，其他值为资源目录下的 PNG 文件
+fn load_icon<R: Runtime, M: Manager<R>>(manager: &M, icon: &str) -> AppResult<Image<'static>> {
+    if icon == "app" {
+        return manager
+            .app_handle()
+            .default_window_icon()
+            .cloned()
+            .map(Image::to_owned)
+            .ok_or_else(|| AppError::internal("没有配置应用图标"));
+    }
+    let path = manager.path().resolve(icon, BaseDirectory::Resource)?;
+    Image::from_path(&path).map_err(|error| {
+        AppError::invalid_argument(tr("errors.invalidMenuIcon", &[("icon", icon)]))
+            .with_details(error)
+    })
+}
 
-    let lang_str = app.config().identifier.clone();
-    let check_sub_item_1 = CheckMenuItemBuilder::new("English")
-        .id("en")
-        .checked(lang_str == "en")
-        .build(app)
-        .unwrap();
-    let check_sub_item_2 = CheckMenuItemBuilder::new("简体中文")
-        .id("en")
-        .checked(lang_str == "en")
-        .enabled(false)
-        .build(app)
-        .unwrap();
-    let other_item = SubmenuBuilder::new(app, "语言切换")
-        .item(&check_sub_item_1)
-        .item(&check_sub_item_2)
-        .build()
-        .unwrap();
+/// 使用当前的菜单定义重建托盘菜单
+pub fn refresh_tray_menu<R: Runtime>(app: &AppHandle<R>) -> AppResult<()> {
+    let definition = app.state::<TrayMenuState>().get();
+    let menu = create_tray_menu(app, &definition)?;
+    let tray = app.tray_by_id(TRAY_ID).ok_or_else(|| {
+        AppError::new(ErrorCode::TrayNotFound, tr("errors.trayNotFound", &[])).with_details(TRAY_ID)
+    })?;
+    tray.set_menu(Some(menu.clone()))?;
+    if let Some(handle) = app.try_state::<TrayMenuHandle<R>>() {
+        handle.set(menu);
+    }
+    status::apply(app)
+}
 
-    // 按照一定顺序 把菜单项 MenuItem 放到菜单 Menu 里
-    let menu = MenuBuilder::new(app)
-        .item(&open_i)
-        .separator() // 分割线
-        .item(&hide_i)
-        .item(&about_i)
-        .item(&dashboard_submenu)
-        .item(&other_item)
-        .item(&settings_i)
-        .separator() // 分割线
-        .item(&quit_i)
-        .build()
-        .unwrap();
+/// 查询当前的托盘菜单定义
+#[tauri::command]
+pub fn get_tray_menu(state: State<'_, TrayMenuState>) -> TrayMenuDefinition {
+    state.get()
+}
 
-    menu
+/// 替换托盘菜单，只在本次运行中生效，重启后恢复为 `tray-menu.json` 中的菜单
+#[tauri::command]
+pub fn set_tray_menu<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, TrayMenuState>,
+    definition: TrayMenuDefinition,
+) -> AppResult<TrayMenuDefinition> {
+    definition.validate()?;
+    let previous = std::mem::replace(&mut *state.0.lock().unwrap(), definition.clone());
+    if let Err(error) = refresh_tray_menu(&app) {
+        // 构建失败（例如图标无法加载）时保留原来的菜单
+        *state.0.lock().unwrap() = previous;
+        return Err(error);
+    }
+    Ok(definition)
 }
@@ -0,0 +1,224 @@
+//! # 更新配置
+//!
+//! 用户可以选择更新通道（stable/beta/nightly）、更新端点以及后台检查到更新后的处理方式，
+//! 配置以 JSON 格式保存在应用配置目录下的 `updater.json` 中。
+
+use crate::core::config::{self, UpdaterConfig};
+use crate::core::locale::tr;
+use crate::core::tray::{self, TrayStatusState};
+use crate::models::{AppError, AppResult};
+use crate::utils::fs::write_atomic;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, Runtime, State};
+use url::Url;
+
+/// 更新配置文件名
+const CONFIG_FILE: &str = "updater.json";
+
+/// 端点中的通道占位符，其余占位符（{{target}}、{{arch}}、{{current_version}}）由更新插件替换
+const CHANNEL_PLACEHOLDER: &str = "{{channel}}";
+
+/// 更新通道
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum UpdateChannel {
+    #[default]
+    Stable,
+    Beta,
+    Nightly,
+}
+
+impl UpdateChannel {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            UpdateChannel::Stable => "stable",
+            UpdateChannel::Beta => "beta",
+            UpdateChannel::Nightly => "nightly",
+        }
+    }
+}
+
+/// 后台检查到更新后的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum UpdateMode {
+    /// 只通知前端，由用户决定是否安装
+    #[default]
+    Notify,
+    /// 后台下载并暂存，在用户选择立即重启或下次正常退出时安装
+    Staged,
+    /// 自动下载安装并重启应用
+    Auto,
+    /// 启动时不检查更新
+    Disabled,
+}
+
+/// 持久化的更新配置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct UpdaterSettings {
+    pub channel: UpdateChannel,
+    pub mode: UpdateMode,
+    /// 更新端点，可以使用 {{channel}} 占位符
+    pub endpoints: Vec<String>,
+    /// 下载限速（字节/秒），为空时不限速
+    pub max_download_speed: Option<u64>,
+    /// 用户选择跳过的版本，关键更新除外
+    pub skipped_versions: Vec<String>,
+}
+
+impl Default for UpdaterSettings {
+    fn default() -> Self {
+        Self {
+            channel: UpdateChannel::default(),
+            mode: UpdateMode::default(),
+            endpoints: vec![
+                "https://cdn.myupdater.com/{{target}}-{{arch}}/{{current_version}}?channel={{channel}}"
+                    .to_string(),
+            ],
+            max_download_speed: None,
+            skipped_versions: Vec::new(),
+        }
+    }
+}
+
+impl UpdaterSettings {
+    /// 将端点中的通道占位符替换为当前通道并解析为 URL
+    pub fn endpoint_urls(&self) -> AppResult<Vec<Url>> {
+        if self.endpoints.is_empty() {
+            return Err(AppError::invalid_argument(tr("errors.noEndpoints", &[])));
+        }
+        self.endpoints
+            .iter()
+            .map(|endpoint| {
+                let endpoint = endpoint.replace(CHANNEL_PLACEHOLDER, self.channel.as_str());
+                Url::parse(&endpoint).map_err(|error| {
+                    AppError::invalid_argument(tr(
+                        "errors.invalidEndpoint",
+                        &[("endpoint", endpoint.as_str())],
+                    ))
+                    .with_details(error)
+                })
+            })
+            .collect()
+    }
+
+    /// 叠加管理员在分层配置中指定的端点和通道
+    pub fn with_config(mut self, config: &UpdaterConfig) -> Self {
+        if let Some(endpoint) = &config.endpoint {
+            self.endpoints = vec![endpoint.clone()];
+        }
+        match config.channel.as_deref() {
+            Some("stable") => self.channel = UpdateChannel::Stable,
+            Some("beta") => self.channel = UpdateChannel::Beta,
+            Some("nightly") => self.channel = UpdateChannel::Nightly,
+            _ => {}
+        }
+        self
+    }
+
+    /// 校验端点和下载限速
+    pub fn validate(&self) -> AppResult<()> {
+        self.endpoint_urls()?;
+        if self.max_download_speed == Some(0) {
+            return Err(AppError::invalid_argument(tr("errors.zeroSpeedLimit", &[])));
+        }
+        Ok(())
+    }
+
+    /// 从应用配置目录加载更新配置，文件不存在或无法解析时使用默认配置
+    pub fn load<R: Runtime>(app: &AppHandle<R>) -> Self {
+        let Ok(path) = config_path(app) else {
+            return Self::default();
+        };
+        match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|error| {
+                log::warn!("更新配置 {:?} 无法解析，使用默认配置: {}", path, error);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// 保存更新配置到应用配置目录
+    pub fn save<R: Runtime>(&self, app: &AppHandle<R>) -> AppResult<()> {
+        let path = config_path(app)?;
+        let content = serde_json::to_string_pretty(self).map_err(AppError::internal)?;
+        write_atomic(&path, content)?;
+        Ok(())
+    }
+}
+
+fn config_path<R: Runtime>(app: &AppHandle<R>) -> AppResult<PathBuf> {
+    Ok(app.path().app_config_dir()?.join(CONFIG_FILE))
+}
+
+/// 实际生效的更新配置：用户的更新配置叠加分层配置中的端点和通道
+pub fn effective<R: Runtime>(app: &AppHandle<R>) -> UpdaterSettings {
+    app.state::<UpdaterState>()
+        .get()
+        .with_config(&config::current(app).updater)
+}
+
+/// 由 Tauri 管理的更新配置
+pub struct UpdaterState(pub Mutex<UpdaterSettings>);
+
+impl UpdaterState {
+    pub fn new(settings: UpdaterSettings) -> Self {
+        Self(Mutex::new(settings))
+    }
+
+    pub fn get(&self) -> UpdaterSettings {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+#[tauri::command]
+pub fn get_updater_settings(state: State<'_, UpdaterState>) -> UpdaterSettings {
+    state.get()
+}
+
+#[tauri::command]
+pub fn set_updater_settings<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, UpdaterState>,
+    settings: UpdaterSettings,
+) -> AppResult<UpdaterSettings> {
+    // 保存前先校验所有端点都是合法的 URL
+    settings.validate()?;
+    settings.save(&app)?;
+    *state.0.lock().unwrap() = settings.clone();
+    Ok(settings)
+}
+
+/// 跳过指定版本，之后检查更新时不再提供该版本（关键更新除外）
+#[tauri::command]
+pub fn skip_update_version<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, UpdaterState>,
+    version: String,
+) -> AppResult<UpdaterSettings> {
+    let version = version.trim().to_string();
+    if version.is_empty() {
+        return Err(AppError::invalid_argument(tr("errors.emptyVersion", &[])));
+    }
+
+    // 托盘中不再提示被跳过的版本
+    let shown = app
+        .try_state::<TrayStatusState>()
+        .is_some_and(|status| status.get().update_version.as_ref() == Some(&version));
+    if shown {
+        tray::status::set_update_available(&app, None);
+    }
+
+    let mut settings = state.get();
+    if !settings.skipped_versions.contains(&version) {
+        settings.skipped_versions.push(version);
+    }
+    settings.save(&app)?;
+    *state.0.lock().unwrap() = settings.clone();
+    Ok(settings)
+}
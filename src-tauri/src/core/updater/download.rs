@@ -0,0 +1,273 @@
+//! # 断点续传下载
+//!
+//! 更新包先写入暂存目录中的 `{version}.part` 文件。连接中断时通过 HTTP Range 请求从已下载的位置继续，
+//! 应用重启后再次下载同一版本时也会接着之前的进度。旁边的 `{version}.part.json` 记录下载地址和签名，
+//! 服务器更换了更新包时丢弃旧的部分文件重新下载。
+//!
+//! 更新配置中的 `maxDownloadSpeed` 可以限制下载速度（字节/秒），适合按流量计费的网络。
+
+use super::app_updates::DownloadEvent;
+use super::{staging, UpdaterState};
+use crate::core::locale::tr;
+use crate::models::{AppError, AppResult, ErrorCode};
+use reqwest::header::{HeaderValue, ACCEPT, CONTENT_RANGE, RANGE};
+use reqwest::{Client, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_updater::Update;
+
+/// 单次下载最多尝试的次数
+const MAX_ATTEMPTS: u32 = 3;
+/// 重试前等待的时间，按尝试次数递增
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+const USER_AGENT: &str = concat!("tauri-bun-vite/", env!("CARGO_PKG_VERSION"));
+
+/// 部分文件对应的下载记录
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct PartialManifest {
+    url: String,
+    signature: String,
+}
+
+/// 单次请求失败的原因，网络问题可以重试，其他错误直接返回
+enum FetchError {
+    Retry(AppError),
+    Fatal(AppError),
+}
+
+impl From<std::io::Error> for FetchError {
+    fn from(error: std::io::Error) -> Self {
+        FetchError::Fatal(error.into())
+    }
+}
+
+fn network_error(error: impl std::fmt::Display) -> FetchError {
+    FetchError::Retry(
+        AppError::new(ErrorCode::UpdateFailed, tr("errors.downloadFailed", &[]))
+            .with_details(error),
+    )
+}
+
+/// 下载进度统计，速度按本次下载开始以来实际传输的字节计算
+struct Progress {
+    limit: Option<u64>,
+    downloaded: u64,
+    total: Option<u64>,
+    started_at: Instant,
+    transferred: u64,
+}
+
+impl Progress {
+    fn new(limit: Option<u64>) -> Self {
+        Self {
+            limit,
+            downloaded: 0,
+            total: None,
+            started_at: Instant::now(),
+            transferred: 0,
+        }
+    }
+
+    fn bytes_per_second(&self) -> u64 {
+        let elapsed = self.started_at.elapsed().as_secs_f64();
+        if elapsed <= 0.0 {
+            return 0;
+        }
+        (self.transferred as f64 / elapsed) as u64
+    }
+
+    fn advance(&mut self, chunk_length: usize) -> DownloadEvent {
+        self.downloaded += chunk_length as u64;
+        self.transferred += chunk_length as u64;
+
+        let bytes_per_second = self.bytes_per_second();
+        let eta_seconds = match (self.total, bytes_per_second) {
+            (Some(total), speed) if speed > 0 => {
+                Some(total.saturating_sub(self.downloaded) / speed)
+            }
+            _ => None,
+        };
+        DownloadEvent::Progress {
+            chunk_length,
+            downloaded: self.downloaded,
+            total: self.total,
+            bytes_per_second,
+            eta_seconds,
+        }
+    }
+
+    /// 超过限速时需要等待的时间
+    fn throttle(&self) -> Option<Duration> {
+        let limit = self.limit?;
+        let expected = Duration::from_secs_f64(self.transferred as f64 / limit as f64);
+        expected.checked_sub(self.started_at.elapsed())
+    }
+}
+
+/// 下载并校验更新包，连接中断时自动续传，通过 `on_event` 报告下载进度
+pub async fn download<R: Runtime>(
+    app: &AppHandle<R>,
+    update: &Update,
+    mut on_event: impl FnMut(DownloadEvent),
+) -> AppResult<Vec<u8>> {
+    let limit = app.state::<UpdaterState>().get().max_download_speed;
+    let (path, manifest_path) = staging::partial_paths(app, &update.version)?;
+    prepare_partial(&path, &manifest_path, update)?;
+
+    let client = client(update)?;
+    let mut progress = Progress::new(limit);
+    let mut started = false;
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let result = fetch(&client, update, &path, &mut progress, &mut |event| {
+            if let DownloadEvent::Started { .. } = event {
+                if started {
+                    return;
+                }
+                started = true;
+            }
+            on_event(event);
+        })
+        .await;
+
+        match result {
+            Ok(()) => break,
+            Err(FetchError::Retry(error)) if attempt < MAX_ATTEMPTS => {
+                log::info!(
+                    "下载更新 {} 中断，从 {} 字节处继续: {:?}",
+                    update.version,
+                    progress.downloaded,
+                    error.details()
+                );
+                tokio::time::sleep(RETRY_DELAY * attempt).await;
+            }
+            // 网络问题时保留部分文件，下次下载同一版本时继续
+            Err(FetchError::Retry(error) | FetchError::Fatal(error)) => return Err(error),
+        }
+    }
+    on_event(DownloadEvent::Finished);
+
+    let bytes = fs::read(&path)?;
+    let verified = staging::verify_signature(&bytes, &update.signature, &staging::pubkey(app)?);
+    // 无论校验是否通过，部分文件都已经没有用处
+    let _ = fs::remove_file(&path);
+    let _ = fs::remove_file(&manifest_path);
+    verified.map(|_| bytes)
+}
+
+/// 下载记录与本次更新不一致时丢弃旧的部分文件
+fn prepare_partial(path: &Path, manifest_path: &Path, update: &Update) -> AppResult<()> {
+    let manifest = PartialManifest {
+        url: update.download_url.to_string(),
+        signature: update.signature.clone(),
+    };
+    let previous = fs::read_to_string(manifest_path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<PartialManifest>(&content).ok());
+    if previous.as_ref() != Some(&manifest) {
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+        let content = serde_json::to_string(&manifest).map_err(AppError::internal)?;
+        fs::write(manifest_path, content)?;
+    }
+    Ok(())
+}
+
+/// 按照更新插件的请求配置创建 HTTP 客户端
+fn client(update: &Update) -> AppResult<Client> {
+    let mut builder = Client::builder().user_agent(USER_AGENT);
+    if let Some(timeout) = update.timeout {
+        // 限速时整个下载可能持续很久，所以只限制连接和每次读取的时间
+        builder = builder.connect_timeout(timeout).read_timeout(timeout);
+    }
+    if let Some(proxy) = &update.proxy {
+        let proxy = reqwest::Proxy::all(proxy.as_str()).map_err(|error| {
+            AppError::invalid_argument(tr("errors.invalidProxy", &[])).with_details(error)
+        })?;
+        builder = builder.proxy(proxy);
+    }
+    builder.build().map_err(AppError::internal)
+}
+
+/// 从部分文件的末尾开始请求剩余内容，并追加写入部分文件
+async fn fetch(
+    client: &Client,
+    update: &Update,
+    path: &Path,
+    progress: &mut Progress,
+    on_event: &mut impl FnMut(DownloadEvent),
+) -> Result<(), FetchError> {
+    let offset = fs::metadata(path)
+        .map(|metadata| metadata.len())
+        .unwrap_or(0);
+
+    let mut headers = update.headers.clone();
+    if !headers.contains_key(ACCEPT) {
+        headers.insert(ACCEPT, HeaderValue::from_static("application/octet-stream"));
+    }
+    let mut request = client.get(update.download_url.clone()).headers(headers);
+    if offset > 0 {
+        request = request.header(RANGE, format!("bytes={}-", offset));
+    }
+    let mut response = request.send().await.map_err(network_error)?;
+
+    let status = response.status();
+    let (mut file, offset) = match status {
+        StatusCode::PARTIAL_CONTENT if content_range_start(&response) == Some(offset) => {
+            (OpenOptions::new().append(true).open(path)?, offset)
+        }
+        // 部分文件已经不可用，从头开始下载
+        StatusCode::PARTIAL_CONTENT | StatusCode::RANGE_NOT_SATISFIABLE => {
+            fs::remove_file(path)?;
+            return Err(network_error(format!("服务器无法从 {} 字节处续传", offset)));
+        }
+        // 服务器不支持 Range 请求时返回完整内容
+        status if status.is_success() => (File::create(path)?, 0),
+        status if status.is_server_error() => {
+            return Err(network_error(format!("服务器返回 {}", status)));
+        }
+        status => {
+            return Err(FetchError::Fatal(
+                AppError::new(ErrorCode::UpdateFailed, tr("errors.downloadFailed", &[]))
+                    .with_details(format!("服务器返回 {}", status)),
+            ));
+        }
+    };
+
+    progress.downloaded = offset;
+    progress.total = response.content_length().map(|length| offset + length);
+    on_event(DownloadEvent::Started {
+        content_length: progress.total,
+        resumed_from: offset,
+    });
+
+    while let Some(chunk) = response.chunk().await.map_err(network_error)? {
+        file.write_all(&chunk)?;
+        on_event(progress.advance(chunk.len()));
+        if let Some(delay) = progress.throttle() {
+            tokio::time::sleep(delay).await;
+        }
+    }
+    file.flush()?;
+
+    match progress.total {
+        Some(total) if progress.downloaded < total => Err(network_error(format!(
+            "连接在 {} / {} 字节处关闭",
+            progress.downloaded, total
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// 解析 `Content-Range: bytes start-end/total` 中的起始位置
+fn content_range_start(response: &Response) -> Option<u64> {
+    let range = response.headers().get(CONTENT_RANGE)?.to_str().ok()?;
+    let (start, _) = range.strip_prefix("bytes ")?.split_once('-')?;
+    start.parse().ok()
+}
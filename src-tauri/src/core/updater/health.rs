@@ -0,0 +1,299 @@
+//! # 更新后健康检查与回滚
+//!
+//! 安装更新前记录当前版本，并将新版本标记为“待确认”。新版本每次启动都会累加启动次数，
+//! 前端完成启动后调用 [`confirm_update_health`] 确认新版本工作正常。
+//! 如果新版本连续多次启动都没有得到确认（例如启动即崩溃），就认为它不稳定，
+//! 前端可以通过 [`rollback_update`] 重新安装之前的版本，回滚不需要访问更新服务器。
+//!
+//! 为了能够回滚，每次安装更新时都会在 `rollback` 目录中保留新版本的更新包，
+//! 只保留当前版本和上一个版本两份。当前版本不是通过更新安装时，
+//! 先由 [`install::package_current`] 把当前应用打包保留下来（Windows 上无法打包，第一次更新之前的版本无法回滚）。
+
+use super::{install, staging};
+use crate::core::locale::tr;
+use crate::models::{AppError, AppResult, ErrorCode};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_updater::Update;
+
+/// 保留更新包和健康记录的目录名
+const ROLLBACK_DIR: &str = "rollback";
+/// 健康记录文件名
+const RECORD_FILE: &str = "health.json";
+/// 新版本未得到确认的启动次数超过该值时认为新版本不稳定
+pub const MAX_UNCONFIRMED_LAUNCHES: u32 = 3;
+
+/// 为回滚保留的更新包
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetainedPackage {
+    pub version: String,
+    /// 更新包的签名，更新前由本机打包的当前应用没有签名
+    pub signature: Option<String>,
+    pub file_name: String,
+}
+
+/// 持久化的更新健康记录
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HealthRecord {
+    /// 最近一次更新之前的版本
+    pub previous_version: Option<String>,
+    /// 已安装但尚未确认的版本
+    pub pending_version: Option<String>,
+    /// 待确认版本已经启动的次数
+    pub launches: u32,
+    /// 被回滚的版本，之后不再自动安装
+    pub rolled_back_version: Option<String>,
+    /// 为回滚保留的更新包
+    pub packages: Vec<RetainedPackage>,
+}
+
+/// 新版本的健康状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HealthStatus {
+    /// 当前版本已确认正常，或者不是通过更新安装的
+    Confirmed,
+    /// 新版本等待前端确认
+    PendingConfirmation,
+    /// 新版本多次启动都没有得到确认
+    Unstable,
+}
+
+/// 返回给前端的健康状态
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateHealth {
+    pub status: HealthStatus,
+    pub current_version: String,
+    pub previous_version: Option<String>,
+    pub launches: u32,
+    /// 是否保留了上一个版本的更新包，可以回滚
+    pub rollback_available: bool,
+}
+
+impl HealthRecord {
+    /// 安装新版本前调用，记录之前的版本并保留新版本的更新包
+    pub fn installing(&mut self, current_version: &str, package: RetainedPackage) {
+        self.previous_version = Some(current_version.to_string());
+        self.pending_version = Some(package.version.clone());
+        self.launches = 0;
+        self.packages.retain(|retained| {
+            retained.version == current_version && retained.version != package.version
+        });
+        self.packages.push(package);
+    }
+
+    /// 应用启动时调用，待确认的版本累加启动次数
+    ///
+    /// 正在运行的版本与待确认版本不一致时说明安装没有成功或已经回滚，不再等待确认。
+    pub fn launched(&mut self, running_version: &str) {
+        match self.pending_version.as_deref() {
+            Some(pending) if pending == running_version => self.launches += 1,
+            Some(_) => {
+                self.pending_version = None;
+                self.launches = 0;
+            }
+            None => {}
+        }
+    }
+
+    /// 前端确认新版本工作正常
+    pub fn confirm(&mut self) {
+        self.pending_version = None;
+        self.launches = 0;
+    }
+
+    /// 回滚完成前调用，记录被回滚的版本，即正在运行的版本
+    ///
+    /// 已确认的版本也可以手动回滚，此时没有待确认的版本，所以记录正在运行的版本。
+    /// 回滚后运行的就是上一个版本，不能再次回滚。
+    pub fn rolled_back(&mut self, running_version: &str) {
+        self.rolled_back_version = Some(running_version.to_string());
+        self.pending_version = None;
+        self.previous_version = None;
+        self.launches = 0;
+    }
+
+    pub fn status(&self) -> HealthStatus {
+        match self.pending_version {
+            None => HealthStatus::Confirmed,
+            Some(_) if self.launches > MAX_UNCONFIRMED_LAUNCHES => HealthStatus::Unstable,
+            Some(_) => HealthStatus::PendingConfirmation,
+        }
+    }
+
+    /// 可以用于回滚的上一个版本的更新包
+    pub fn rollback_package(&self) -> Option<&RetainedPackage> {
+        let previous = self.previous_version.as_deref()?;
+        self.packages
+            .iter()
+            .find(|package| package.version == previous)
+    }
+
+    pub fn health(&self, current_version: &str) -> UpdateHealth {
+        UpdateHealth {
+            status: self.status(),
+            current_version: current_version.to_string(),
+            previous_version: self.previous_version.clone(),
+            launches: self.launches,
+            rollback_available: self.rollback_package().is_some(),
+        }
+    }
+}
+
+fn rollback_dir<R: Runtime>(app: &AppHandle<R>) -> AppResult<PathBuf> {
+    Ok(app.path().app_local_data_dir()?.join(ROLLBACK_DIR))
+}
+
+/// 读取健康记录，不存在或无法解析时返回默认记录
+pub fn load<R: Runtime>(app: &AppHandle<R>) -> HealthRecord {
+    rollback_dir(app)
+        .ok()
+        .and_then(|dir| fs::read_to_string(dir.join(RECORD_FILE)).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// 保存健康记录，并删除不再需要的更新包
+fn save<R: Runtime>(app: &AppHandle<R>, record: &HealthRecord) -> AppResult<()> {
+    let dir = rollback_dir(app)?;
+    fs::create_dir_all(&dir)?;
+    let content = serde_json::to_string_pretty(record).map_err(AppError::internal)?;
+    fs::write(dir.join(RECORD_FILE), content)?;
+
+    for entry in fs::read_dir(&dir)?.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let retained = record
+            .packages
+            .iter()
+            .any(|package| package.file_name == name);
+        if name != RECORD_FILE && !retained {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+    Ok(())
+}
+
+fn running_version<R: Runtime>(app: &AppHandle<R>) -> String {
+    app.package_info().version.to_string()
+}
+
+/// 在调用 [`Update::install`] 之前记录安装信息，所有安装更新的地方都需要调用
+pub fn record_install<R: Runtime>(
+    app: &AppHandle<R>,
+    update: &Update,
+    bytes: &[u8],
+) -> AppResult<()> {
+    let package = RetainedPackage {
+        version: update.version.clone(),
+        signature: Some(update.signature.clone()),
+        file_name: format!("{}.update", update.version),
+    };
+    record_install_package(app, &update.current_version, package, bytes)
+}
+
+/// 安装本地的更新包（例如暂存的更新）之前记录安装信息
+pub fn record_install_package<R: Runtime>(
+    app: &AppHandle<R>,
+    current_version: &str,
+    package: RetainedPackage,
+    bytes: &[u8],
+) -> AppResult<()> {
+    let dir = rollback_dir(app)?;
+    fs::create_dir_all(&dir)?;
+    fs::write(dir.join(&package.file_name), bytes)?;
+
+    let mut record = load(app);
+    // 当前版本不是通过更新安装的，没有它的更新包，先把当前应用打包保留下来
+    if !record
+        .packages
+        .iter()
+        .any(|retained| retained.version == current_version)
+    {
+        let file_name = format!("{}.bundle", current_version);
+        match install::package_current(&dir.join(&file_name)) {
+            Ok(()) => record.packages.push(RetainedPackage {
+                version: current_version.to_string(),
+                signature: None,
+                file_name,
+            }),
+            Err(error) => log::warn!("无法保留版本 {} 用于回滚: {}", current_version, error),
+        }
+    }
+    record.installing(current_version, package);
+    save(app, &record)
+}
+
+/// 应用启动时记录一次启动，返回当前的健康状态
+pub fn record_launch<R: Runtime>(app: &AppHandle<R>) -> AppResult<UpdateHealth> {
+    let version = running_version(app);
+    let mut record = load(app);
+    if record.pending_version.is_none() {
+        return Ok(record.health(&version));
+    }
+
+    record.launched(&version);
+    save(app, &record)?;
+    let health = record.health(&version);
+    if health.status == HealthStatus::Unstable {
+        log::warn!(
+            "版本 {} 已连续启动 {} 次未得到确认，可以回滚到 {:?}",
+            version,
+            health.launches,
+            health.previous_version
+        );
+    }
+    Ok(health)
+}
+
+/// 查询当前版本的健康状态
+#[tauri::command]
+pub fn get_update_health<R: Runtime>(app: AppHandle<R>) -> UpdateHealth {
+    load(&app).health(&running_version(&app))
+}
+
+/// 前端启动完成后调用，确认新版本工作正常
+#[tauri::command]
+pub fn confirm_update_health<R: Runtime>(app: AppHandle<R>) -> AppResult<UpdateHealth> {
+    let mut record = load(&app);
+    if record.pending_version.is_some() {
+        record.confirm();
+        save(&app, &record)?;
+    }
+    Ok(record.health(&running_version(&app)))
+}
+
+/// 重新安装上一个版本并重启应用
+///
+/// 不稳定或已确认的版本都可以回滚，被回滚的版本之后只通知、不再自动安装。
+/// 直接安装本地保留的上一个版本，不需要访问更新服务器。带签名的更新包会重新校验签名。
+#[tauri::command]
+pub async fn rollback_update<R: Runtime>(app: AppHandle<R>) -> AppResult<()> {
+    let record = load(&app);
+    let Some(package) = record.rollback_package().cloned() else {
+        return Err(AppError::new(
+            ErrorCode::RollbackUnavailable,
+            tr("errors.rollbackUnavailable", &[]),
+        ));
+    };
+
+    let bytes = fs::read(rollback_dir(&app)?.join(&package.file_name))?;
+    if let Some(signature) = &package.signature {
+        staging::verify_signature(&bytes, signature, &staging::pubkey(&app)?)?;
+    }
+
+    log::info!("回滚到版本 {}", package.version);
+    // Windows 上安装程序会直接退出当前进程，所以需要先记录回滚，安装失败时再恢复原来的记录
+    let mut rolled_back = record.clone();
+    rolled_back.rolled_back(&running_version(&app));
+    save(&app, &rolled_back)?;
+    if let Err(error) = install::install(&bytes) {
+        save(&app, &record)?;
+        return Err(error);
+    }
+    app.restart();
+}
@@ -0,0 +1,215 @@
+//! # 安装本地更新包
+//!
+//! 更新插件的 [`Update::install`](tauri_plugin_updater::Update::install) 只能通过检查更新得到，
+//! 应用重启后如果没有网络，就无法安装之前已经下载并校验过的暂存更新包。
+//! 这里按照更新插件的更新包格式直接安装本地文件，调用方需要先校验签名：
+//!
+//! - Windows：运行 NSIS 安装程序或 MSI，安装程序会接管并退出当前进程
+//! - macOS：解压 `.app.tar.gz` 并替换当前的 `.app`
+//! - Linux：替换当前运行的 AppImage（支持 `.AppImage.tar.gz`）
+//!
+//! 回滚时安装的是之前版本的更新包，或者在更新前由 [`package_current`] 打包的当前应用。
+
+use crate::core::locale::tr;
+use crate::models::{AppError, AppResult, ErrorCode};
+use std::fs;
+use std::path::Path;
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+use std::path::PathBuf;
+
+/// 当前的安装方式不支持直接安装更新包，例如通过系统包管理器安装的 deb
+fn unsupported(details: impl std::fmt::Display) -> AppError {
+    AppError::new(
+        ErrorCode::UpdateFailed,
+        tr("errors.unsupportedInstall", &[]),
+    )
+    .with_details(details)
+}
+
+/// 在目标文件旁边使用的临时路径，保证重命名在同一个文件系统中完成
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn sibling(target: &Path, suffix: &str) -> AppResult<PathBuf> {
+    let name = target
+        .file_name()
+        .ok_or_else(|| unsupported(format!("无效的安装路径: {:?}", target)))?;
+    Ok(target.with_file_name(format!(".{}.{}", name.to_string_lossy(), suffix)))
+}
+
+/// 当前运行的应用：Linux 上为 AppImage 文件，macOS 上为 `.app` 目录
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn current_bundle() -> AppResult<PathBuf> {
+    #[cfg(target_os = "linux")]
+    {
+        std::env::var_os("APPIMAGE")
+            .map(PathBuf::from)
+            .ok_or_else(|| unsupported("没有以 AppImage 方式运行"))
+    }
+    #[cfg(target_os = "macos")]
+    {
+        let executable = std::env::current_exe()?;
+        executable
+            .ancestors()
+            .find(|path| path.extension().is_some_and(|extension| extension == "app"))
+            .map(Path::to_path_buf)
+            .ok_or_else(|| unsupported(format!("{:?} 不在 .app 中", executable)))
+    }
+}
+
+/// 把当前运行的应用保存为与更新包格式相同的文件，回滚时可以直接交给 [`install`] 安装
+///
+/// Linux 上复制 AppImage，macOS 上把 `.app` 打包为 `.app.tar.gz`。
+/// Windows 上无法从已安装的文件得到安装程序，只能保留通过更新安装的版本的安装程序。
+pub fn package_current(destination: &Path) -> AppResult<()> {
+    #[cfg(target_os = "linux")]
+    {
+        fs::copy(current_bundle()?, destination)?;
+        Ok(())
+    }
+    #[cfg(target_os = "macos")]
+    {
+        let bundle = current_bundle()?;
+        let name = bundle
+            .file_name()
+            .ok_or_else(|| unsupported(format!("无效的安装路径: {:?}", bundle)))?;
+        let encoder = flate2::write::GzEncoder::new(
+            fs::File::create(destination)?,
+            flate2::Compression::default(),
+        );
+        let mut archive = tar::Builder::new(encoder);
+        archive.append_dir_all(name, &bundle)?;
+        archive.into_inner()?.finish()?;
+        Ok(())
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        Err(unsupported(format!("无法打包当前应用: {:?}", destination)))
+    }
+}
+
+/// 安装已经校验过签名的更新包
+///
+/// Windows 上启动安装程序后直接退出当前进程，其他平台原地替换应用，由调用方决定重启还是退出。
+pub fn install(bytes: &[u8]) -> AppResult<()> {
+    #[cfg(target_os = "linux")]
+    {
+        replace_appimage(&current_bundle()?, bytes)
+    }
+    #[cfg(target_os = "macos")]
+    {
+        replace_app_bundle(&current_bundle()?, bytes)
+    }
+    #[cfg(windows)]
+    {
+        run_installer(bytes)
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+    {
+        let _ = bytes;
+        Err(unsupported(std::env::consts::OS))
+    }
+}
+
+/// 用更新包替换 AppImage，保留原有的文件权限
+///
+/// 新文件先写入同一目录中的临时文件，再整体替换，写入失败时原来的 AppImage 不受影响。
+#[cfg(target_os = "linux")]
+pub fn replace_appimage(target: &Path, bytes: &[u8]) -> AppResult<()> {
+    let permissions = fs::metadata(target)?.permissions();
+    let temp = sibling(target, "update")?;
+    let result = write_appimage(&temp, bytes)
+        .and_then(|()| Ok(fs::set_permissions(&temp, permissions)?))
+        .and_then(|()| Ok(fs::rename(&temp, target)?));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+#[cfg(target_os = "linux")]
+fn write_appimage(path: &Path, bytes: &[u8]) -> AppResult<()> {
+    if !is_gzip(bytes) {
+        fs::write(path, bytes)?;
+        return Ok(());
+    }
+    let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(bytes));
+    for mut entry in archive.entries()?.flatten() {
+        let is_appimage = entry.path().is_ok_and(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "AppImage")
+        });
+        if is_appimage {
+            entry.unpack(path)?;
+            return Ok(());
+        }
+    }
+    Err(unsupported("更新包中没有 AppImage"))
+}
+
+/// 解压更新包中的 `.app` 并替换当前的应用
+///
+/// 新的应用先解压到同一目录中的临时目录，替换失败时恢复原来的应用。
+#[cfg(target_os = "macos")]
+pub fn replace_app_bundle(bundle: &Path, bytes: &[u8]) -> AppResult<()> {
+    if !is_gzip(bytes) {
+        return Err(unsupported("更新包不是 .app.tar.gz"));
+    }
+    let extracted = sibling(bundle, "update")?;
+    let previous = sibling(bundle, "previous")?;
+    let _ = fs::remove_dir_all(&extracted);
+    let _ = fs::remove_dir_all(&previous);
+    fs::create_dir_all(&extracted)?;
+
+    let result = (|| {
+        tar::Archive::new(flate2::read::GzDecoder::new(bytes)).unpack(&extracted)?;
+        let app = fs::read_dir(&extracted)?
+            .flatten()
+            .map(|entry| entry.path())
+            .find(|path| path.extension().is_some_and(|extension| extension == "app"))
+            .ok_or_else(|| unsupported("更新包中没有 .app"))?;
+        fs::rename(bundle, &previous)?;
+        if let Err(error) = fs::rename(&app, bundle) {
+            fs::rename(&previous, bundle)?;
+            return Err(error.into());
+        }
+        Ok(())
+    })();
+    let _ = fs::remove_dir_all(&extracted);
+    let _ = fs::remove_dir_all(&previous);
+    result
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn is_gzip(bytes: &[u8]) -> bool {
+    bytes.starts_with(&[0x1f, 0x8b])
+}
+
+/// 把安装程序写入临时目录并启动，然后退出当前进程，由安装程序完成更新并重新打开应用
+///
+/// 通过 `start` 启动，需要管理员权限的安装程序可以正常弹出提权确认。
+#[cfg(windows)]
+fn run_installer(bytes: &[u8]) -> AppResult<()> {
+    use std::process::Command;
+
+    let dir = std::env::temp_dir().join(format!("tauri-bun-vite-update-{}", std::process::id()));
+    fs::create_dir_all(&dir)?;
+    let mut command = Command::new("cmd");
+    command.args(["/C", "start", ""]);
+    if bytes.starts_with(b"MZ") {
+        // NSIS 安装程序，与更新插件使用相同的被动安装参数
+        let path = dir.join("setup.exe");
+        fs::write(&path, bytes)?;
+        command.arg(&path).args(["/P", "/R", "/UPDATE"]);
+    } else if bytes.starts_with(&[0xd0, 0xcf, 0x11, 0xe0]) {
+        let path = dir.join("setup.msi");
+        fs::write(&path, bytes)?;
+        command.arg("msiexec.exe").arg("/i").arg(&path).args([
+            "/passive",
+            "/promptrestart",
+            "AUTOLAUNCHAPP=True",
+        ]);
+    } else {
+        return Err(unsupported("更新包不是 NSIS 安装程序或 MSI"));
+    }
+    command.spawn()?;
+    std::process::exit(0);
+}
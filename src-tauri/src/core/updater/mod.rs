@@ -1,100 +1,224 @@
-use tauri_plugin_updater::UpdaterExt;
+pub mod config;
+pub mod download;
+pub mod health;
+pub mod install;
+pub mod policy;
+pub mod release_notes;
+pub mod staging;
+
+pub use config::{UpdateChannel, UpdateMode, UpdaterSettings, UpdaterState};
+pub use staging::{install_staged, StagedUpdate};
+
+use crate::core::tray;
+use crate::models::AppResult;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+use tauri_plugin_updater::{Update, UpdaterExt};
+
+/// 后台检查到新版本时发送给前端的事件
+pub const UPDATE_AVAILABLE_EVENT: &str = "updater://available";
+/// 更新包已下载并暂存，等待用户重启时发送给前端的事件
+pub const UPDATE_STAGED_EVENT: &str = "updater://staged";
+
+/// 按照用户配置的更新通道和端点检查更新，不满足发布策略的更新视为没有更新
+pub async fn check_for_update<R: Runtime>(app: &AppHandle<R>) -> AppResult<Option<Update>> {
+    let settings = config::effective(app);
+    let update = app
+        .updater_builder()
+        .endpoints(settings.endpoint_urls()?)?
+        .build()?
+        .check()
+        .await?;
+    let Some(update) = update else {
+        return Ok(None);
+    };
+
+    let decision = policy::evaluate(
+        &policy::ReleasePolicy::from_manifest(&update.raw_json),
+        &policy::client_info(app)?,
+        &update.version,
+        &settings.skipped_versions,
+    );
+    if !decision.is_offered() {
+        log::info!("不提供更新 {}: {:?}", update.version, decision);
+        return Ok(None);
+    }
+    Ok(Some(update))
+}
 
 // 检查更新，参考 https://v2.tauri.org.cn/plugin/updater/#checking-for-updates
-#[cfg(desktop)]
-pub(crate) async fn update(app: tauri::AppHandle) -> tauri_plugin_updater::Result<()> {
-    if let Some(update) = app.updater()?.check().await? {
-        let mut downloaded = 0;
-
-        // 或者，我们也可以分别调用 update.download（） 和 update.install（）
-        update
-            .download_and_install(
-                |chunk_length, content_length| {
-                    downloaded += chunk_length;
-                    println!("downloaded {downloaded} from {content_length:?}");
-                },
-                || {
-                    println!("download finished");
-                },
-            )
-            .await?;
-
-        println!("update installed");
-        app.restart();
+pub async fn update<R: Runtime>(app: AppHandle<R>) -> AppResult<()> {
+    let mode = app.state::<UpdaterState>().get().mode;
+    if mode == UpdateMode::Disabled {
+        log::info!("已关闭启动时检查更新");
+        return Ok(());
     }
 
-    Ok(())
-}
+    let update = check_for_update(&app).await?;
 
-// 要将下载进度通知给前端，请考虑使用带有通道的命令。
-#[cfg(desktop)]
-mod app_updates {
-    use serde::Serialize;
-    use std::sync::Mutex;
-    use tauri::{ipc::Channel, AppHandle, State};
-    use tauri_plugin_updater::{Update, UpdaterExt};
-
-    #[derive(Debug, thiserror::Error)]
-    pub enum Error {
-        #[error(transparent)]
-        Updater(#[from] tauri_plugin_updater::Error),
-        #[error("there is no pending update")]
-        NoPendingUpdate,
+    // 没有可用更新，或者服务器已提供更新的版本时，之前暂存的更新包已经没有意义
+    let staged = staging::load_manifest(&app);
+    if let Some(manifest) = staged {
+        if update.as_ref().map(|update| &update.version) != Some(&manifest.version) {
+            log::info!("丢弃已过期的暂存更新 {}", manifest.version);
+            app.state::<StagedUpdate>().set(None);
+            staging::discard(&app)?;
+        }
+    }
+
+    // 无论以哪种方式安装，托盘都提示有可用更新
+    tray::status::set_update_available(&app, update.as_ref().map(|update| update.version.as_str()));
+    let Some(update) = update else {
+        return Ok(());
+    };
+
+    // 关键更新强制安装：即使用户选择只通知，也在后台下载并暂存，下次退出时安装，
+    // 由前端提示用户立即重启，不会中断正在进行的操作。被回滚过的版本不再自动安装，只通知前端
+    let critical = policy::ReleasePolicy::from_manifest(&update.raw_json).critical;
+    let rolled_back = health::load(&app).rolled_back_version.as_ref() == Some(&update.version);
+    let mode = if rolled_back {
+        UpdateMode::Notify
+    } else if critical && mode == UpdateMode::Notify {
+        UpdateMode::Staged
+    } else {
+        mode
+    };
+
+    if mode == UpdateMode::Staged {
+        return stage_update(&app, update).await;
+    }
+
+    if mode == UpdateMode::Notify {
+        // 只通知前端，由用户通过 install_update 命令决定是否安装
+        log::info!("发现新版本 {}", update.version);
+        let metadata = app_updates::UpdateMetadata::from(&update);
+        *app.state::<app_updates::PendingUpdate>().0.lock().unwrap() = Some(update);
+        let _ = app.emit(UPDATE_AVAILABLE_EVENT, metadata);
+        return Ok(());
     }
 
-    impl Serialize for Error {
-        fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
-        where
-            S: serde::Serializer,
+    let bytes = download::download(&app, &update, |event| {
+        if let app_updates::DownloadEvent::Progress {
+            downloaded, total, ..
+        } = event
         {
-            serializer.serialize_str(self.to_string().as_str())
+            log::debug!("downloaded {downloaded} from {total:?}");
         }
-    }
+    })
+    .await?;
+    health::record_install(&app, &update, &bytes)?;
+    update.install(bytes)?;
+
+    log::info!("update installed");
+    app.restart();
+}
+
+/// 在后台下载并校验更新包，暂存到磁盘后通知前端
+async fn stage_update<R: Runtime>(app: &AppHandle<R>, update: Update) -> AppResult<()> {
+    let metadata = app_updates::UpdateMetadata::from(&update);
 
-    type Result<T> = std::result::Result<T, Error>;
+    // 重启前已经暂存过同一版本，并且签名依然有效，则直接复用
+    let reusable = staging::load_manifest(app).filter(|manifest| {
+        manifest.version == update.version && staging::read_verified(app, manifest).is_ok()
+    });
+
+    let manifest = match reusable {
+        Some(manifest) => manifest,
+        None => {
+            log::info!("后台下载更新 {}", update.version);
+            // download 在返回之前已经使用配置的公钥校验过签名
+            let bytes = download::download(app, &update, |_| {}).await?;
+            staging::stage(app, &update, &bytes)?
+        }
+    };
+
+    log::info!("更新 {} 已暂存，将在重启或退出时安装", update.version);
+    app.state::<StagedUpdate>().set(Some(manifest));
+    let _ = app.emit(UPDATE_STAGED_EVENT, metadata);
+    Ok(())
+}
+
+// 要将下载进度通知给前端，请考虑使用带有通道的命令。
+pub mod app_updates {
+    use super::release_notes::{self, ReleaseEntry};
+    use crate::core::locale::tr;
+    use crate::core::tray;
+    use crate::models::{AppError, AppResult, ErrorCode};
+    use serde::Serialize;
+    use std::sync::Mutex;
+    use tauri::{ipc::Channel, AppHandle, Runtime, State};
+    use tauri_plugin_updater::Update;
 
     #[derive(Clone, Serialize)]
     #[serde(tag = "event", content = "data")]
     pub enum DownloadEvent {
         #[serde(rename_all = "camelCase")]
         Started {
+            /// 更新包总字节数
             content_length: Option<u64>,
+            /// 从之前中断的位置继续下载时已有的字节数
+            resumed_from: u64,
         },
         #[serde(rename_all = "camelCase")]
         Progress {
             chunk_length: usize,
+            /// 已下载的字节数，包括续传前已有的部分
+            downloaded: u64,
+            total: Option<u64>,
+            bytes_per_second: u64,
+            /// 预计剩余秒数，总大小未知时为空
+            eta_seconds: Option<u64>,
         },
         Finished,
     }
 
-    #[derive(Serialize)]
+    #[derive(Clone, Serialize)]
     #[serde(rename_all = "camelCase")]
     pub struct UpdateMetadata {
-        version: String,
-        current_version: String,
+        pub version: String,
+        pub current_version: String,
+        /// 关键更新，前端不应提供跳过选项
+        pub critical: bool,
+        /// 目标版本的说明，已清理过的 Markdown
+        pub notes: Option<String>,
+        pub pub_date: Option<String>,
+        /// 更新包大小（字节），服务器未提供时为空
+        pub download_size: Option<u64>,
+        /// 当前版本到目标版本之间所有版本的说明，从新到旧排列
+        pub releases: Vec<ReleaseEntry>,
+        /// 合并后的所有版本说明
+        pub aggregated_notes: String,
+    }
+
+    impl From<&Update> for UpdateMetadata {
+        fn from(update: &Update) -> Self {
+            let manifest = &update.raw_json;
+            let releases =
+                release_notes::releases_between(manifest, &update.current_version, &update.version);
+            Self {
+                version: update.version.clone(),
+                current_version: update.current_version.clone(),
+                critical: super::policy::ReleasePolicy::from_manifest(manifest).critical,
+                notes: update.body.as_deref().map(release_notes::sanitize_markdown),
+                pub_date: release_notes::pub_date(manifest),
+                download_size: release_notes::download_size(manifest),
+                aggregated_notes: release_notes::aggregate_notes(&releases),
+                releases,
+            }
+        }
     }
 
     #[tauri::command]
-    pub async fn fetch_update(
-        app: AppHandle,
+    pub async fn fetch_update<R: Runtime>(
+        app: AppHandle<R>,
         pending_update: State<'_, PendingUpdate>,
-    ) -> Result<Option<UpdateMetadata>> {
-        let channel = "stable";
-        let url = url::Url::parse(&format!(
-            "https://cdn.myupdater.com/{{{{target}}}}-{{{{arch}}}}/{{{{current_version}}}}?channel={channel}",
-        )).expect("invalid URL");
-
-        let update = app
-            .updater_builder()
-            .endpoints(vec![url])?
-            .build()?
-            .check()
-            .await?;
-
-        let update_metadata = update.as_ref().map(|update| UpdateMetadata {
-            version: update.version.clone(),
-            current_version: update.current_version.clone(),
-        });
+    ) -> AppResult<Option<UpdateMetadata>> {
+        let update = super::check_for_update(&app).await?;
+
+        let update_metadata = update.as_ref().map(UpdateMetadata::from);
+        tray::status::set_update_available(
+            &app,
+            update.as_ref().map(|update| update.version.as_str()),
+        );
 
         *pending_update.0.lock().unwrap() = update;
 
@@ -102,54 +226,58 @@ mod app_updates {
     }
 
     #[tauri::command]
-    pub async fn install_update(
+    pub async fn install_update<R: Runtime>(
+        app: AppHandle<R>,
         pending_update: State<'_, PendingUpdate>,
         on_event: Channel<DownloadEvent>,
-    ) -> Result<()> {
+    ) -> AppResult<()> {
         let Some(update) = pending_update.0.lock().unwrap().take() else {
-            return Err(Error::NoPendingUpdate);
+            return Err(AppError::new(
+                ErrorCode::NoPendingUpdate,
+                tr("errors.noPendingUpdate", &[]),
+            ));
         };
 
-        let mut started = false;
+        let bytes = download_update(&app, &update, &on_event).await?;
+        super::health::record_install(&app, &update, &bytes)?;
+        update.install(bytes)?;
 
-        update
-            .download_and_install(
-                |chunk_length, content_length| {
-                    if !started {
-                        let _ = on_event.send(DownloadEvent::Started { content_length });
-                        started = true;
-                    }
+        Ok(())
+    }
 
-                    let _ = on_event.send(DownloadEvent::Progress { chunk_length });
-                },
-                || {
-                    let _ = on_event.send(DownloadEvent::Finished);
-                },
-            )
-            .await?;
+    /// 下载并校验更新包，同时通过通道向前端报告下载进度
+    pub async fn download_update<R: Runtime>(
+        app: &AppHandle<R>,
+        update: &Update,
+        on_event: &Channel<DownloadEvent>,
+    ) -> AppResult<Vec<u8>> {
+        super::download::download(app, update, |event| {
+            let _ = on_event.send(event);
+        })
+        .await
+    }
 
-        Ok(())
+    /// 立即安装暂存的更新并重启应用
+    #[tauri::command]
+    pub fn restart_to_update<R: Runtime>(app: AppHandle<R>) -> AppResult<()> {
+        if !super::install_staged(&app)? {
+            return Err(AppError::new(
+                ErrorCode::NoPendingUpdate,
+                tr("errors.noStagedUpdate", &[]),
+            ));
+        }
+        app.restart();
     }
 
-    struct PendingUpdate(Mutex<Option<Update>>);
-}
+    /// 查询本次运行中可以安装的暂存更新
+    #[tauri::command]
+    pub fn get_staged_update(
+        staged_update: State<'_, super::StagedUpdate>,
+    ) -> Option<super::staging::StagedManifest> {
+        staged_update.get()
+    }
 
-// #[cfg_attr(mobile, tauri::mobile_entry_point)]
-// pub fn run() {
-//     tauri::Builder::default()
-//         .plugin(tauri_plugin_process::init())
-//         .setup(|app| {
-//             #[cfg(desktop)]
-//             {
-//                 app.handle().plugin(tauri_plugin_updater::Builder::new().build());
-//                 app.manage(app_updates::PendingUpdate(Mutex::new(None)));
-//             }
-//             Ok(())
-//         })
-//         .invoke_handler(tauri::generate_handler![
-//             #[cfg(desktop)]
-//             app_updates::fetch_update,
-//             #[cfg(desktop)]
-//             app_updates::install_update
-//         ])
-// }
+    /// 已检查到但尚未安装的更新，由 Tauri 管理
+    #[derive(Default)]
+    pub struct PendingUpdate(pub Mutex<Option<Update>>);
+}
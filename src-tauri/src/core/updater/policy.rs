@@ -0,0 +1,222 @@
+//! # 更新策略
+//!
+//! 更新服务器返回的清单中可以携带额外的发布策略字段，客户端据此决定是否提供这次更新：
+//!
+//! ```json
+//! {
+//!   "version": "1.2.0",
+//!   "rollout": 20,
+//!   "minOsVersion": "10.0.19041",
+//!   "architectures": ["x86_64"],
+//!   "critical": false
+//! }
+//! ```
+//!
+//! - `rollout`：灰度发布百分比，按安装 ID 和版本号稳定地划分用户，默认 100
+//! - `minOsVersion` / `architectures`：最低系统版本和支持的架构，不满足时不提供更新
+//! - `critical`：关键更新，忽略灰度比例和用户跳过的版本，并在后台暂存、下次退出时强制安装
+//!
+//! 策略判断都是纯函数，不依赖 Tauri 运行时。
+
+use crate::models::AppResult;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::hash_map::RandomState;
+use std::fs;
+use std::hash::{BuildHasher, Hasher};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager, Runtime};
+
+/// 安装 ID 文件名
+const INSTALL_ID_FILE: &str = "install-id";
+
+/// 清单中的发布策略
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReleasePolicy {
+    /// 灰度发布百分比（0-100）
+    pub rollout: u8,
+    pub min_os_version: Option<String>,
+    /// 支持的架构，为空时不限制
+    pub architectures: Vec<String>,
+    pub critical: bool,
+}
+
+impl Default for ReleasePolicy {
+    fn default() -> Self {
+        Self {
+            rollout: 100,
+            min_os_version: None,
+            architectures: Vec::new(),
+            critical: false,
+        }
+    }
+}
+
+impl ReleasePolicy {
+    /// 从更新清单中读取策略字段
+    ///
+    /// 每个字段单独解析，某个字段缺失或格式错误时只有该字段使用默认值，
+    /// 例如 `rollout` 写错不会让 `critical` 失效。
+    pub fn from_manifest(manifest: &serde_json::Value) -> Self {
+        let default = Self::default();
+        Self {
+            rollout: field::<u8>(manifest, "rollout")
+                .map(|rollout| rollout.min(100))
+                .unwrap_or(default.rollout),
+            min_os_version: field(manifest, "minOsVersion").or(default.min_os_version),
+            architectures: field(manifest, "architectures").unwrap_or(default.architectures),
+            critical: field(manifest, "critical").unwrap_or(default.critical),
+        }
+    }
+}
+
+/// 读取清单中的一个字段，缺失或格式错误时返回 None
+fn field<T: DeserializeOwned>(manifest: &serde_json::Value, key: &str) -> Option<T> {
+    serde_json::from_value(manifest.get(key)?.clone()).ok()
+}
+
+/// 当前客户端的信息
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientInfo {
+    pub install_id: String,
+    pub os_version: String,
+    pub arch: String,
+}
+
+/// 策略判断结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase", tag = "decision", content = "reason")]
+pub enum PolicyDecision {
+    /// 提供更新，由用户或更新模式决定何时安装
+    Offer,
+    /// 关键更新，需要强制安装
+    Force,
+    /// 用户选择跳过了这个版本
+    Skipped,
+    /// 当前安装不在灰度范围内
+    NotInRollout,
+    /// 系统版本或架构不满足要求
+    Unsupported(String),
+}
+
+impl PolicyDecision {
+    /// 是否应该向用户提供这次更新
+    pub fn is_offered(&self) -> bool {
+        matches!(self, PolicyDecision::Offer | PolicyDecision::Force)
+    }
+}
+
+/// 根据发布策略、客户端信息和用户跳过的版本判断是否提供更新
+pub fn evaluate(
+    policy: &ReleasePolicy,
+    client: &ClientInfo,
+    version: &str,
+    skipped_versions: &[String],
+) -> PolicyDecision {
+    // 不满足系统要求时即使是关键更新也无法安装
+    if let Some(minimum) = &policy.min_os_version {
+        if !version_at_least(&client.os_version, minimum) {
+            return PolicyDecision::Unsupported(format!(
+                "需要系统版本 {} 以上，当前为 {}",
+                minimum, client.os_version
+            ));
+        }
+    }
+    if !policy.architectures.is_empty()
+        && !policy
+            .architectures
+            .iter()
+            .any(|arch| arch.eq_ignore_ascii_case(&client.arch))
+    {
+        return PolicyDecision::Unsupported(format!("不支持 {} 架构", client.arch));
+    }
+
+    if policy.critical {
+        return PolicyDecision::Force;
+    }
+    if skipped_versions.iter().any(|skipped| skipped == version) {
+        return PolicyDecision::Skipped;
+    }
+    if rollout_bucket(&client.install_id, version) >= policy.rollout.min(100) {
+        return PolicyDecision::NotInRollout;
+    }
+    PolicyDecision::Offer
+}
+
+/// 将安装 ID 和版本号稳定地映射到 0-99 的灰度分桶
+///
+/// 使用 FNV-1a 哈希，保证不同平台和 Rust 版本的结果一致。
+pub fn rollout_bucket(install_id: &str, version: &str) -> u8 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in install_id
+        .bytes()
+        .chain(std::iter::once(b':'))
+        .chain(version.bytes())
+    {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    (hash % 100) as u8
+}
+
+/// 比较以点分隔的版本号，非数字部分按 0 处理，缺失的部分视为 0
+pub fn version_at_least(actual: &str, minimum: &str) -> bool {
+    let parse = |version: &str| -> Vec<u64> {
+        version
+            .split(['.', '-', ' '])
+            .map(|part| part.parse().unwrap_or(0))
+            .collect()
+    };
+    let (actual, minimum) = (parse(actual), parse(minimum));
+    let length = actual.len().max(minimum.len());
+    for index in 0..length {
+        let a = actual.get(index).copied().unwrap_or(0);
+        let m = minimum.get(index).copied().unwrap_or(0);
+        if a != m {
+            return a > m;
+        }
+    }
+    true
+}
+
+/// 读取本机的安装 ID，第一次调用时生成并保存到应用本地数据目录
+pub fn install_id<R: Runtime>(app: &AppHandle<R>) -> AppResult<String> {
+    let dir = app.path().app_local_data_dir()?;
+    let path = dir.join(INSTALL_ID_FILE);
+    if let Ok(id) = fs::read_to_string(&path) {
+        let id = id.trim();
+        if !id.is_empty() {
+            return Ok(id.to_string());
+        }
+    }
+
+    fs::create_dir_all(&dir)?;
+    let id = generate_install_id();
+    fs::write(&path, &id)?;
+    Ok(id)
+}
+
+/// 生成随机的 128 位安装 ID，RandomState 每次创建时使用不同的随机种子
+fn generate_install_id() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or_default();
+    let mut halves = [0u64; 2];
+    for half in halves.iter_mut() {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u128(nanos);
+        hasher.write_u32(std::process::id());
+        *half = hasher.finish();
+    }
+    format!("{:016x}{:016x}", halves[0], halves[1])
+}
+
+/// 当前客户端的信息
+pub fn client_info<R: Runtime>(app: &AppHandle<R>) -> AppResult<ClientInfo> {
+    Ok(ClientInfo {
+        install_id: install_id(app)?,
+        os_version: os_info::get().version().to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+    })
+}
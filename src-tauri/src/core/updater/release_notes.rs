@@ -0,0 +1,244 @@
+//! # 更新说明
+//!
+//! 更新清单中的 `notes` 是当前目标版本的说明，服务器还可以通过 `changelog` 提供历史版本的说明，
+//! `size` 提供更新包大小：
+//!
+//! ```json
+//! {
+//!   "version": "1.2.0",
+//!   "notes": "……",
+//!   "size": 52428800,
+//!   "changelog": [
+//!     { "version": "1.1.0", "notes": "……", "pub_date": "2025-09-01T00:00:00Z" }
+//!   ]
+//! }
+//! ```
+//!
+//! 跨越多个版本更新时，前端会看到当前版本与目标版本之间所有版本的说明。
+//! 所有说明在发送给 webview 之前都会经过 [`sanitize_markdown`] 处理，
+//! 去掉原始 HTML 和危险的链接，前端可以直接渲染。
+
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+/// 单个版本说明的最大字符数，超出部分会被截断
+const MAX_NOTES_LENGTH: usize = 20_000;
+
+/// 允许出现在链接中的协议
+const ALLOWED_SCHEMES: &[&str] = &["http", "https", "mailto"];
+
+/// 一个版本的更新说明
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReleaseEntry {
+    pub version: String,
+    #[serde(default)]
+    pub notes: String,
+    #[serde(default, alias = "pub_date")]
+    pub pub_date: Option<String>,
+}
+
+/// 从更新清单中读取更新包大小
+pub fn download_size(manifest: &serde_json::Value) -> Option<u64> {
+    manifest.get("size").and_then(|size| size.as_u64())
+}
+
+/// 从更新清单中读取发布时间
+pub fn pub_date(manifest: &serde_json::Value) -> Option<String> {
+    manifest
+        .get("pub_date")
+        .and_then(|date| date.as_str())
+        .map(str::to_string)
+}
+
+/// 当前版本（不含）到目标版本（含）之间所有版本的说明，按版本从新到旧排列
+///
+/// 清单中没有 `changelog` 或其中缺少目标版本时，使用清单本身的 `notes` 作为目标版本的说明。
+/// 无法解析的版本号会被忽略。
+pub fn releases_between(
+    manifest: &serde_json::Value,
+    current_version: &str,
+    target_version: &str,
+) -> Vec<ReleaseEntry> {
+    let (Ok(current), Ok(target)) = (
+        Version::parse(current_version),
+        Version::parse(target_version),
+    ) else {
+        return Vec::new();
+    };
+
+    let changelog: Vec<ReleaseEntry> = manifest
+        .get("changelog")
+        .cloned()
+        .and_then(|changelog| serde_json::from_value(changelog).ok())
+        .unwrap_or_default();
+
+    let mut releases: Vec<(Version, ReleaseEntry)> = changelog
+        .into_iter()
+        .filter_map(|entry| Some((Version::parse(&entry.version).ok()?, entry)))
+        .filter(|(version, _)| *version > current && *version <= target)
+        .collect();
+
+    if !releases.iter().any(|(version, _)| *version == target) {
+        let notes = manifest
+            .get("notes")
+            .and_then(|notes| notes.as_str())
+            .unwrap_or_default();
+        releases.push((
+            target,
+            ReleaseEntry {
+                version: target_version.to_string(),
+                notes: notes.to_string(),
+                pub_date: pub_date(manifest),
+            },
+        ));
+    }
+
+    releases.sort_by(|(a, _), (b, _)| b.cmp(a));
+    releases.dedup_by(|(a, _), (b, _)| a == b);
+    releases
+        .into_iter()
+        .map(|(_, mut entry)| {
+            entry.notes = sanitize_markdown(&entry.notes);
+            entry
+        })
+        .collect()
+}
+
+/// 将多个版本的说明合并为一份 Markdown，每个版本一个二级标题
+pub fn aggregate_notes(releases: &[ReleaseEntry]) -> String {
+    releases
+        .iter()
+        .map(|release| {
+            let notes = release.notes.trim();
+            if notes.is_empty() {
+                format!("## {}", release.version)
+            } else {
+                format!("## {}\n\n{}", release.version, notes)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// 清理来自更新服务器的 Markdown
+///
+/// - 去掉除换行和制表符以外的控制字符
+/// - 转义原始 HTML，只保留 `<https://…>` 形式的自动链接
+/// - 将 `javascript:`、`data:` 等不在白名单中的链接替换为 `#`
+/// - 超长内容截断
+pub fn sanitize_markdown(markdown: &str) -> String {
+    let text: String = markdown
+        .replace("\r\n", "\n")
+        .chars()
+        .filter(|c| !c.is_control() || *c == '\n' || *c == '\t')
+        .take(MAX_NOTES_LENGTH)
+        .collect();
+
+    let escaped = escape_html(&text);
+    let linked = sanitize_inline_links(&escaped);
+    linked
+        .lines()
+        .map(sanitize_reference_definition)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// 转义 `<`，自动链接除外
+fn escape_html(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let autolink = rest[1..]
+            .find('>')
+            .map(|end| &rest[1..end + 1])
+            .filter(|target| !target.contains(char::is_whitespace) && is_safe_url(target))
+            .filter(|target| target.contains(':'));
+        match autolink {
+            Some(target) => {
+                result.push('<');
+                result.push_str(target);
+                result.push('>');
+                rest = &rest[target.len() + 2..];
+            }
+            None => {
+                result.push_str("&lt;");
+                rest = &rest[1..];
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// 处理 `[text](target)` 和 `![alt](target)` 中的链接地址
+fn sanitize_inline_links(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("](") {
+        result.push_str(&rest[..start + 2]);
+        rest = &rest[start + 2..];
+        let Some(end) = rest.find(')') else {
+            break;
+        };
+        let target = &rest[..end];
+        // 链接地址后面可能跟着标题：[text](url "title")
+        let url = target.split_whitespace().next().unwrap_or_default();
+        if is_safe_url(url.trim_start_matches('<').trim_end_matches('>')) {
+            result.push_str(target);
+        } else {
+            result.push('#');
+        }
+        result.push(')');
+        rest = &rest[end + 1..];
+    }
+    result.push_str(rest);
+    result
+}
+
+/// 处理 `[id]: target` 形式的链接定义
+fn sanitize_reference_definition(line: &str) -> String {
+    let trimmed = line.trim_start();
+    let definition = trimmed
+        .strip_prefix('[')
+        .and_then(|rest| rest.split_once("]:"));
+    match definition {
+        Some((label, target)) if !label.contains(']') => {
+            let url = target.split_whitespace().next().unwrap_or_default();
+            if is_safe_url(url.trim_start_matches('<').trim_end_matches('>')) {
+                line.to_string()
+            } else {
+                let indent = &line[..line.len() - trimmed.len()];
+                format!("{}[{}]: #", indent, label)
+            }
+        }
+        _ => line.to_string(),
+    }
+}
+
+/// 相对地址、锚点以及白名单中的协议是安全的
+fn is_safe_url(url: &str) -> bool {
+    // 浏览器解析协议时会忽略空白和控制字符，例如 "java\tscript:"
+    let url: String = url
+        .chars()
+        .filter(|c| !c.is_whitespace() && !c.is_control())
+        .collect();
+    // Markdown 渲染器会解码链接中的 HTML 实体，例如 "&#106;avascript:"
+    let head = &url[..url.find(['/', '?']).unwrap_or(url.len())];
+    if head.contains('&') {
+        return false;
+    }
+    let scheme_end = url.find(':');
+    let path_start = url.find(['/', '?', '#']);
+    match (scheme_end, path_start) {
+        // 冒号出现在路径之后，是相对地址
+        (Some(colon), Some(path)) if path < colon => true,
+        (Some(colon), _) => {
+            let scheme = url[..colon].to_ascii_lowercase();
+            ALLOWED_SCHEMES.contains(&scheme.as_str())
+        }
+        (None, _) => true,
+    }
+}
@@ -0,0 +1,200 @@
+//! # 暂存更新
+//!
+//! 在后台下载并校验更新包后，将其暂存到应用本地数据目录的 `updates` 目录中，
+//! 等到用户点击“立即重启”或通过托盘菜单正常退出时再安装，避免打断用户当前的工作。
+//!
+//! 暂存的更新包在应用重启后仍然有效：启动时重新校验签名后即可安装，不需要访问更新服务器。
+//! 下一次检查更新时，如果服务器提供的仍是同一版本，就直接复用暂存的更新包；
+//! 如果已有更新的版本，则丢弃旧的暂存文件重新下载。
+
+use crate::core::locale::tr;
+use crate::models::{AppError, AppResult, ErrorCode};
+use base64::Engine;
+use minisign_verify::{PublicKey, Signature};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_updater::Update;
+
+use super::install;
+
+/// 暂存目录名
+const STAGING_DIR: &str = "updates";
+/// 暂存清单文件名
+const MANIFEST_FILE: &str = "staged.json";
+/// 下载中的更新包后缀
+const PARTIAL_SUFFIX: &str = "part";
+
+/// 暂存更新的清单，记录更新包的版本和签名，用于重启后重新校验
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StagedManifest {
+    pub version: String,
+    pub current_version: String,
+    pub signature: String,
+    pub file_name: String,
+}
+
+/// 本次运行中可以安装的暂存更新，由 Tauri 管理
+///
+/// 只保存签名校验通过、并且基于当前运行版本下载的暂存更新，安装时不再访问更新服务器。
+#[derive(Default)]
+pub struct StagedUpdate(pub Mutex<Option<StagedManifest>>);
+
+impl StagedUpdate {
+    pub fn get(&self) -> Option<StagedManifest> {
+        self.0.lock().unwrap().clone()
+    }
+
+    pub fn set(&self, manifest: Option<StagedManifest>) {
+        *self.0.lock().unwrap() = manifest;
+    }
+}
+
+fn staging_dir<R: Runtime>(app: &AppHandle<R>) -> AppResult<PathBuf> {
+    Ok(app.path().app_local_data_dir()?.join(STAGING_DIR))
+}
+
+/// 正在下载的更新包及其下载记录的路径，下载中断后可以从这里继续
+pub(crate) fn partial_paths<R: Runtime>(
+    app: &AppHandle<R>,
+    version: &str,
+) -> AppResult<(PathBuf, PathBuf)> {
+    let dir = staging_dir(app)?;
+    fs::create_dir_all(&dir)?;
+    Ok((
+        dir.join(format!("{}.{}", version, PARTIAL_SUFFIX)),
+        dir.join(format!("{}.{}.json", version, PARTIAL_SUFFIX)),
+    ))
+}
+
+/// 读取暂存清单，不存在或无法解析时返回 None
+pub fn load_manifest<R: Runtime>(app: &AppHandle<R>) -> Option<StagedManifest> {
+    let path = staging_dir(app).ok()?.join(MANIFEST_FILE);
+    let content = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// 将已校验的更新包写入暂存目录
+pub fn stage<R: Runtime>(
+    app: &AppHandle<R>,
+    update: &Update,
+    bytes: &[u8],
+) -> AppResult<StagedManifest> {
+    let dir = staging_dir(app)?;
+    // 同一时间只保留一个暂存的更新
+    discard(app)?;
+    fs::create_dir_all(&dir)?;
+
+    let manifest = StagedManifest {
+        version: update.version.clone(),
+        current_version: update.current_version.clone(),
+        signature: update.signature.clone(),
+        file_name: format!("{}.update", update.version),
+    };
+    fs::write(dir.join(&manifest.file_name), bytes)?;
+    let content = serde_json::to_string_pretty(&manifest).map_err(AppError::internal)?;
+    fs::write(dir.join(MANIFEST_FILE), content)?;
+    Ok(manifest)
+}
+
+/// 读取暂存的更新包并重新校验签名
+pub fn read_verified<R: Runtime>(
+    app: &AppHandle<R>,
+    manifest: &StagedManifest,
+) -> AppResult<Vec<u8>> {
+    let bytes = fs::read(staging_dir(app)?.join(&manifest.file_name))?;
+    verify_signature(&bytes, &manifest.signature, &pubkey(app)?)?;
+    Ok(bytes)
+}
+
+/// 应用启动时恢复之前暂存的更新，签名无效或者不是基于当前版本下载的暂存文件会被删除
+///
+/// 返回恢复的暂存更新，没有网络时也可以在重启或退出时安装。
+pub fn restore<R: Runtime>(app: &AppHandle<R>) -> Option<StagedManifest> {
+    let manifest = load_manifest(app)?;
+    let running = app.package_info().version.to_string();
+    let valid = manifest.current_version == running && read_verified(app, &manifest).is_ok();
+    if !valid {
+        log::warn!("丢弃无效的暂存更新 {}", manifest.version);
+        if let Err(error) = discard(app) {
+            log::warn!("删除暂存更新失败: {}", error);
+        }
+        return None;
+    }
+    app.state::<StagedUpdate>().set(Some(manifest.clone()));
+    Some(manifest)
+}
+
+/// 删除暂存目录中的所有文件
+pub fn discard<R: Runtime>(app: &AppHandle<R>) -> AppResult<()> {
+    let dir = staging_dir(app)?;
+    if dir.exists() {
+        fs::remove_dir_all(dir)?;
+    }
+    Ok(())
+}
+
+/// 安装本次运行中可用的暂存更新，返回是否执行了安装
+///
+/// 直接安装重新校验过签名的暂存更新包，不访问更新服务器。
+/// 在 Windows 上安装程序会接管并退出当前进程，其他平台会原地替换应用，由调用方决定重启还是退出。
+pub fn install_staged<R: Runtime>(app: &AppHandle<R>) -> AppResult<bool> {
+    let Some(manifest) = app.state::<StagedUpdate>().0.lock().unwrap().take() else {
+        return Ok(false);
+    };
+
+    let bytes = read_verified(app, &manifest)?;
+    log::info!("安装暂存的更新 {}", manifest.version);
+    super::health::record_install_package(
+        app,
+        &manifest.current_version,
+        super::health::RetainedPackage {
+            version: manifest.version.clone(),
+            signature: manifest.signature.clone(),
+            file_name: manifest.file_name.clone(),
+        },
+        &bytes,
+    )?;
+    install::install(&bytes)?;
+    discard(app)?;
+    Ok(true)
+}
+
+/// 读取 tauri.conf.json5 中配置的更新公钥
+pub(crate) fn pubkey<R: Runtime>(app: &AppHandle<R>) -> AppResult<String> {
+    app.config()
+        .plugins
+        .0
+        .get("updater")
+        .and_then(|updater| updater.get("pubkey"))
+        .and_then(|pubkey| pubkey.as_str())
+        .map(str::to_string)
+        .ok_or_else(|| AppError::internal("未配置更新公钥 plugins.updater.pubkey"))
+}
+
+/// 使用 minisign 公钥校验更新包签名，签名和公钥均为 base64 编码，与更新插件保持一致
+pub fn verify_signature(data: &[u8], signature: &str, pubkey: &str) -> AppResult<()> {
+    let invalid = |details: String| {
+        AppError::new(
+            ErrorCode::UpdateVerificationFailed,
+            tr("errors.verificationFailed", &[]),
+        )
+        .with_details(details)
+    };
+    let decode = |value: &str| {
+        base64::engine::general_purpose::STANDARD
+            .decode(value)
+            .ok()
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .ok_or_else(|| invalid(format!("无法解码 base64 内容: {}", value)))
+    };
+
+    let public_key = PublicKey::decode(&decode(pubkey)?).map_err(|e| invalid(e.to_string()))?;
+    let signature = Signature::decode(&decode(signature)?).map_err(|e| invalid(e.to_string()))?;
+    public_key
+        .verify(data, &signature, true)
+        .map_err(|e| invalid(e.to_string()))
+}
@@ -0,0 +1,311 @@
+//! # 窗口位置和大小
+//!
+//! 每个窗口关闭或隐藏时记录它的位置、大小、最大化/全屏状态以及所在的显示器，
+//! 下次打开同一标签的窗口时恢复。显示器布局变化后（例如拔掉外接显示器），
+//! [`WindowGeometry::clamp`] 会把窗口移回可见区域，并在必要时缩小到显示器的大小。
+//!
+//! 记录以窗口标签为键保存在应用配置目录下的 `window-state.json` 中，坐标和尺寸都是物理像素。
+
+use crate::core::splashscreen::SPLASH_WINDOW;
+use crate::models::{AppError, AppResult};
+use crate::utils::fs::write_atomic;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{
+    AppHandle, Manager, PhysicalPosition, PhysicalSize, Runtime, WebviewWindow, WindowEvent,
+};
+
+/// 窗口位置记录文件名
+const STATE_FILE: &str = "window-state.json";
+
+/// 窗口至少需要有这么多像素留在显示器内，才认为用户还能把它拖回来
+const MIN_VISIBLE: i32 = 64;
+
+/// 窗口的最小尺寸，避免恢复出无法操作的窗口
+const MIN_SIZE: u32 = 200;
+
+/// 显示器的区域，物理像素
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MonitorArea {
+    pub name: Option<String>,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl MonitorArea {
+    /// 与矩形相交部分的宽和高
+    fn overlap(&self, x: i32, y: i32, width: u32, height: u32) -> (i32, i32) {
+        let horizontal = (self.x + self.width as i32).min(x + width as i32) - self.x.max(x);
+        let vertical = (self.y + self.height as i32).min(y + height as i32) - self.y.max(y);
+        (horizontal.max(0), vertical.max(0))
+    }
+}
+
+impl From<&tauri::Monitor> for MonitorArea {
+    fn from(monitor: &tauri::Monitor) -> Self {
+        Self {
+            name: monitor.name().cloned(),
+            x: monitor.position().x,
+            y: monitor.position().y,
+            width: monitor.size().width,
+            height: monitor.size().height,
+        }
+    }
+}
+
+/// 一个窗口的位置和大小
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowGeometry {
+    /// 窗口外框左上角的位置
+    pub x: i32,
+    pub y: i32,
+    /// 窗口内容区域的大小
+    pub width: u32,
+    pub height: u32,
+    #[serde(default)]
+    pub maximized: bool,
+    #[serde(default)]
+    pub fullscreen: bool,
+    /// 窗口所在显示器的名称
+    #[serde(default)]
+    pub monitor: Option<String>,
+}
+
+impl WindowGeometry {
+    /// 让窗口落在当前的显示器上
+    ///
+    /// 记录的显示器仍然存在且窗口足够可见时保持原样；否则把窗口移到记录的显示器
+    /// （不存在时使用主显示器，系统没有报告主显示器时使用第一个显示器）的中央。
+    /// 尺寸不会超过目标显示器。没有任何显示器信息时原样返回。
+    pub fn clamp(&self, monitors: &[MonitorArea], primary: Option<&MonitorArea>) -> WindowGeometry {
+        let Some(primary) = primary.or(monitors.first()) else {
+            return self.clone();
+        };
+        let saved = self
+            .monitor
+            .as_ref()
+            .and_then(|name| monitors.iter().find(|m| m.name.as_ref() == Some(name)));
+        let target = saved.unwrap_or(primary);
+
+        let mut geometry = self.clone();
+        geometry.width = self.width.clamp(MIN_SIZE.min(target.width), target.width);
+        geometry.height = self
+            .height
+            .clamp(MIN_SIZE.min(target.height), target.height);
+        geometry.monitor = target.name.clone();
+
+        let visible = monitors.iter().any(|monitor| {
+            let (width, height) =
+                monitor.overlap(geometry.x, geometry.y, geometry.width, geometry.height);
+            width >= MIN_VISIBLE.min(geometry.width as i32)
+                && height >= MIN_VISIBLE.min(geometry.height as i32)
+        });
+        // 标题栏在显示器上方时用户无法拖动窗口
+        let title_visible = monitors.iter().any(|monitor| {
+            geometry.y >= monitor.y
+                && geometry.y < monitor.y + monitor.height as i32
+                && monitor.overlap(geometry.x, geometry.y, geometry.width, 1).0 > 0
+        });
+        if saved.is_none() || !visible || !title_visible {
+            geometry.x = target.x + (target.width - geometry.width) as i32 / 2;
+            geometry.y = target.y + (target.height - geometry.height) as i32 / 2;
+        }
+        geometry
+    }
+}
+
+/// 所有窗口的位置记录，以窗口标签为键
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct GeometryStore(pub BTreeMap<String, WindowGeometry>);
+
+impl GeometryStore {
+    /// 从应用配置目录加载，文件不存在或无法解析时为空
+    pub fn load<R: Runtime>(app: &AppHandle<R>) -> Self {
+        let Ok(path) = state_path(app) else {
+            return Self::default();
+        };
+        match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|error| {
+                log::warn!("窗口位置记录 {:?} 无法解析，忽略: {}", path, error);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// 保存到应用配置目录
+    pub fn save<R: Runtime>(&self, app: &AppHandle<R>) -> AppResult<()> {
+        let path = state_path(app)?;
+        let content = serde_json::to_string_pretty(self).map_err(AppError::internal)?;
+        write_atomic(&path, content)?;
+        Ok(())
+    }
+}
+
+fn state_path<R: Runtime>(app: &AppHandle<R>) -> AppResult<PathBuf> {
+    Ok(app.path().app_config_dir()?.join(STATE_FILE))
+}
+
+/// 由 Tauri 管理的窗口位置记录
+pub struct GeometryState(pub Mutex<GeometryStore>);
+
+impl GeometryState {
+    pub fn new(store: GeometryStore) -> Self {
+        Self(Mutex::new(store))
+    }
+
+    pub fn get(&self, label: &str) -> Option<WindowGeometry> {
+        self.0.lock().unwrap().0.get(label).cloned()
+    }
+}
+
+/// 读取窗口当前的位置和大小
+///
+/// 最大化或全屏时位置和大小是整个屏幕，此时保留上一次记录的普通状态下的位置和大小，
+/// 这样取消最大化后窗口仍然回到原来的位置。
+fn capture<R: Runtime>(
+    window: &WebviewWindow<R>,
+    previous: Option<&WindowGeometry>,
+) -> AppResult<WindowGeometry> {
+    let maximized = window.is_maximized()?;
+    let fullscreen = window.is_fullscreen()?;
+    let monitor = window
+        .current_monitor()?
+        .and_then(|monitor| monitor.name().cloned());
+    let (x, y, width, height) = match previous {
+        Some(previous) if maximized || fullscreen => {
+            (previous.x, previous.y, previous.width, previous.height)
+        }
+        _ => {
+            let position = window.outer_position()?;
+            let size = window.inner_size()?;
+            (position.x, position.y, size.width, size.height)
+        }
+    };
+    Ok(WindowGeometry {
+        x,
+        y,
+        width,
+        height,
+        maximized,
+        fullscreen,
+        monitor,
+    })
+}
+
+/// 记录窗口当前的位置和大小，`persist` 为 true 时同时写入文件
+pub fn save<R: Runtime>(window: &WebviewWindow<R>, persist: bool) {
+    let app = window.app_handle();
+    let Some(state) = app.try_state::<GeometryState>() else {
+        return;
+    };
+    // 最小化的窗口位置没有意义，保留上一次的记录
+    if window.is_minimized().unwrap_or(false) {
+        if persist {
+            persist_all(app);
+        }
+        return;
+    }
+    let label = window.label().to_string();
+    let previous = state.get(&label);
+    match capture(window, previous.as_ref()) {
+        Ok(geometry) => {
+            state.0.lock().unwrap().0.insert(label, geometry);
+        }
+        Err(error) => log::warn!("读取窗口 {} 的位置失败: {}", label, error),
+    }
+    if persist {
+        persist_all(app);
+    }
+}
+
+/// 把所有窗口的位置记录写入文件，退出应用前调用
+pub fn persist_all<R: Runtime>(app: &AppHandle<R>) {
+    let Some(state) = app.try_state::<GeometryState>() else {
+        return;
+    };
+    let store = state.0.lock().unwrap().clone();
+    if let Err(error) = store.save(app) {
+        log::warn!("保存窗口位置失败: {}", error);
+    }
+}
+
+/// 记录所有窗口（启动画面除外）当前的位置和大小并写入文件
+///
+/// 退出应用时调用，托盘“退出”等不经过窗口关闭事件的退出方式也能保存最后的位置。
+pub fn save_all<R: Runtime>(app: &AppHandle<R>) {
+    for (label, window) in app.webview_windows() {
+        if label != SPLASH_WINDOW {
+            save(&window, false);
+        }
+    }
+    persist_all(app);
+}
+
+/// 所有窗口的位置记录，仍然打开的窗口（启动画面除外）使用当前的位置和大小
+///
+/// 只读取窗口的位置，不修改已保存的记录，用于导出和预览设置档案
+pub fn snapshot<R: Runtime>(app: &AppHandle<R>) -> Option<GeometryStore> {
+    let state = app.try_state::<GeometryState>()?;
+    let mut store = state.0.lock().unwrap().clone();
+    for (label, window) in app.webview_windows() {
+        if label == SPLASH_WINDOW || window.is_minimized().unwrap_or(false) {
+            continue;
+        }
+        match capture(&window, store.0.get(&label)) {
+            Ok(geometry) => {
+                store.0.insert(label, geometry);
+            }
+            Err(error) => log::warn!("读取窗口 {} 的位置失败: {}", label, error),
+        }
+    }
+    Some(store)
+}
+
+/// 恢复窗口上一次的位置和大小，没有记录时保持 tauri.conf.json5 中的配置
+pub fn restore<R: Runtime>(window: &WebviewWindow<R>) -> AppResult<()> {
+    let Some(saved) = window
+        .app_handle()
+        .try_state::<GeometryState>()
+        .and_then(|state| state.get(window.label()))
+    else {
+        return Ok(());
+    };
+    let monitors: Vec<MonitorArea> = window
+        .available_monitors()?
+        .iter()
+        .map(MonitorArea::from)
+        .collect();
+    let primary = window.primary_monitor()?.as_ref().map(MonitorArea::from);
+    let geometry = saved.clamp(&monitors, primary.as_ref());
+    window.set_size(PhysicalSize::new(geometry.width, geometry.height))?;
+    window.set_position(PhysicalPosition::new(geometry.x, geometry.y))?;
+    if geometry.maximized {
+        window.maximize()?;
+    }
+    if geometry.fullscreen {
+        window.set_fullscreen(true)?;
+    }
+    Ok(())
+}
+
+/// 恢复窗口的位置和大小，并在窗口移动、缩放和关闭时记录
+pub fn track<R: Runtime>(window: &WebviewWindow<R>) {
+    if let Err(error) = restore(window) {
+        log::warn!("恢复窗口 {} 的位置失败: {}", window.label(), error);
+    }
+    let handle = window.clone();
+    window.on_window_event(move |event| match event {
+        WindowEvent::Moved(_) | WindowEvent::Resized(_) => save(&handle, false),
+        WindowEvent::CloseRequested { .. } => save(&handle, true),
+        WindowEvent::Destroyed => persist_all(handle.app_handle()),
+        _ => {}
+    });
+}
@@ -0,0 +1,210 @@
+//! # 窗口管理
+//!
+//! 统一负责窗口的显示、隐藏、聚焦以及最小化/关闭到托盘。托盘、单实例插件和启动画面
+//! 都通过本模块操作主窗口，不再各自调用 `get_webview_window("main").unwrap()`。
+//!
+//! - 窗口不存在时返回 [`ErrorCode::WindowNotFound`]，不会导致应用崩溃
+//! - 操作的结果由 [`transition`] 根据用户的 [`WindowSettings`] 计算
+//! - 窗口的可见性变化后发出 [`VISIBILITY_CHANGED_EVENT`] 事件，托盘据此切换“打开”和“隐藏”菜单项
+//! - 除启动画面外，所有窗口的位置和大小由 [`geometry`] 在关闭时记录、下次打开时恢复
+//! - 设置、关于、订单详情等其他窗口在 [`registry`] 中登记，由 Rust 创建和复用
+
+pub mod geometry;
+pub mod registry;
+pub mod settings;
+pub mod state;
+
+pub use geometry::{GeometryState, GeometryStore, MonitorArea, WindowGeometry};
+pub use registry::{OpenWindowRequest, ResolvedWindow, WindowRegistry, WindowSpec};
+pub use settings::WindowSettings;
+pub use state::{transition, WindowAction, WindowVisibility};
+
+use crate::core::locale::tr;
+use crate::core::splashscreen::SPLASH_WINDOW;
+use crate::core::tray::TRAY_ID;
+use crate::models::{AppError, AppResult, ErrorCode};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, Runtime, WebviewWindow, WindowEvent};
+
+/// 主窗口标签，与 tauri.conf.json5 中的配置保持一致
+pub const MAIN_WINDOW: &str = "main";
+
+/// 窗口可见性变化事件
+pub const VISIBILITY_CHANGED_EVENT: &str = "window://visibility";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VisibilityChangedPayload {
+    pub label: String,
+    pub visibility: WindowVisibility,
+}
+
+/// 最近一次通知的窗口可见性，避免重复发出相同的事件
+#[derive(Default)]
+struct VisibilityTracker(Mutex<HashMap<String, WindowVisibility>>);
+
+/// 恢复窗口的位置和大小，并让主窗口按照设置处理关闭和最小化
+pub fn init<R: Runtime>(app: &AppHandle<R>) {
+    app.manage(VisibilityTracker::default());
+    app.manage(GeometryState::new(GeometryStore::load(app)));
+    registry::init(app);
+    // 启动画面始终居中显示，不需要记录位置
+    for (label, window) in app.webview_windows() {
+        if label != SPLASH_WINDOW {
+            geometry::track(&window);
+        }
+    }
+    let Some(window) = app.get_webview_window(MAIN_WINDOW) else {
+        log::warn!("主窗口不存在，跳过窗口事件监听");
+        return;
+    };
+    let handle = app.clone();
+    window.on_window_event(move |event| match event {
+        WindowEvent::CloseRequested { api, .. } => {
+            // 没有托盘时隐藏窗口将无法找回，只能正常关闭
+            let settings = settings::current(&handle);
+            let target = transition(WindowVisibility::Visible, WindowAction::Close, &settings);
+            if target == WindowVisibility::Hidden && handle.tray_by_id(TRAY_ID).is_some() {
+                api.prevent_close();
+                if let Err(error) = apply(&handle, MAIN_WINDOW, WindowAction::Hide) {
+                    log::warn!("隐藏主窗口失败: {}", error);
+                }
+            }
+        }
+        WindowEvent::Resized(_) => {
+            let minimized = handle
+                .get_webview_window(MAIN_WINDOW)
+                .and_then(|window| window.is_minimized().ok())
+                .unwrap_or(false);
+            if minimized
+                && settings::current(&handle).minimize_to_tray
+                && handle.tray_by_id(TRAY_ID).is_some()
+            {
+                if let Err(error) = apply(&handle, MAIN_WINDOW, WindowAction::Minimize) {
+                    log::warn!("最小化主窗口到托盘失败: {}", error);
+                }
+            } else {
+                notify(&handle, MAIN_WINDOW);
+            }
+        }
+        WindowEvent::Focused(_) | WindowEvent::Destroyed => notify(&handle, MAIN_WINDOW),
+        _ => {}
+    });
+}
+
+fn get_window<R: Runtime>(app: &AppHandle<R>, label: &str) -> AppResult<WebviewWindow<R>> {
+    app.get_webview_window(label).ok_or_else(|| {
+        AppError::new(
+            ErrorCode::WindowNotFound,
+            tr("errors.windowNotFound", &[("label", label)]),
+        )
+        .with_details(label)
+    })
+}
+
+/// 窗口当前的可见性
+pub fn visibility<R: Runtime>(app: &AppHandle<R>, label: &str) -> WindowVisibility {
+    let Some(window) = app.get_webview_window(label) else {
+        return WindowVisibility::Missing;
+    };
+    if window.is_minimized().unwrap_or(false) {
+        WindowVisibility::Minimized
+    } else if window.is_visible().unwrap_or(false) {
+        WindowVisibility::Visible
+    } else {
+        WindowVisibility::Hidden
+    }
+}
+
+/// 对窗口执行操作，返回操作后的可见性
+pub fn apply<R: Runtime>(
+    app: &AppHandle<R>,
+    label: &str,
+    action: WindowAction,
+) -> AppResult<WindowVisibility> {
+    let window = get_window(app, label)?;
+    let target = transition(visibility(app, label), action, &settings::current(app));
+    match target {
+        WindowVisibility::Visible => {
+            window.unminimize()?;
+            window.show()?;
+            window.set_focus()?;
+        }
+        WindowVisibility::Minimized => window.minimize()?,
+        WindowVisibility::Hidden => {
+            // 隐藏到托盘的窗口可能直到退出都不会再关闭，提前记录位置
+            geometry::save(&window, true);
+            window.hide()?;
+        }
+        WindowVisibility::Missing => window.close()?,
+    }
+    notify(app, label);
+    Ok(target)
+}
+
+/// 显示、取消最小化并聚焦窗口
+pub fn show<R: Runtime>(app: &AppHandle<R>, label: &str) -> AppResult<WindowVisibility> {
+    apply(app, label, WindowAction::Show)
+}
+
+/// 隐藏窗口到托盘
+pub fn hide<R: Runtime>(app: &AppHandle<R>, label: &str) -> AppResult<WindowVisibility> {
+    apply(app, label, WindowAction::Hide)
+}
+
+/// 显示并聚焦主窗口，主窗口不存在时只记录日志
+pub fn show_main<R: Runtime>(app: &AppHandle<R>) {
+    if let Err(error) = show(app, MAIN_WINDOW) {
+        log::warn!("显示主窗口失败: {}", error);
+    }
+}
+
+/// 隐藏主窗口到托盘，主窗口不存在时只记录日志
+pub fn hide_main<R: Runtime>(app: &AppHandle<R>) {
+    if let Err(error) = hide(app, MAIN_WINDOW) {
+        log::warn!("隐藏主窗口失败: {}", error);
+    }
+}
+
+/// 窗口的可见性与上一次通知的不同时发出 [`VISIBILITY_CHANGED_EVENT`] 事件
+pub fn notify<R: Runtime>(app: &AppHandle<R>, label: &str) {
+    let visibility = visibility(app, label);
+    if let Some(tracker) = app.try_state::<VisibilityTracker>() {
+        let previous = tracker
+            .0
+            .lock()
+            .unwrap()
+            .insert(label.to_string(), visibility);
+        if previous == Some(visibility) {
+            return;
+        }
+    }
+    let _ = app.emit(
+        VISIBILITY_CHANGED_EVENT,
+        VisibilityChangedPayload {
+            label: label.to_string(),
+            visibility,
+        },
+    );
+}
+
+/// 对窗口执行操作，不指定窗口时操作主窗口
+#[tauri::command]
+pub fn window_action<R: Runtime>(
+    app: AppHandle<R>,
+    label: Option<String>,
+    action: WindowAction,
+) -> AppResult<WindowVisibility> {
+    apply(&app, label.as_deref().unwrap_or(MAIN_WINDOW), action)
+}
+
+/// 查询窗口的可见性，不指定窗口时查询主窗口
+#[tauri::command]
+pub fn get_window_visibility<R: Runtime>(
+    app: AppHandle<R>,
+    label: Option<String>,
+) -> WindowVisibility {
+    visibility(&app, label.as_deref().unwrap_or(MAIN_WINDOW))
+}
@@ -0,0 +1,382 @@
+//! # 窗口注册表
+//!
+//! 除主窗口和启动画面外的窗口（设置、关于、订单详情等）都在这里登记，由 Rust 负责创建：
+//! 已经打开的窗口只会被显示并聚焦，不会重复创建。
+//!
+//! - 单例窗口的标签就是窗口类型，例如 `settings`
+//! - 多实例窗口（[`WindowSpec::multiple`]）的标签为 `<类型>-<key>`，例如 `order-detail-1024`，
+//!   路由中的 `{key}` 会被替换为 key
+//!
+//! 每种窗口的权限（包括可以调用的应用命令）在 `capabilities/` 下按窗口标签单独配置，
+//! 多实例窗口使用 `order-detail-*` 这样的通配符。
+
+use super::{geometry, notify, show, MAIN_WINDOW};
+use crate::core::locale::{self, tr, translate, LocaleChangedPayload, LOCALE_CHANGED_EVENT};
+use crate::core::tray::recent::{NavigatePayload, NAVIGATE_EVENT};
+use crate::models::{AppError, AppResult};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::{
+    AppHandle, Emitter, Listener, Manager, Runtime, WebviewUrl, WebviewWindowBuilder, WindowEvent,
+};
+
+/// 设置窗口
+pub const SETTINGS_WINDOW: &str = "settings";
+/// 关于窗口
+pub const ABOUT_WINDOW: &str = "about";
+/// 订单详情窗口，每个订单一个
+pub const ORDER_DETAIL_WINDOW: &str = "order-detail";
+/// 在新窗口中打开任意路由
+pub const PAGE_WINDOW: &str = "page";
+
+/// 一种窗口的创建参数
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowSpec {
+    kind: String,
+    route: String,
+    title_key: String,
+    width: f64,
+    height: f64,
+    resizable: bool,
+    multiple: bool,
+}
+
+impl WindowSpec {
+    /// 创建一个单例窗口，默认大小为 800x600，可以调整大小
+    pub fn new(kind: impl Into<String>, route: impl Into<String>) -> Self {
+        Self {
+            kind: kind.into(),
+            route: route.into(),
+            title_key: "common.windows.main".to_string(),
+            width: 800.0,
+            height: 600.0,
+            resizable: true,
+            multiple: false,
+        }
+    }
+
+    /// 窗口标题在语言包中的键名，切换语言时自动更新
+    pub fn title_key(mut self, key: impl Into<String>) -> Self {
+        self.title_key = key.into();
+        self
+    }
+
+    /// 窗口的初始大小，之后以用户调整后的大小为准
+    pub fn size(mut self, width: f64, height: f64) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    /// 禁止调整窗口大小
+    pub fn fixed_size(mut self) -> Self {
+        self.resizable = false;
+        self
+    }
+
+    /// 允许同时打开多个窗口，以 key 区分
+    pub fn multiple(mut self) -> Self {
+        self.multiple = true;
+        self
+    }
+
+    /// 窗口类型
+    pub fn kind(&self) -> &str {
+        &self.kind
+    }
+}
+
+/// 打开窗口的请求
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenWindowRequest {
+    /// 窗口类型
+    pub kind: String,
+    /// 多实例窗口的 key，例如订单号；`page` 窗口不指定时根据路由生成
+    #[serde(default)]
+    pub key: Option<String>,
+    /// 要打开的路由，不指定时使用窗口类型的默认路由
+    #[serde(default)]
+    pub route: Option<String>,
+}
+
+/// 解析后的窗口标签和路由
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolvedWindow {
+    pub label: String,
+    pub route: String,
+}
+
+/// 所有可以由 Rust 创建的窗口类型
+#[derive(Debug, Clone)]
+pub struct WindowRegistry {
+    specs: HashMap<String, WindowSpec>,
+}
+
+impl Default for WindowRegistry {
+    fn default() -> Self {
+        let mut registry = Self {
+            specs: HashMap::new(),
+        };
+        registry.register(
+            WindowSpec::new(SETTINGS_WINDOW, "/settings")
+                .title_key("common.windows.settings")
+                .size(800.0, 600.0),
+        );
+        registry.register(
+            WindowSpec::new(ABOUT_WINDOW, "/about")
+                .title_key("common.windows.about")
+                .size(420.0, 360.0)
+                .fixed_size(),
+        );
+        registry.register(
+            WindowSpec::new(ORDER_DETAIL_WINDOW, "/orders/{key}")
+                .title_key("common.windows.orderDetail")
+                .size(900.0, 700.0)
+                .multiple(),
+        );
+        registry.register(
+            WindowSpec::new(PAGE_WINDOW, "/")
+                .title_key("common.windows.main")
+                .size(1000.0, 700.0)
+                .multiple(),
+        );
+        registry
+    }
+}
+
+impl WindowRegistry {
+    /// 登记一种窗口，同名的窗口类型会被替换
+    pub fn register(&mut self, spec: WindowSpec) {
+        self.specs.insert(spec.kind.clone(), spec);
+    }
+
+    pub fn get(&self, kind: &str) -> Option<&WindowSpec> {
+        self.specs.get(kind)
+    }
+
+    /// 根据窗口标签找到窗口类型
+    pub fn spec_for_label(&self, label: &str) -> Option<&WindowSpec> {
+        self.specs.values().find(|spec| {
+            if spec.multiple {
+                label
+                    .strip_prefix(spec.kind.as_str())
+                    .is_some_and(|rest| rest.starts_with('-'))
+            } else {
+                label == spec.kind
+            }
+        })
+    }
+
+    /// 计算请求对应的窗口标签和路由
+    pub fn resolve(&self, request: &OpenWindowRequest) -> AppResult<(&WindowSpec, ResolvedWindow)> {
+        let spec = self.get(&request.kind).ok_or_else(|| {
+            AppError::invalid_argument(tr(
+                "errors.unknownWindow",
+                &[("kind", request.kind.as_str())],
+            ))
+        })?;
+
+        let key = match (&request.key, &request.route) {
+            (Some(key), _) => Some(key.trim().to_string()),
+            // 没有 key 时以路由区分窗口，同一路由只打开一个窗口
+            (None, Some(route)) if spec.multiple => Some(key_from_route(route)),
+            _ => None,
+        };
+        let label = match (&key, spec.multiple) {
+            (_, false) => spec.kind.clone(),
+            (Some(key), true) if is_valid_key(key) => format!("{}-{}", spec.kind, key),
+            (key, true) => {
+                let key = key.as_deref().unwrap_or_default();
+                return Err(AppError::invalid_argument(tr(
+                    "errors.invalidWindowKey",
+                    &[("key", key)],
+                )));
+            }
+        };
+
+        let route = match &request.route {
+            Some(route) => route.clone(),
+            None => spec
+                .route
+                .replace("{key}", key.as_deref().unwrap_or_default()),
+        };
+        if !route.starts_with('/') {
+            return Err(AppError::invalid_argument(tr(
+                "errors.invalidRoute",
+                &[("route", route.as_str())],
+            )));
+        }
+        Ok((spec, ResolvedWindow { label, route }))
+    }
+}
+
+/// key 只能包含字母、数字、`-` 和 `_`，保证窗口标签合法且可以被 capabilities 中的通配符匹配
+fn is_valid_key(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// 把路由转换为合法且不会冲突的 key，例如 `/dashboard/workbench` 转换为 `dashboard-workbench`
+///
+/// 去掉开头的 `/` 后，字母和数字保持不变，`/` 转换为 `-`，其他字节（包括 `-` 和 `_`）转换为
+/// `_` 加两位十六进制，例如 `/a-b` 转换为 `a_2Db`，不同的路由不会得到相同的 key。
+/// 根路由转换为 `_`，其他路由转换后的 `_` 后面总是跟着两位十六进制，不会与之相同。
+fn key_from_route(route: &str) -> String {
+    let path = route.strip_prefix('/').unwrap_or(route);
+    if path.is_empty() {
+        return "_".to_string();
+    }
+    let mut key = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        match byte {
+            b'/' => key.push('-'),
+            byte if byte.is_ascii_alphanumeric() => key.push(byte as char),
+            byte => key.push_str(&format!("_{:02X}", byte)),
+        }
+    }
+    key
+}
+
+/// 由 Tauri 管理的窗口注册表
+#[derive(Default)]
+pub struct WindowRegistryState(pub Mutex<WindowRegistry>);
+
+/// 登记一种窗口，其他模块可以在 `setup` 中登记自己的窗口
+pub fn register<R: Runtime>(app: &AppHandle<R>, spec: WindowSpec) {
+    if let Some(state) = app.try_state::<WindowRegistryState>() {
+        state.0.lock().unwrap().register(spec);
+    }
+}
+
+/// 注册窗口注册表，并在切换语言后更新已打开窗口的标题
+pub(super) fn init<R: Runtime>(app: &AppHandle<R>) {
+    app.manage(WindowRegistryState::default());
+    let handle = app.clone();
+    app.listen(
+        LOCALE_CHANGED_EVENT,
+        move |event| match serde_json::from_str::<LocaleChangedPayload>(event.payload()) {
+            Ok(payload) => {
+                let registry = handle.state::<WindowRegistryState>();
+                let registry = registry.0.lock().unwrap();
+                for (label, window) in handle.webview_windows() {
+                    if let Some(spec) = registry.spec_for_label(&label) {
+                        let _ = window.set_title(&translate(payload.locale, &spec.title_key, &[]));
+                    }
+                }
+            }
+            Err(error) => log::warn!("无法解析语言切换事件: {}", error),
+        },
+    );
+}
+
+/// 打开窗口：已经打开时显示并聚焦，指定了路由时跳转到该路由；否则创建新窗口
+pub fn open<R: Runtime>(app: &AppHandle<R>, request: &OpenWindowRequest) -> AppResult<String> {
+    let (spec, resolved) = {
+        let state = app.state::<WindowRegistryState>();
+        let registry = state.0.lock().unwrap();
+        let (spec, resolved) = registry.resolve(request)?;
+        (spec.clone(), resolved)
+    };
+
+    if app.get_webview_window(&resolved.label).is_some() {
+        show(app, &resolved.label)?;
+        if request.route.is_some() {
+            app.emit_to(
+                resolved.label.as_str(),
+                NAVIGATE_EVENT,
+                NavigatePayload {
+                    route: resolved.route,
+                },
+            )?;
+        }
+        return Ok(resolved.label);
+    }
+
+    let url = WebviewUrl::App(resolved.route.trim_start_matches('/').into());
+    let title = translate(locale::current(app), &spec.title_key, &[]);
+    let window = WebviewWindowBuilder::new(app, &resolved.label, url)
+        .title(title)
+        .inner_size(spec.width, spec.height)
+        .resizable(spec.resizable)
+        .center()
+        // 恢复位置和大小之后再显示，避免窗口闪动
+        .visible(false)
+        .build()?;
+    geometry::track(&window);
+    let handle = app.clone();
+    let label = resolved.label.clone();
+    window.on_window_event(move |event| {
+        if matches!(event, WindowEvent::Destroyed) {
+            notify(&handle, &label);
+        }
+    });
+    show(app, &resolved.label)?;
+    Ok(resolved.label)
+}
+
+/// 打开设置、关于等窗口，失败时只记录日志，供托盘菜单等没有返回值的调用方使用
+pub fn open_kind<R: Runtime>(app: &AppHandle<R>, kind: &str) {
+    let request = OpenWindowRequest {
+        kind: kind.to_string(),
+        ..OpenWindowRequest::default()
+    };
+    if let Err(error) = open(app, &request) {
+        log::warn!("打开窗口 {} 失败: {}", kind, error);
+    }
+}
+
+// Windows 上在同步命令中创建窗口会死锁，创建窗口的命令必须是异步的
+// 问题: https://github.com/tauri-apps/tauri/issues/11144 https://github.com/tauri-apps/tauri/issues/8196
+
+/// 打开窗口，返回窗口标签
+#[tauri::command]
+pub async fn open_window<R: Runtime>(
+    app: AppHandle<R>,
+    request: OpenWindowRequest,
+) -> AppResult<String> {
+    open(&app, &request)
+}
+
+/// 在新窗口中打开路由，同一路由只打开一个窗口
+#[tauri::command]
+pub async fn open_route_window<R: Runtime>(app: AppHandle<R>, route: String) -> AppResult<String> {
+    open(
+        &app,
+        &OpenWindowRequest {
+            kind: PAGE_WINDOW.to_string(),
+            key: None,
+            route: Some(route),
+        },
+    )
+}
+
+/// 关闭注册表中的窗口，窗口不存在时忽略
+///
+/// 主窗口、启动画面以及其他没有登记的窗口不能通过这里关闭。
+pub fn close<R: Runtime>(app: &AppHandle<R>, label: &str) -> AppResult<()> {
+    let registered = label != MAIN_WINDOW
+        && app
+            .try_state::<WindowRegistryState>()
+            .is_some_and(|state| state.0.lock().unwrap().spec_for_label(label).is_some());
+    if !registered {
+        return Err(AppError::invalid_argument(tr(
+            "errors.cannotCloseWindow",
+            &[("label", label)],
+        )));
+    }
+    if let Some(window) = app.get_webview_window(label) {
+        window.close()?;
+    }
+    Ok(())
+}
+
+/// 关闭设置、关于、订单详情等窗口，窗口不存在时忽略
+#[tauri::command]
+pub fn close_window<R: Runtime>(app: AppHandle<R>, label: String) -> AppResult<()> {
+    close(&app, &label)
+}
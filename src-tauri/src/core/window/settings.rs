@@ -0,0 +1,16 @@
+//! # 窗口行为设置
+//!
+//! 用户可以选择点击关闭按钮或最小化时隐藏到托盘，而不是退出或最小化到任务栏。
+//! 设置保存在用户偏好设置的 `window` 分组中，通过 `update_settings` 修改。
+
+use crate::core::settings::SettingsState;
+use tauri::{AppHandle, Manager, Runtime};
+
+pub use crate::core::settings::WindowSettings;
+
+/// 当前的窗口设置，偏好设置尚未加载时使用默认设置
+pub fn current<R: Runtime>(app: &AppHandle<R>) -> WindowSettings {
+    app.try_state::<SettingsState>()
+        .map(|state| state.get().window)
+        .unwrap_or_default()
+}
@@ -0,0 +1,63 @@
+//! # 窗口可见性状态机
+//!
+//! 窗口只有四种状态：可见、最小化、隐藏到托盘、不存在。所有显示/隐藏窗口的操作
+//! 先由 [`transition`] 根据当前状态和用户设置计算目标状态，再统一执行，
+//! 托盘、单实例和启动画面因此拥有相同的行为。
+
+use super::settings::WindowSettings;
+use serde::{Deserialize, Serialize};
+
+/// 窗口的可见性
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WindowVisibility {
+    /// 显示在屏幕上（不一定有焦点）
+    Visible,
+    /// 最小化到任务栏
+    Minimized,
+    /// 隐藏，只能通过托盘或再次启动应用找回
+    Hidden,
+    /// 窗口不存在或已经关闭
+    Missing,
+}
+
+/// 对窗口的操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WindowAction {
+    /// 显示、取消最小化并聚焦
+    Show,
+    /// 隐藏到托盘
+    Hide,
+    /// 可见时隐藏，否则显示
+    Toggle,
+    /// 最小化；开启“最小化到托盘”时隐藏到托盘
+    Minimize,
+    /// 用户点击关闭按钮；开启“关闭到托盘”时隐藏到托盘
+    Close,
+}
+
+/// 根据当前状态、操作和用户设置计算目标状态
+///
+/// 窗口不存在时任何操作都不会让它出现，结果仍为 [`WindowVisibility::Missing`]。
+pub fn transition(
+    current: WindowVisibility,
+    action: WindowAction,
+    settings: &WindowSettings,
+) -> WindowVisibility {
+    if current == WindowVisibility::Missing {
+        return WindowVisibility::Missing;
+    }
+    match action {
+        WindowAction::Show => WindowVisibility::Visible,
+        WindowAction::Hide => WindowVisibility::Hidden,
+        WindowAction::Toggle => match current {
+            WindowVisibility::Visible => WindowVisibility::Hidden,
+            _ => WindowVisibility::Visible,
+        },
+        WindowAction::Minimize if settings.minimize_to_tray => WindowVisibility::Hidden,
+        WindowAction::Minimize => WindowVisibility::Minimized,
+        WindowAction::Close if settings.close_to_tray => WindowVisibility::Hidden,
+        WindowAction::Close => WindowVisibility::Missing,
+    }
+}
@@ -5,8 +5,9 @@
 //!
 //! ## 功能特性
 //!
-//! - 配置文件管理
-//! - 用户偏好设置
+//! - 配置文件管理，见 [`core::config`]
+//! - 用户偏好设置，见 [`core::settings`]
+//! - 设置档案的导入和导出，见 [`core::profile`]
 //! - 系统信息获取
 //! - 文件操作工具
 //!
@@ -19,125 +20,231 @@ pub mod core;
 pub mod models;
 pub mod utils;
 
+use crate::core::locale::{self, tr};
+use crate::core::splashscreen::{self, SplashConfig};
+use crate::core::startup::{
+    self, StartupOrchestrator, StartupTask, TaskRunners, BACKEND_TASK, FRONTEND_TASK,
+};
 #[cfg(desktop)]
 use crate::core::tray::create_system_tray;
+#[cfg(desktop)]
+use crate::core::updater::{
+    app_updates::PendingUpdate, StagedUpdate, UpdaterSettings, UpdaterState,
+};
+#[cfg(desktop)]
+use crate::core::window;
+use crate::models::AppResult;
 use std::sync::Mutex;
+use std::time::Instant;
 use tauri::async_runtime::spawn;
-use tauri::{AppHandle, Manager, State};
+use tauri::{AppHandle, Manager};
 use tokio::time::{sleep, Duration};
 
 // 了解有关 Tauri 命令的更多信息，请访问 https://tauri.app/develop/calling-rust/
 #[tauri::command]
-fn greet(name: &str) -> String {
-    format!("Hello, {}! 你已经收到了 Rust 的问候!", name)
-}
-
-// 用于设置设置任务状态的自定义任务
-#[tauri::command]
-async fn set_complete(
-    app: AppHandle,
-    state: State<'_, Mutex<SetupState>>,
-    task: String,
-) -> Result<(), ()> {
-    // 在没有写入权限的情况下锁定状态
-    let mut state_lock = state.lock().unwrap();
-    match task.as_str() {
-        "frontend" => state_lock.frontend_task = true,
-        "backend" => state_lock.backend_task = true,
-        _ => panic!("已完成无效任务!"),
-    }
-    println!("设置任务 {} 已完成!", task);
-    // 检查两项任务是否已完成
-    if state_lock.backend_task && state_lock.frontend_task {
-        println!("所有设置任务已完成!");
-        // 设置完成，我们可以关闭启动画面并取消隐藏主窗口！
-
-        // 使用桌面端条件编译
-        #[cfg(desktop)]
-        {
-            // 获取窗口并处理可能的错误
-            let splash_window = app
-                .get_webview_window("splashscreen")
-                .ok_or("无法获取启动画面窗口".to_string())
-                .unwrap();
-            let main_window = app
-                .get_webview_window("main")
-                .ok_or("无法获取主窗口".to_string())
-                .unwrap();
-
-            // 关闭启动画面并显示主窗口
-            let _ = splash_window.close();
-            let _ = main_window.show();
-            let _ = main_window.set_focus();
-        }
-    }
-    Ok(())
+fn greet(name: &str) -> AppResult<String> {
+    Ok(tr("common.greet", &[("name", name)]))
 }
 
 // 执行一些繁重设置任务的异步函数
-async fn setup(app: AppHandle) -> Result<(), ()> {
-    // 假动作 3 秒
-    println!("执行非常繁重的后端设置任务...");
-    sleep(Duration::from_secs(3)).await;
-    println!("后端设置任务已完成!");
-    // 将后端任务设置为已完成
-    // 命令可以作为常规函数运行，只要您自己处理输入参数
-    set_complete(
-        app.clone(),
-        app.state::<Mutex<SetupState>>(),
-        "backend".to_string(),
-    )
-    .await?;
+async fn setup(app: AppHandle) -> Result<(), String> {
+    // 假动作 3 秒，期间向启动画面汇报进度
+    log::info!("执行非常繁重的后端设置任务...");
+    for step in 1..=3u8 {
+        sleep(Duration::from_secs(1)).await;
+        let _ =
+            startup::report_progress(&app, BACKEND_TASK, step * 33, Some("正在初始化后端服务..."));
+    }
+    log::info!("后端设置任务已完成!");
     Ok(())
 }
 
-// 创建一个结构，我们将用于跟踪设置相关任务的完成情况
-struct SetupState {
-    frontend_task: bool,
-    backend_task: bool,
-}
-
 // 我们在版本 2 移动兼容应用程序中的主要入口点
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    // 日志输出需要最先安装，读取配置后由 config::init 设置日志级别
+    utils::logger::init();
+
     // 不要在 Tauri 启动之前编写代码，而是将其编写在设置钩子中！
-    tauri::Builder::default()
+    let builder = tauri::Builder::default();
+
+    // 添加一个单实例插件，用于防止多个实例运行。使用单实例插件确保 Tauri 应用程序在同一时间只运行单个实例
+    // 详情请查看 https://v2.tauri.org.cn/plugin/single-instance/
+    // 单实例插件必须第一个注册：再次启动的进程在这里就会退出，不会加载设置、创建托盘或者监视配置文件。
+    // 已有的实例显示并聚焦主窗口，并把命令行参数和工作目录转发给前端
+    #[cfg(desktop)]
+    let builder = builder.plugin(tauri_plugin_single_instance::init(|app, args, cwd| {
+        core::instance::handle_second_instance(app, args, cwd);
+    }));
+
+    builder
         // 注册一个由 Tauri 管理的 “状态”
         // 我们需要对它的写访问权限，因此我们将其包装在 “互斥体” 中
-        .manage(Mutex::new(SetupState {
-            frontend_task: false,
-            backend_task: false,
-        }))
+        .manage(Mutex::new(StartupOrchestrator::new()))
+        .manage(TaskRunners::default())
         // 添加一个命令，我们可以使用它来检查
-        .invoke_handler(tauri::generate_handler![greet, set_complete])
+        .invoke_handler(tauri::generate_handler![
+            greet,
+            startup::set_complete,
+            startup::set_progress,
+            startup::set_failed,
+            splashscreen::splash_retry,
+            splashscreen::splash_quit,
+            locale::get_locale,
+            locale::set_locale,
+            core::settings::get_settings,
+            core::settings::update_settings,
+            core::settings::reset_settings,
+            core::config::get_effective_config,
+            #[cfg(desktop)]
+            core::tray::tray_menu::get_tray_menu,
+            #[cfg(desktop)]
+            core::tray::tray_menu::set_tray_menu,
+            #[cfg(desktop)]
+            core::tray::status::get_tray_status,
+            #[cfg(desktop)]
+            core::tray::status::update_tray_status,
+            #[cfg(desktop)]
+            core::tray::recent::get_recent_items,
+            #[cfg(desktop)]
+            core::tray::recent::add_recent_item,
+            #[cfg(desktop)]
+            core::tray::recent::remove_recent_item,
+            #[cfg(desktop)]
+            core::tray::recent::clear_recent_items,
+            #[cfg(desktop)]
+            core::tray::recent::set_quick_actions,
+            #[cfg(desktop)]
+            core::updater::app_updates::fetch_update,
+            #[cfg(desktop)]
+            core::updater::app_updates::install_update,
+            #[cfg(desktop)]
+            core::updater::app_updates::restart_to_update,
+            #[cfg(desktop)]
+            core::updater::app_updates::get_staged_update,
+            #[cfg(desktop)]
+            core::updater::health::get_update_health,
+            #[cfg(desktop)]
+            core::updater::health::confirm_update_health,
+            #[cfg(desktop)]
+            core::updater::health::rollback_update,
+            #[cfg(desktop)]
+            core::updater::config::get_updater_settings,
+            #[cfg(desktop)]
+            core::updater::config::set_updater_settings,
+            #[cfg(desktop)]
+            core::updater::config::skip_update_version,
+            #[cfg(desktop)]
+            core::window::window_action,
+            #[cfg(desktop)]
+            core::window::get_window_visibility,
+            #[cfg(desktop)]
+            core::window::registry::open_window,
+            #[cfg(desktop)]
+            core::window::registry::open_route_window,
+            #[cfg(desktop)]
+            core::window::registry::close_window,
+            #[cfg(desktop)]
+            core::instance::take_launch_args,
+            #[cfg(desktop)]
+            core::deeplink::take_deep_links,
+            #[cfg(desktop)]
+            core::profile::export_profile,
+            #[cfg(desktop)]
+            core::profile::preview_profile,
+            #[cfg(desktop)]
+            core::profile::import_profile
+        ])
         // 使用设置挂钩执行与设置相关的任务
         // 在主循环之前运行，因此尚未创建任何窗口
         .setup(|app| {
+            // 暂存本次启动的参数，再次启动时转发的参数也先暂存，直到前端通过 take_launch_args 取走
+            #[cfg(desktop)]
+            core::instance::init(app.handle());
+
+            // 启动画面负责展示启动进度，并在启动失败时提供重试/退出
+            splashscreen::init(app.handle(), SplashConfig::default());
+
+            // 注册启动任务，其他模块也可以在这里通过 startup::register_task 注册自己的任务
+            startup::register_task(
+                app.handle(),
+                StartupTask::new(FRONTEND_TASK).timeout(Duration::from_secs(60)),
+            )?;
+            startup::register_task(
+                app.handle(),
+                StartupTask::new(BACKEND_TASK)
+                    .timeout(Duration::from_secs(30))
+                    .weight(2),
+            )?;
+            {
+                let state = app.state::<Mutex<StartupOrchestrator>>();
+                let mut orchestrator = state.lock().unwrap();
+                orchestrator.validate()?;
+                // 前端任务由 splashscreen.html 上报完成，超时时间从应用启动开始计算
+                orchestrator.start(FRONTEND_TASK, Instant::now())?;
+            }
+
             // 生成设置作为非阻塞任务，以便在执行时可以创建和运行窗口
-            spawn(setup(app.handle().clone()));
+            startup::spawn_task(app.handle(), BACKEND_TASK, setup);
+            spawn(startup::watch_timeouts(app.handle().clone()));
+
+            // 界面语言需要在创建托盘之前加载，托盘菜单的文字依赖当前语言
+            locale::init(app.handle());
+
+            // 用户偏好设置需要在前端读取之前加载
+            core::settings::init(app.handle());
+
+            // 分层配置需要在创建托盘和检查更新之前加载
+            core::config::init(app.handle());
 
-            // 添加一个异步任务，用于检查更新
-            let handle = app.handle().clone();
-            spawn(async move {
-                core::updater::update(handle).await.unwrap();
-            });
+            // 窗口管理需要在创建托盘之前初始化，托盘根据主窗口的可见性切换菜单项
+            #[cfg(desktop)]
+            window::init(app.handle());
 
+            // 预览过的设置档案保存在内存中，导入时应用同一份档案
             #[cfg(desktop)]
-            create_system_tray(app);
+            core::profile::init(app.handle());
 
-            // 添加一个单实例插件，用于防止多个实例运行。使用单实例插件确保 Tauri 应用程序在同一时间只运行单个实例
-            // 详情请查看 https://v2.tauri.org.cn/plugin/single-instance/
             #[cfg(desktop)]
-            app.handle()
-                .plugin(tauri_plugin_single_instance::init(|_app, _args, _cwd| {}))
-                .expect("TODO: panic message");
+            create_system_tray(app.handle())?;
+
+            // 托盘创建之后开始监视配置文件和设置文件，修改后的托盘标题等配置可以立即生效
+            core::config::watcher::watch(app.handle());
+
+            // 深度链接插件需要在单实例插件之后注册，命令行参数中的深度链接由单实例插件转发
+            #[cfg(desktop)]
+            {
+                app.handle().plugin(tauri_plugin_deep_link::init())?;
+                core::deeplink::init(app.handle());
+            }
 
             // 添加更新插件，允许您检查更新并下载更新。使用更新服务器或静态 JSON 自动更新你的 Tauri 应用程序
             // 详情请查看 https://v2.tauri.org.cn/plugin/updater/
             #[cfg(desktop)]
-            app.handle()
-                .plugin(tauri_plugin_updater::Builder::new().build())
-                .expect("TODO: panic message");
+            {
+                app.handle()
+                    .plugin(tauri_plugin_updater::Builder::new().build())?;
+                // 更新通道和端点从用户配置中读取
+                let settings = UpdaterSettings::load(app.handle());
+                app.manage(UpdaterState::new(settings));
+                app.manage(PendingUpdate::default());
+                app.manage(StagedUpdate::default());
+                // 之前下载的暂存更新在没有网络时也可以在重启或退出时安装
+                core::updater::staging::restore(app.handle());
+
+                // 记录本次启动，更新后的新版本需要前端确认正常启动
+                if let Err(error) = core::updater::health::record_launch(app.handle()) {
+                    log::warn!("记录更新健康状态失败: {}", error);
+                }
+
+                // 添加一个异步任务，按照用户偏好检查更新
+                let handle = app.handle().clone();
+                spawn(async move {
+                    if let Err(error) = core::updater::update(handle).await {
+                        log::warn!("检查更新失败: {}", error);
+                    }
+                });
+            }
 
             // 添加 NFC 插件，允许您使用 Tauri 创建 NFC 标签。本机 NFC 标签，用于读取和写入 NFC 标签。
             // 在 Android 和 iOS 上读取和写入 NFC 标签
@@ -161,6 +268,15 @@ pub fn run() {
         // 添加对话框插件，允许您使用 Tauri 创建对话框。本机系统对话框，用于打开和保存文件，以及消息对话框。
         // 详情请查看 https://tauri.app/zh-cn/plugin/dialog/
         .plugin(tauri_plugin_dialog::init())
-        .run(tauri::generate_context!())
-        .expect("运行 Tauri 应用程序时出错");
+        .build(tauri::generate_context!())
+        .expect("运行 Tauri 应用程序时出错")
+        .run(|app, event| {
+            // 退出前记录所有窗口的位置和大小，通过托盘退出时窗口不会收到关闭事件
+            if let tauri::RunEvent::ExitRequested { .. } = event {
+                #[cfg(desktop)]
+                window::geometry::save_all(app);
+                #[cfg(not(desktop))]
+                let _ = app;
+            }
+        });
 }
@@ -0,0 +1,201 @@
+//! # 命令错误类型
+//!
+//! 所有 Tauri 命令统一返回 [`AppError`]。它会被序列化为结构化对象：
+//!
+//! ```json
+//! { "code": "UNKNOWN_TASK", "category": "startup", "message": "未知的启动任务", "details": "database" }
+//! ```
+//!
+//! 前端可以根据 `code` 或 `category` 分支处理，`message` 可以直接展示给用户，`details` 仅用于排查问题。
+
+use crate::core::locale::tr;
+use crate::core::startup::StartupError;
+use serde::Serialize;
+use std::fmt::Display;
+
+/// 错误分类，便于前端按类别统一处理
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ErrorCategory {
+    /// 参数校验失败
+    Validation,
+    /// 启动任务编排
+    Startup,
+    /// 应用更新
+    Updater,
+    /// 窗口管理
+    Window,
+    /// 系统托盘
+    Tray,
+    /// 文件读写
+    Io,
+    /// 未预期的内部错误
+    Internal,
+}
+
+/// 错误码，前端以此为依据进行分支处理，新增错误码时不要修改已有错误码的名称
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    InvalidArgument,
+    UnknownTask,
+    UnknownDependency,
+    DuplicateTask,
+    DependencyNotReady,
+    DependencyFailed,
+    TaskAlreadyFinished,
+    UpdateFailed,
+    NoPendingUpdate,
+    UpdateVerificationFailed,
+    RollbackUnavailable,
+    WindowNotFound,
+    TrayNotFound,
+    InvalidDeepLink,
+    InvalidConfig,
+    InvalidProfile,
+    Io,
+    Internal,
+}
+
+impl ErrorCode {
+    /// 错误码所属的分类
+    pub fn category(self) -> ErrorCategory {
+        match self {
+            ErrorCode::InvalidArgument
+            | ErrorCode::InvalidDeepLink
+            | ErrorCode::InvalidConfig
+            | ErrorCode::InvalidProfile => ErrorCategory::Validation,
+            ErrorCode::UnknownTask
+            | ErrorCode::UnknownDependency
+            | ErrorCode::DuplicateTask
+            | ErrorCode::DependencyNotReady
+            | ErrorCode::DependencyFailed
+            | ErrorCode::TaskAlreadyFinished => ErrorCategory::Startup,
+            ErrorCode::UpdateFailed
+            | ErrorCode::NoPendingUpdate
+            | ErrorCode::UpdateVerificationFailed
+            | ErrorCode::RollbackUnavailable => ErrorCategory::Updater,
+            ErrorCode::WindowNotFound => ErrorCategory::Window,
+            ErrorCode::TrayNotFound => ErrorCategory::Tray,
+            ErrorCode::Io => ErrorCategory::Io,
+            ErrorCode::Internal => ErrorCategory::Internal,
+        }
+    }
+}
+
+/// 所有 Tauri 命令共用的错误类型
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, thiserror::Error)]
+#[serde(rename_all = "camelCase")]
+#[error("{message}")]
+pub struct AppError {
+    code: ErrorCode,
+    category: ErrorCategory,
+    /// 面向用户的错误信息
+    message: String,
+    /// 面向开发者的详细信息
+    #[serde(skip_serializing_if = "Option::is_none")]
+    details: Option<String>,
+}
+
+impl AppError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            category: code.category(),
+            message: message.into(),
+            details: None,
+        }
+    }
+
+    /// 附加面向开发者的详细信息
+    pub fn with_details(mut self, details: impl Display) -> Self {
+        self.details = Some(details.to_string());
+        self
+    }
+
+    pub fn invalid_argument(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::InvalidArgument, message)
+    }
+
+    pub fn internal(details: impl Display) -> Self {
+        Self::new(ErrorCode::Internal, tr("errors.internal", &[])).with_details(details)
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        self.code
+    }
+
+    pub fn category(&self) -> ErrorCategory {
+        self.category
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn details(&self) -> Option<&str> {
+        self.details.as_deref()
+    }
+}
+
+pub type AppResult<T> = std::result::Result<T, AppError>;
+
+impl From<StartupError> for AppError {
+    fn from(error: StartupError) -> Self {
+        let (code, message) = match &error {
+            StartupError::UnknownTask(task) => (
+                ErrorCode::UnknownTask,
+                tr("errors.unknownTask", &[("task", task.as_str())]),
+            ),
+            StartupError::UnknownDependency { task, dependency } => (
+                ErrorCode::UnknownDependency,
+                tr(
+                    "errors.unknownDependency",
+                    &[("task", task.as_str()), ("dependency", dependency.as_str())],
+                ),
+            ),
+            StartupError::DuplicateTask(task) => (
+                ErrorCode::DuplicateTask,
+                tr("errors.duplicateTask", &[("task", task.as_str())]),
+            ),
+            StartupError::DependencyNotReady { task, dependency } => (
+                ErrorCode::DependencyNotReady,
+                tr(
+                    "errors.dependencyNotReady",
+                    &[("task", task.as_str()), ("dependency", dependency.as_str())],
+                ),
+            ),
+            StartupError::DependencyFailed { task, dependency } => (
+                ErrorCode::DependencyFailed,
+                tr(
+                    "errors.dependencyFailed",
+                    &[("task", task.as_str()), ("dependency", dependency.as_str())],
+                ),
+            ),
+            StartupError::AlreadyFinished(task) => (
+                ErrorCode::TaskAlreadyFinished,
+                tr("errors.taskAlreadyFinished", &[("task", task.as_str())]),
+            ),
+        };
+        AppError::new(code, message)
+    }
+}
+
+#[cfg(desktop)]
+impl From<tauri_plugin_updater::Error> for AppError {
+    fn from(error: tauri_plugin_updater::Error) -> Self {
+        AppError::new(ErrorCode::UpdateFailed, tr("errors.updateFailed", &[])).with_details(error)
+    }
+}
+
+impl From<tauri::Error> for AppError {
+    fn from(error: tauri::Error) -> Self {
+        AppError::internal(error)
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(error: std::io::Error) -> Self {
+        AppError::new(ErrorCode::Io, tr("errors.io", &[])).with_details(error)
+    }
+}
@@ -1 +1,3 @@
+mod error;
 
+pub use error::{AppError, AppResult, ErrorCategory, ErrorCode};
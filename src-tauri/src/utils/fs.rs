@@ -0,0 +1,24 @@
+//! # 文件操作工具
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// 原子地写入文件：先写入同目录下的临时文件并同步到磁盘，再重命名为目标文件，
+/// 写入过程中崩溃或断电不会留下只写了一半的文件
+pub fn write_atomic(path: &Path, content: impl AsRef<[u8]>) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".tmp");
+    let temp = path.with_file_name(file_name);
+    {
+        let mut file = fs::File::create(&temp)?;
+        file.write_all(content.as_ref())?;
+        file.sync_all()?;
+    }
+    fs::rename(&temp, path).inspect_err(|_| {
+        let _ = fs::remove_file(&temp);
+    })
+}
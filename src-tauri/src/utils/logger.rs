@@ -0,0 +1,39 @@
+//! # 日志输出
+//!
+//! 应用通过 `log` 宏输出日志，由 [`StdoutLogger`] 写到标准输出。
+//! 输出的级别由配置项 `log.level` 控制，修改配置文件后通过 [`set_level`] 立即生效。
+//! 依赖库的日志不输出。
+
+use log::{LevelFilter, Log, Metadata, Record};
+
+/// 把本应用的日志写到标准输出
+pub struct StdoutLogger;
+
+impl Log for StdoutLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+            && metadata.target().starts_with(env!("CARGO_CRATE_NAME"))
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            println!("[{}] {}", record.level(), record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: StdoutLogger = StdoutLogger;
+
+/// 安装日志输出，读取配置之前使用 `info` 级别
+pub fn init() {
+    if log::set_logger(&LOGGER).is_ok() {
+        log::set_max_level(LevelFilter::Info);
+    }
+}
+
+/// 修改日志输出的级别
+pub fn set_level(level: LevelFilter) {
+    log::set_max_level(level);
+}
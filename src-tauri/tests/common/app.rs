@@ -0,0 +1,118 @@
+//! 测试共用的模拟应用和临时目录
+//!
+//! 每个模拟应用使用独立的应用标识，并行运行的测试不会共用配置目录和数据目录；
+//! [`TestApp`] 和 [`TestDir`] 释放时删除各自的目录，测试结束后不会在系统中留下文件。
+
+use std::fs;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tauri::test::{mock_builder, mock_context, noop_assets, MockRuntime};
+use tauri::{App, Builder, Config, Manager};
+
+static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// 进程内唯一的名称，例如 `settings.1234.5`
+fn unique(name: &str) -> String {
+    format!(
+        "{}.{}.{}",
+        name,
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::SeqCst)
+    )
+}
+
+/// 独立的应用标识，例如 `com.tauri-bun-vite.settings.1234.5`
+pub fn identifier(name: &str) -> String {
+    format!("com.tauri-bun-vite.{}", unique(name))
+}
+
+/// 使用模拟运行时的应用，释放时删除它的配置、数据和缓存目录
+pub struct TestApp(Option<App<MockRuntime>>);
+
+impl TestApp {
+    /// 关闭应用但保留它的目录，返回应用标识。用相同的标识再次构建应用可以模拟重启，
+    /// 目录由重启后的应用负责删除
+    pub fn shutdown(mut self) -> String {
+        let app = self.0.take().expect("应用已经关闭");
+        app.config().identifier.clone()
+    }
+}
+
+impl Deref for TestApp {
+    type Target = App<MockRuntime>;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.as_ref().expect("应用已经关闭")
+    }
+}
+
+impl Drop for TestApp {
+    fn drop(&mut self) {
+        let Some(app) = self.0.take() else {
+            return;
+        };
+        let path = app.path();
+        let dirs: Vec<PathBuf> = [
+            path.app_config_dir(),
+            path.app_data_dir(),
+            path.app_local_data_dir(),
+            path.app_cache_dir(),
+            path.app_log_dir(),
+        ]
+        .into_iter()
+        .filter_map(Result::ok)
+        .collect();
+        drop(app);
+        for dir in dirs {
+            let _ = fs::remove_dir_all(dir);
+        }
+    }
+}
+
+/// 使用指定的应用标识构建应用，`configure` 可以修改应用的配置，例如插件的配置
+pub fn build_app(
+    identifier: String,
+    builder: Builder<MockRuntime>,
+    configure: impl FnOnce(&mut Config),
+) -> TestApp {
+    let mut context = mock_context(noop_assets());
+    context.config_mut().identifier = identifier;
+    configure(context.config_mut());
+    TestApp(Some(builder.build(context).expect("无法构建测试应用")))
+}
+
+/// 使用独立应用标识的模拟应用，`name` 用于区分不同的测试文件
+pub fn mock_app(name: &str) -> TestApp {
+    mock_app_with(name, mock_builder())
+}
+
+/// 使用独立应用标识的模拟应用，可以在 `builder` 中注册命令、插件和状态
+pub fn mock_app_with(name: &str, builder: Builder<MockRuntime>) -> TestApp {
+    build_app(identifier(name), builder, |_| {})
+}
+
+/// 临时目录，释放时连同其中的文件一起删除
+pub struct TestDir(PathBuf);
+
+impl TestDir {
+    pub fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("tauri-bun-vite-{}", unique(name)));
+        fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    pub fn join(&self, name: impl AsRef<Path>) -> PathBuf {
+        self.0.join(name)
+    }
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
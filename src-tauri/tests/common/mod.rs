@@ -0,0 +1,10 @@
+//! 集成测试共用的工具，各测试文件通过 `mod common;` 引入
+//!
+//! - [`app`]：使用独立目录的模拟应用和临时目录
+//! - [`update_server`]：按照更新插件的格式返回更新清单的模拟服务器
+
+// 每个测试文件只用到其中的一部分
+#![allow(dead_code)]
+
+pub mod app;
+pub mod update_server;
@@ -0,0 +1,204 @@
+//! 模拟更新服务器
+//!
+//! 按照 Tauri 更新插件的动态服务器格式返回更新清单，并提供已签名的更新包。
+//! 清单地址为 `/{scenario}/{target}/{arch}/{current_version}`，不同的 scenario 模拟不同的服务器行为：
+//!
+//! - `latest`：返回 1.0.0 版本和正确签名的更新包
+//! - `none`：返回 204，表示没有可用更新
+//! - `error`：返回 500
+//! - `bad-signature`：更新包内容被篡改，签名校验失败
+//! - `truncated`：更新包只返回一半内容
+//! - `flaky`：第一次下载在传输一半时断开连接，之后支持 Range 请求续传
+//! - `critical`：与 `latest` 相同，但标记为关键更新
+//! - `no-rollout`：与 `latest` 相同，但灰度比例为 0
+//!
+//! 更新包支持 `Range: bytes=start-` 请求，服务器会记录每次下载请求的 Range 头。
+
+use axum::body::{Body, Bytes};
+use axum::extract::{Path, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use base64::Engine;
+use futures_util::StreamExt;
+use serde_json::json;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::runtime::Runtime;
+
+/// 模拟服务器返回的新版本号
+pub const LATEST_VERSION: &str = "1.0.0";
+
+/// 测试用的更新包，由 fixtures/updater/updater.key.pub 对应的私钥签名
+pub const ARTIFACT: &[u8] = include_bytes!("../fixtures/updater/update.tar.gz");
+const SIGNATURE: &str = include_str!("../fixtures/updater/update.tar.gz.sig");
+const PUBKEY: &str = include_str!("../fixtures/updater/updater.key.pub");
+
+/// tauri.conf.json5 中 plugins.updater.pubkey 使用的 base64 编码公钥
+pub fn pubkey() -> String {
+    base64::engine::general_purpose::STANDARD.encode(PUBKEY)
+}
+
+/// 更新清单中使用的 base64 编码签名
+pub fn signature() -> String {
+    base64::engine::general_purpose::STANDARD.encode(SIGNATURE)
+}
+
+struct ServerState {
+    base_url: String,
+    /// flaky 场景是否已经断开过一次连接
+    dropped: AtomicBool,
+    /// 每次下载请求的 Range 头
+    ranges: Mutex<Vec<Option<String>>>,
+}
+
+/// 在独立的 tokio 运行时中运行的模拟更新服务器，随测试结束一起销毁
+pub struct UpdateServer {
+    addr: SocketAddr,
+    state: Arc<ServerState>,
+    _runtime: Runtime,
+}
+
+impl UpdateServer {
+    pub fn start() -> Self {
+        let runtime = Runtime::new().expect("无法创建 tokio 运行时");
+        let listener = runtime
+            .block_on(tokio::net::TcpListener::bind("127.0.0.1:0"))
+            .expect("无法绑定本地端口");
+        let addr = listener.local_addr().unwrap();
+
+        let state = Arc::new(ServerState {
+            base_url: format!("http://{addr}"),
+            dropped: AtomicBool::new(false),
+            ranges: Mutex::new(Vec::new()),
+        });
+        let router = Router::new()
+            .route("/artifacts/{name}", get(artifact))
+            .route(
+                "/{scenario}/{target}/{arch}/{current_version}",
+                get(manifest),
+            )
+            .with_state(state.clone());
+        runtime.spawn(async move { axum::serve(listener, router).await });
+
+        Self {
+            addr,
+            state,
+            _runtime: runtime,
+        }
+    }
+
+    /// 指定场景的更新端点，占位符由更新插件替换
+    pub fn endpoint(&self, scenario: &str) -> String {
+        format!(
+            "http://{}/{scenario}/{{{{target}}}}/{{{{arch}}}}/{{{{current_version}}}}",
+            self.addr
+        )
+    }
+
+    /// 到目前为止所有下载请求的 Range 头
+    pub fn ranges(&self) -> Vec<Option<String>> {
+        self.state.ranges.lock().unwrap().clone()
+    }
+}
+
+async fn manifest(
+    State(state): State<Arc<ServerState>>,
+    Path((scenario, _target, _arch, _current_version)): Path<(String, String, String, String)>,
+) -> Response {
+    let artifact = match scenario.as_str() {
+        "latest" => "update.tar.gz",
+        "bad-signature" => "tampered.tar.gz",
+        "truncated" => "truncated.tar.gz",
+        "flaky" => "flaky.tar.gz",
+        "critical" | "no-rollout" => "update.tar.gz",
+        "none" => return StatusCode::NO_CONTENT.into_response(),
+        _ => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    };
+
+    let mut manifest = json!({
+        "version": LATEST_VERSION,
+        "notes": "修复了若干问题",
+        "pub_date": "2025-10-01T00:00:00Z",
+        "url": format!("{}/artifacts/{artifact}", state.base_url),
+        "signature": signature(),
+        "size": ARTIFACT.len(),
+        "changelog": [
+            { "version": "0.2.0", "notes": "新增订单导出", "pub_date": "2025-09-01T00:00:00Z" },
+            { "version": "0.0.9", "notes": "早于当前版本的说明" },
+        ],
+    });
+    match scenario.as_str() {
+        "critical" => manifest["critical"] = json!(true),
+        "no-rollout" => manifest["rollout"] = json!(0),
+        _ => {}
+    }
+    Json(manifest).into_response()
+}
+
+async fn artifact(
+    State(state): State<Arc<ServerState>>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+) -> Response {
+    let range = headers
+        .get(header::RANGE)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    state.ranges.lock().unwrap().push(range.clone());
+
+    let body = match name.as_str() {
+        "update.tar.gz" | "flaky.tar.gz" => ARTIFACT.to_vec(),
+        "tampered.tar.gz" => {
+            let mut bytes = ARTIFACT.to_vec();
+            bytes[0] ^= 0xff;
+            bytes
+        }
+        "truncated.tar.gz" => ARTIFACT[..ARTIFACT.len() / 2].to_vec(),
+        _ => return StatusCode::NOT_FOUND.into_response(),
+    };
+
+    if name == "flaky.tar.gz" && !state.dropped.swap(true, Ordering::SeqCst) {
+        // 声明完整长度，但只发送一半内容后断开连接
+        let half = Bytes::from(body[..body.len() / 2].to_vec());
+        let chunks: Vec<io::Result<Bytes>> = vec![
+            Ok(half),
+            Err(io::Error::new(io::ErrorKind::ConnectionReset, "连接中断")),
+        ];
+        // 稍等片刻再断开，确保客户端已经收到前一半内容
+        let stream = futures_util::stream::iter(chunks).then(|chunk| async move {
+            if chunk.is_err() {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+            chunk
+        });
+        return (
+            [(header::CONTENT_LENGTH, body.len().to_string())],
+            Body::from_stream(stream),
+        )
+            .into_response();
+    }
+
+    let start = range
+        .as_deref()
+        .and_then(|range| range.strip_prefix("bytes="))
+        .and_then(|range| range.strip_suffix('-'))
+        .and_then(|start| start.parse::<usize>().ok());
+    match start {
+        Some(start) if start >= body.len() => StatusCode::RANGE_NOT_SATISFIABLE.into_response(),
+        Some(start) => (
+            StatusCode::PARTIAL_CONTENT,
+            [(
+                header::CONTENT_RANGE,
+                format!("bytes {}-{}/{}", start, body.len() - 1, body.len()),
+            )],
+            Bytes::from(body[start..].to_vec()),
+        )
+            .into_response(),
+        None => Bytes::from(body).into_response(),
+    }
+}
@@ -0,0 +1,371 @@
+mod common;
+
+use common::app::{TestApp, TestDir};
+use log::LevelFilter;
+use serde_json::{json, Value};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tauri::test::MockRuntime;
+use tauri::{App, Listener, Manager};
+use tauri_bun_vite_lib::core::config::watcher::{
+    self, FileSnapshots, WatchedFile, CONFIG_REJECTED_EVENT,
+};
+use tauri_bun_vite_lib::core::config::{
+    self, changed_keys, env_name, flag_name, resolve, resolve_strict, ConfigLayer, ConfigSource,
+    ConfigState, LogLevel, CONFIG_CHANGED_EVENT, KEYS,
+};
+use tauri_bun_vite_lib::models::ErrorCode;
+
+fn mock_app() -> TestApp {
+    common::app::mock_app("config")
+}
+
+/// 记录应用发出的某个事件的内容
+fn record(app: &App<MockRuntime>, event: &str) -> Arc<Mutex<Vec<Value>>> {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let recorded = events.clone();
+    app.listen_any(event, move |event| {
+        recorded
+            .lock()
+            .unwrap()
+            .push(serde_json::from_str(event.payload()).unwrap());
+    });
+    events
+}
+
+fn write_user_config(app: &App<MockRuntime>, content: &str) -> PathBuf {
+    let path = config::user_config_path(app.handle()).unwrap();
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, content).unwrap();
+    path
+}
+
+fn args(args: &[&str]) -> Vec<String> {
+    std::iter::once("tauri-bun-vite")
+        .chain(args.iter().copied())
+        .map(str::to_string)
+        .collect()
+}
+
+fn env(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+    vars.iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect()
+}
+
+#[test]
+fn derives_env_and_flag_names() {
+    assert_eq!(env_name("api.baseUrl"), "TAURI_BUN_VITE_API_BASE_URL");
+    assert_eq!(env_name("log.level"), "TAURI_BUN_VITE_LOG_LEVEL");
+    assert_eq!(flag_name("api.baseUrl"), "api-base-url");
+    assert_eq!(flag_name("updater.channel"), "updater-channel");
+}
+
+#[test]
+fn defaults_without_layers() {
+    let effective = resolve(&[]);
+    assert_eq!(effective.config.api.base_url, None);
+    assert_eq!(effective.config.log.level, LogLevel::Info);
+    assert_eq!(effective.entries.len(), KEYS.len());
+    assert!(effective
+        .entries
+        .iter()
+        .all(|entry| entry.source == ConfigSource::Default && entry.origin.is_none()));
+    assert!(effective.warnings.is_empty());
+}
+
+#[test]
+fn later_layers_win() {
+    let system = ConfigLayer::from_json(
+        ConfigSource::System,
+        "/etc/tauri-bun-vite/config.json",
+        &json!({
+            "api": { "baseUrl": "https://system.example.com" },
+            "log": { "level": "warn" },
+            "updater": { "channel": "beta" },
+            "tray": { "title": "Warehouse 3" }
+        }),
+    );
+    let user = ConfigLayer::from_json(
+        ConfigSource::User,
+        "config.json",
+        &json!({
+            "api": { "baseUrl": "https://user.example.com" },
+            "log": { "level": "debug" }
+        }),
+    );
+    let env = ConfigLayer::from_env(env(&[
+        ("TAURI_BUN_VITE_API_BASE_URL", "https://env.example.com"),
+        ("PATH", "/usr/bin"),
+    ]));
+    let cli = ConfigLayer::from_args(&args(&["--api-base-url=https://cli.example.com"]));
+
+    // 层的顺序由来源决定，与传入的顺序无关
+    let effective = resolve(&[cli, user, env, system]);
+    assert_eq!(
+        effective.config.api.base_url.as_deref(),
+        Some("https://cli.example.com")
+    );
+    assert_eq!(effective.config.log.level, LogLevel::Debug);
+    assert_eq!(effective.config.updater.channel.as_deref(), Some("beta"));
+    assert_eq!(effective.config.tray.title.as_deref(), Some("Warehouse 3"));
+
+    assert_eq!(effective.source_of("api.baseUrl"), Some(ConfigSource::Cli));
+    assert_eq!(effective.source_of("log.level"), Some(ConfigSource::User));
+    assert_eq!(
+        effective.source_of("updater.channel"),
+        Some(ConfigSource::System)
+    );
+    assert_eq!(
+        effective.source_of("updater.endpoint"),
+        Some(ConfigSource::Default)
+    );
+    let entry = &effective.entries[0];
+    assert_eq!(entry.key, "api.baseUrl");
+    assert_eq!(
+        entry.origin.as_deref(),
+        Some("--api-base-url=https://cli.example.com")
+    );
+}
+
+#[test]
+fn skips_invalid_values_with_warnings() {
+    let user = ConfigLayer::from_json(
+        ConfigSource::User,
+        "config.json",
+        &json!({
+            "api": { "baseUrl": "ftp://example.com" },
+            "log": { "level": "verbose" },
+            "updater": { "channel": "alpha", "endpoint": "https://updates.example.com/{{target}}/{{arch}}" },
+            "theme": "dark"
+        }),
+    );
+    assert_eq!(user.warnings.len(), 4);
+    let env = ConfigLayer::from_env(env(&[("TAURI_BUN_VITE_LOG_LEVEL", " ")]));
+    assert_eq!(env.warnings.len(), 1);
+
+    let effective = resolve(&[user, env]);
+    assert_eq!(effective.config.api.base_url, None);
+    assert_eq!(effective.config.log.level, LogLevel::Info);
+    assert_eq!(effective.config.updater.channel, None);
+    // 同一层中合法的值仍然生效
+    assert_eq!(
+        effective.config.updater.endpoint.as_deref(),
+        Some("https://updates.example.com/{{target}}/{{arch}}")
+    );
+    assert_eq!(effective.warnings.len(), 5);
+}
+
+#[test]
+fn accepts_relative_api_path() {
+    let layer = ConfigLayer::from_json(
+        ConfigSource::User,
+        "config.json",
+        &json!({ "api": { "baseUrl": "/api" } }),
+    );
+    assert!(layer.warnings.is_empty());
+    assert_eq!(
+        resolve(&[layer]).config.api.base_url.as_deref(),
+        Some("/api")
+    );
+}
+
+#[test]
+fn only_reads_key_value_flags() {
+    let layer = ConfigLayer::from_args(&args(&[
+        "--log-level",
+        "debug",
+        "--log-level=trace",
+        "--unknown=1",
+        "orders.json",
+    ]));
+    assert!(layer.warnings.is_empty());
+    assert_eq!(
+        layer.values,
+        vec![(
+            "log.level".to_string(),
+            Value::String("trace".to_string()),
+            "--log-level=trace".to_string()
+        )]
+    );
+}
+
+#[test]
+fn reports_unreadable_files() {
+    let dir = TestDir::new("config");
+    let path = dir.join("config.json");
+    assert!(
+        ConfigLayer::from_file(ConfigSource::User, &path.with_file_name("missing.json")).is_none()
+    );
+
+    fs::write(&path, "{ \"api\": ").unwrap();
+    let layer = ConfigLayer::from_file(ConfigSource::User, &path).unwrap();
+    assert!(layer.values.is_empty());
+    assert_eq!(layer.warnings.len(), 1);
+
+    fs::write(&path, r#"{ "log": { "level": "error" } }"#).unwrap();
+    let layer = ConfigLayer::from_file(ConfigSource::System, &path).unwrap();
+    assert_eq!(resolve(&[layer]).config.log.level, LogLevel::Error);
+}
+
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+#[test]
+fn overrides_updater_settings() {
+    use tauri_bun_vite_lib::core::config::UpdaterConfig;
+    use tauri_bun_vite_lib::core::updater::{UpdateChannel, UpdaterSettings};
+
+    let config = UpdaterConfig {
+        endpoint: Some("https://updates.example.com/{{target}}".to_string()),
+        channel: Some("nightly".to_string()),
+    };
+    let settings = UpdaterSettings::default().with_config(&config);
+    assert_eq!(settings.channel, UpdateChannel::Nightly);
+    assert_eq!(
+        settings.endpoints,
+        vec!["https://updates.example.com/{{target}}".to_string()]
+    );
+
+    // 没有配置时保留用户的更新设置
+    let user = UpdaterSettings {
+        channel: UpdateChannel::Beta,
+        ..UpdaterSettings::default()
+    };
+    assert_eq!(user.clone().with_config(&UpdaterConfig::default()), user);
+}
+
+#[test]
+fn lists_changed_keys() {
+    let user = |level: &str| {
+        ConfigLayer::from_json(
+            ConfigSource::User,
+            "config.json",
+            &json!({ "log": { "level": level }, "tray": { "title": "POS" } }),
+        )
+    };
+    let old = resolve(&[user("info")]);
+    // 取值相同但来源不同的配置项没有变化
+    assert!(changed_keys(&old, &resolve(&[user("info")])).is_empty());
+    assert_eq!(
+        changed_keys(&old, &resolve(&[user("debug")])),
+        vec!["log.level"]
+    );
+    assert_eq!(changed_keys(&old, &resolve(&[])), vec!["tray.title"]);
+}
+
+#[test]
+fn strict_resolve_rejects_only_changed_files() {
+    let user = ConfigLayer::from_json(
+        ConfigSource::User,
+        "config.json",
+        &json!({ "updater": { "channel": "alpha" }, "tray": { "title": "POS" } }),
+    );
+    let error = resolve_strict(&[user.clone()], &[ConfigSource::User]).unwrap_err();
+    assert_eq!(error.code(), ErrorCode::InvalidConfig);
+
+    // 只修改了系统配置文件时，用户配置文件中原有的问题不影响重新加载
+    let system = ConfigLayer::from_json(
+        ConfigSource::System,
+        "/etc/tauri-bun-vite/config.json",
+        &json!({ "tray": { "title": "Warehouse 3" } }),
+    );
+    let effective = resolve_strict(&[system, user], &[ConfigSource::System]).unwrap();
+    assert_eq!(effective.config.tray.title.as_deref(), Some("POS"));
+    assert_eq!(effective.config.updater.channel, None);
+
+    // 环境变量和命令行参数在启动后不会变化，其中的警告不影响重新加载
+    let env = ConfigLayer::from_env(env(&[("TAURI_BUN_VITE_UPDATER_CHANNEL", "alpha")]));
+    assert!(resolve_strict(&[env], &[ConfigSource::System, ConfigSource::User]).is_ok());
+}
+
+#[test]
+fn reloads_edited_files_and_keeps_last_good_config() {
+    let app = mock_app();
+    config::init(app.handle());
+    let changes = record(&app, CONFIG_CHANGED_EVENT);
+    let rejections = record(&app, CONFIG_REJECTED_EVENT);
+
+    let mut snapshots = FileSnapshots::default();
+    let path = config::user_config_path(app.handle()).unwrap();
+    snapshots.watch(path.clone(), WatchedFile::Config(ConfigSource::User));
+    watcher::check(app.handle(), &mut snapshots);
+    assert!(changes.lock().unwrap().is_empty());
+
+    write_user_config(
+        &app,
+        r#"{ "log": { "level": "debug" }, "tray": { "title": "POS 1" } }"#,
+    );
+    watcher::check(app.handle(), &mut snapshots);
+    assert_eq!(
+        config::current(app.handle()).tray.title.as_deref(),
+        Some("POS 1")
+    );
+    // 日志级别立即生效
+    assert_eq!(log::max_level(), LevelFilter::Debug);
+    assert_eq!(
+        changes.lock().unwrap().as_slice(),
+        &[json!({
+            "changed": ["log.level", "tray.title"],
+            "config": {
+                "api": { "baseUrl": null },
+                "updater": { "endpoint": null, "channel": null },
+                "log": { "level": "debug" },
+                "tray": { "title": "POS 1" }
+            }
+        })]
+    );
+
+    // 无效的修改被拒绝，继续使用之前的配置
+    write_user_config(
+        &app,
+        r#"{ "log": { "level": "verbose" }, "tray": { "title": "POS 2" } }"#,
+    );
+    watcher::check(app.handle(), &mut snapshots);
+    assert_eq!(
+        config::current(app.handle()).tray.title.as_deref(),
+        Some("POS 1")
+    );
+    assert_eq!(changes.lock().unwrap().len(), 1);
+    {
+        let rejections = rejections.lock().unwrap();
+        assert_eq!(rejections.len(), 1);
+        assert_eq!(rejections[0]["path"], json!(path.display().to_string()));
+        assert_eq!(rejections[0]["error"]["code"], json!("INVALID_CONFIG"));
+    }
+
+    // 内容没有变化时不会再次提示
+    watcher::check(app.handle(), &mut snapshots);
+    assert_eq!(rejections.lock().unwrap().len(), 1);
+
+    // 删除配置文件后恢复默认配置
+    fs::remove_file(&path).unwrap();
+    watcher::check(app.handle(), &mut snapshots);
+    assert_eq!(
+        app.state::<ConfigState>().get().config,
+        config::AppConfig::default()
+    );
+    assert_eq!(log::max_level(), LevelFilter::Info);
+}
+
+#[test]
+fn detects_created_and_deleted_files() {
+    let dir = TestDir::new("watch");
+    let path = dir.join("settings.json");
+
+    let mut snapshots = FileSnapshots::default();
+    snapshots.watch(path.clone(), WatchedFile::Settings);
+    assert!(snapshots.poll().is_empty());
+
+    fs::write(&path, "{}").unwrap();
+    assert_eq!(
+        snapshots.poll(),
+        vec![(path.clone(), WatchedFile::Settings)]
+    );
+    assert!(snapshots.poll().is_empty());
+
+    fs::write(&path, "{ }").unwrap();
+    assert_eq!(snapshots.poll().len(), 1);
+
+    fs::remove_file(&path).unwrap();
+    assert_eq!(snapshots.poll(), vec![(path, WatchedFile::Settings)]);
+}
@@ -0,0 +1,90 @@
+#![cfg(not(any(target_os = "android", target_os = "ios")))]
+
+use tauri_bun_vite_lib::core::deeplink::{is_deep_link, DeepLink, DeepLinkQueue};
+use tauri_bun_vite_lib::models::{ErrorCategory, ErrorCode};
+
+#[test]
+fn parses_supported_links() {
+    let cases = [
+        ("tauri-bun-vite://", DeepLink::Home, "/"),
+        (
+            "tauri-bun-vite://dashboard",
+            DeepLink::Dashboard { workbench: false },
+            "/dashboard",
+        ),
+        (
+            "tauri-bun-vite://dashboard/workbench/",
+            DeepLink::Dashboard { workbench: true },
+            "/dashboard/workbench",
+        ),
+        (
+            "tauri-bun-vite://orders/123",
+            DeepLink::Order { id: 123 },
+            "/orders/123",
+        ),
+        (
+            "tauri-bun-vite:orders/123?from=mail",
+            DeepLink::Order { id: 123 },
+            "/orders/123",
+        ),
+        ("tauri-bun-vite://settings", DeepLink::Settings, "/settings"),
+        ("  tauri-bun-vite://about  ", DeepLink::About, "/about"),
+    ];
+    for (link, expected, route) in cases {
+        let parsed = DeepLink::parse(link).unwrap();
+        assert_eq!(parsed, expected, "{}", link);
+        assert_eq!(parsed.route(), route, "{}", link);
+    }
+}
+
+#[test]
+fn rejects_unknown_and_malformed_links() {
+    for link in [
+        "not a url",
+        "https://example.com/orders/123",
+        "tauri-bun-vite://unknown",
+        "tauri-bun-vite://orders",
+        "tauri-bun-vite://orders/abc",
+        "tauri-bun-vite://orders/0",
+        "tauri-bun-vite://orders/-1",
+        "tauri-bun-vite://orders/123/edit",
+        "tauri-bun-vite://orders/../settings/1",
+    ] {
+        let error = DeepLink::parse(link).unwrap_err();
+        assert_eq!(error.code(), ErrorCode::InvalidDeepLink, "{}", link);
+        assert_eq!(error.category(), ErrorCategory::Validation);
+    }
+}
+
+#[test]
+fn detects_app_scheme() {
+    assert!(is_deep_link("tauri-bun-vite://orders/1"));
+    assert!(is_deep_link("TAURI-BUN-VITE://orders/1"));
+    assert!(!is_deep_link("tauri-bun-vite-dev://orders/1"));
+    assert!(!is_deep_link("https://example.com"));
+    assert!(!is_deep_link("订单"));
+}
+
+#[test]
+fn queues_links_until_frontend_is_ready() {
+    let mut queue = DeepLinkQueue::default();
+    assert_eq!(queue.push(DeepLink::Order { id: 1 }), None);
+    assert_eq!(queue.push(DeepLink::Home), None);
+    assert_eq!(
+        queue.take(),
+        vec![DeepLink::Order { id: 1 }, DeepLink::Home]
+    );
+    assert_eq!(
+        queue.push(DeepLink::Dashboard { workbench: true }),
+        Some(DeepLink::Dashboard { workbench: true })
+    );
+    assert!(queue.take().is_empty());
+}
+
+#[test]
+fn opens_settings_and_about_in_their_own_windows() {
+    assert_eq!(DeepLink::Settings.window_kind(), Some("settings"));
+    assert_eq!(DeepLink::About.window_kind(), Some("about"));
+    assert_eq!(DeepLink::Home.window_kind(), None);
+    assert_eq!(DeepLink::Order { id: 1 }.window_kind(), None);
+}
@@ -0,0 +1,178 @@
+#![cfg(not(any(target_os = "android", target_os = "ios")))]
+
+mod common;
+
+use common::app::mock_app_with;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tauri::ipc::{CallbackFn, InvokeBody};
+use tauri::test::{get_ipc_response, mock_builder, INVOKE_KEY};
+use tauri::webview::InvokeRequest;
+use tauri::{Listener, WebviewWindowBuilder};
+use tauri_bun_vite_lib::core::instance::{
+    self, take_launch_args, LaunchArgs, LaunchQueue, SECOND_INSTANCE_EVENT,
+};
+
+fn argv(args: &[&str]) -> Vec<String> {
+    std::iter::once("tauri-bun-vite")
+        .chain(args.iter().copied())
+        .map(str::to_string)
+        .collect()
+}
+
+fn cwd() -> String {
+    std::env::temp_dir().to_string_lossy().into_owned()
+}
+
+#[test]
+fn resolves_relative_files_against_cwd() {
+    let cwd = cwd();
+    let absolute = std::env::temp_dir().join("b.json");
+    let absolute = absolute.to_string_lossy();
+    let launch = LaunchArgs::parse(&argv(&["a.json", &absolute]), &cwd);
+    assert_eq!(launch.cwd, cwd);
+    assert_eq!(
+        launch.args,
+        vec!["a.json".to_string(), absolute.to_string()]
+    );
+    assert_eq!(
+        launch.files,
+        vec![
+            Path::new(&cwd)
+                .join("a.json")
+                .to_string_lossy()
+                .into_owned(),
+            absolute.to_string(),
+        ]
+    );
+    assert!(launch.urls.is_empty() && launch.flags.is_empty());
+}
+
+#[test]
+fn separates_urls_and_flags() {
+    let launch = LaunchArgs::parse(
+        &argv(&[
+            "--minimized",
+            "-v",
+            "tauri-bun-vite://orders/123",
+            "https://example.com/a?b=1",
+            "",
+        ]),
+        &cwd(),
+    );
+    assert_eq!(launch.flags, vec!["--minimized", "-v"]);
+    assert_eq!(
+        launch.urls,
+        vec!["tauri-bun-vite://orders/123", "https://example.com/a?b=1"]
+    );
+    assert!(launch.files.is_empty());
+}
+
+#[test]
+fn converts_file_urls_to_paths() {
+    let path = std::env::temp_dir().join("order.json");
+    let url = url::Url::from_file_path(&path).unwrap();
+    let launch = LaunchArgs::parse(&argv(&[url.as_str()]), &cwd());
+    assert_eq!(launch.files, vec![path.to_string_lossy().into_owned()]);
+    assert!(launch.urls.is_empty());
+}
+
+#[test]
+fn plain_relaunch_has_no_args() {
+    assert!(LaunchArgs::parse(&argv(&[]), &cwd()).is_empty());
+}
+
+#[test]
+fn queues_launches_until_frontend_is_ready() {
+    let mut queue = LaunchQueue::default();
+    let first = LaunchArgs::parse(&argv(&["a.json"]), &cwd());
+    let second = LaunchArgs::parse(&argv(&["b.json"]), &cwd());
+    assert_eq!(queue.push(first.clone()), None);
+    assert_eq!(queue.push(second.clone()), None);
+    assert_eq!(queue.take(), vec![first.clone(), second]);
+
+    // 前端就绪后直接发送，不再暂存
+    assert_eq!(queue.push(first.clone()), Some(first));
+    assert!(queue.take().is_empty());
+}
+
+#[test]
+fn takes_deep_links_out_of_urls() {
+    let mut launch = LaunchArgs::parse(
+        &argv(&["tauri-bun-vite://orders/123", "https://example.com/"]),
+        &cwd(),
+    );
+    assert_eq!(
+        launch.take_deep_links(),
+        vec!["tauri-bun-vite://orders/123"]
+    );
+    assert_eq!(launch.urls, vec!["https://example.com/"]);
+    assert!(!launch.is_empty());
+
+    // 只有深度链接时不需要转发给前端
+    let mut launch = LaunchArgs::parse(&argv(&["tauri-bun-vite://settings"]), &cwd());
+    launch.take_deep_links();
+    assert!(launch.is_empty());
+}
+
+#[test]
+fn forwards_second_instance_args_to_frontend() {
+    let app = mock_app_with(
+        "instance",
+        mock_builder().invoke_handler(tauri::generate_handler![take_launch_args]),
+    );
+    instance::init(app.handle());
+    let main = WebviewWindowBuilder::new(app.handle(), "main", Default::default())
+        .build()
+        .unwrap();
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let sink = received.clone();
+    app.listen(SECOND_INSTANCE_EVENT, move |event| {
+        let launch: LaunchArgs = serde_json::from_str(event.payload()).unwrap();
+        sink.lock().unwrap().push(launch);
+    });
+    // 与前端 setupLaunchArgs 调用 take_launch_args 的方式相同
+    let take = || -> Vec<LaunchArgs> {
+        get_ipc_response(
+            &main,
+            InvokeRequest {
+                cmd: "take_launch_args".into(),
+                callback: CallbackFn(0),
+                error: CallbackFn(1),
+                url: "http://tauri.localhost".parse().unwrap(),
+                body: InvokeBody::Json(serde_json::json!({})),
+                headers: Default::default(),
+                invoke_key: INVOKE_KEY.to_string(),
+            },
+        )
+        .expect("命令调用失败")
+        .deserialize()
+        .unwrap()
+    };
+
+    // 前端就绪之前再次启动，参数先暂存，本次启动的参数（测试进程的参数）排在前面
+    instance::handle_second_instance(app.handle(), argv(&["a.toml"]), cwd());
+    let pending = take();
+    let first = pending.last().expect("暂存的参数丢失");
+    assert_eq!(
+        first.files,
+        vec![Path::new(&cwd())
+            .join("a.toml")
+            .to_string_lossy()
+            .into_owned()]
+    );
+    assert!(received.lock().unwrap().is_empty());
+
+    // 前端就绪之后直接通过事件转发
+    instance::handle_second_instance(
+        app.handle(),
+        argv(&["--minimized", "https://example.com/"]),
+        cwd(),
+    );
+    let received = received.lock().unwrap();
+    assert_eq!(received.len(), 1);
+    assert_eq!(received[0].flags, vec!["--minimized"]);
+    assert_eq!(received[0].urls, vec!["https://example.com/"]);
+    assert_eq!(received[0].cwd, cwd());
+    assert!(take().is_empty());
+}
@@ -0,0 +1,88 @@
+use serde_json::json;
+use tauri_bun_vite_lib::core::locale::{self, catalog, Locale};
+
+#[test]
+fn parses_language_tags() {
+    assert_eq!(Locale::from_tag("en-US"), Some(Locale::EnUs));
+    assert_eq!(Locale::from_tag("zh"), Some(Locale::ZhCn));
+    assert_eq!(Locale::from_tag("zh_CN.UTF-8"), Some(Locale::ZhCn));
+    assert_eq!(Locale::from_tag("ja-JP"), None);
+    assert_eq!(Locale::from_tag(""), None);
+    for locale in Locale::ALL {
+        assert_eq!(Locale::from_tag(locale.tag()), Some(locale));
+    }
+}
+
+#[test]
+fn serializes_as_language_tag() {
+    assert_eq!(serde_json::to_value(Locale::ZhCn).unwrap(), json!("zh-CN"));
+    assert_eq!(
+        serde_json::from_value::<Locale>(json!("en-US")).unwrap(),
+        Locale::EnUs
+    );
+}
+
+#[test]
+fn looks_up_embedded_catalog() {
+    assert_eq!(locale::text(Locale::EnUs, "tray.quit"), Some("Quit Coco"));
+    assert_eq!(locale::text(Locale::ZhCn, "tray.quit"), Some("退出 Coco"));
+    assert_eq!(locale::text(Locale::ZhCn, "tray.missing"), None);
+    // 非字符串节点不是文字
+    assert_eq!(locale::text(Locale::EnUs, "tray"), None);
+}
+
+#[test]
+fn catalogs_are_valid_and_in_sync() {
+    assert!(catalog::invalid_files().is_empty());
+    // 各语言的语言包必须包含相同的键，避免前后端或不同语言之间的文案缺失
+    assert_eq!(catalog::keys(Locale::EnUs), catalog::keys(Locale::ZhCn));
+    assert!(catalog::keys(Locale::EnUs).contains(&"errors.internal".to_string()));
+}
+
+#[test]
+fn translates_with_interpolation_and_fallback() {
+    assert_eq!(
+        locale::translate(Locale::EnUs, "common.greet", &[("name", "Coco")]),
+        "Hello, Coco! You've been greeted from Rust!"
+    );
+    assert_eq!(
+        locale::translate(Locale::ZhCn, "errors.duplicateMenuId", &[("id", "quit")]),
+        "菜单项 id quit 重复"
+    );
+    // 缺少的键返回键名本身
+    assert_eq!(
+        locale::translate(Locale::ZhCn, "missing.key", &[]),
+        "missing.key"
+    );
+}
+
+#[test]
+fn interpolates_like_vue_i18n() {
+    assert_eq!(
+        catalog::interpolate("{a} {'{'}b{'}'} {missing}", &[("a", "1")]),
+        "1 {b} {missing}"
+    );
+    assert_eq!(
+        catalog::interpolate("unclosed {a", &[("a", "1")]),
+        "unclosed {a"
+    );
+}
+
+#[test]
+fn selects_plural_forms() {
+    assert_eq!(catalog::select_plural("apple | apples", 1), "apple");
+    assert_eq!(catalog::select_plural("apple | apples", 0), "apples");
+    assert_eq!(
+        catalog::select_plural("none | one | {count} many", 0),
+        "none"
+    );
+    assert_eq!(
+        catalog::select_plural("none | one | {count} many", 1),
+        "one"
+    );
+    assert_eq!(
+        catalog::select_plural("none | one | {count} many", 5),
+        "{count} many"
+    );
+    assert_eq!(catalog::select_plural("single", 3), "single");
+}
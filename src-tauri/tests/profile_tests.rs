@@ -0,0 +1,281 @@
+#![cfg(not(any(target_os = "android", target_os = "ios")))]
+
+mod common;
+
+use common::app::{TestApp, TestDir};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::fs;
+use tauri::Manager;
+use tauri_bun_vite_lib::core::locale::Locale;
+use tauri_bun_vite_lib::core::profile::{
+    self, diff, FieldChange, LanguageProfile, Profile, ProfileData, ProfileSection, UpdatesProfile,
+    FORMAT_VERSION,
+};
+use tauri_bun_vite_lib::core::settings::{
+    self, AppearanceSettings, Layout, SettingsState, ThemeColor,
+};
+use tauri_bun_vite_lib::core::tray::{TrayMenuDefinition, TrayMenuState};
+use tauri_bun_vite_lib::core::updater::{UpdateChannel, UpdaterSettings, UpdaterState};
+use tauri_bun_vite_lib::core::window::{GeometryState, GeometryStore, WindowGeometry};
+use tauri_bun_vite_lib::models::ErrorCode;
+
+fn mock_app() -> TestApp {
+    let app = common::app::mock_app("profile");
+    settings::init(app.handle());
+    app.manage(UpdaterState::new(UpdaterSettings::default()));
+    app.manage(GeometryState::new(GeometryStore::default()));
+    app.manage(TrayMenuState::new(TrayMenuDefinition::default()));
+    profile::init(app.handle());
+    app
+}
+
+fn geometry(x: i32, width: u32) -> WindowGeometry {
+    WindowGeometry {
+        x,
+        y: 40,
+        width,
+        height: 600,
+        maximized: false,
+        fullscreen: false,
+        monitor: None,
+    }
+}
+
+fn sample() -> ProfileData {
+    ProfileData {
+        appearance: Some(AppearanceSettings {
+            layout: Layout::Sidebar,
+            theme_color: ThemeColor::Teal,
+            dark_mode: true,
+        }),
+        language: Some(LanguageProfile {
+            locale: Locale::ZhCn,
+        }),
+        updates: Some(UpdatesProfile {
+            channel: UpdateChannel::Beta,
+        }),
+        windows: Some(BTreeMap::from([("main".to_string(), geometry(100, 1024))])),
+        shortcuts: Some(BTreeMap::from([(
+            "open".to_string(),
+            "CmdOrCtrl+O".to_string(),
+        )])),
+    }
+}
+
+#[test]
+fn round_trips_through_toml() {
+    let profile = Profile::new(sample(), "0.1.0", 1_760_688_000);
+    let content = profile.to_toml().unwrap();
+    assert!(content.starts_with(&format!("format = {}", FORMAT_VERSION)));
+    assert!(content.contains("[appearance]"));
+    assert!(content.contains("[windows.main]"));
+    assert!(content.contains(&format!("checksum = \"{}\"", profile.checksum)));
+    assert!(profile.checksum.starts_with("sha256:"));
+
+    assert_eq!(Profile::parse(&content).unwrap(), profile);
+    assert_eq!(profile.data.sections(), ProfileSection::ALL.to_vec());
+}
+
+#[test]
+fn rejects_modified_or_unsupported_files() {
+    let content = Profile::new(sample(), "0.1.0", 0).to_toml().unwrap();
+
+    let tampered = content.replace("themeColor = \"teal\"", "themeColor = \"red\"");
+    assert_ne!(tampered, content);
+    let error = Profile::parse(&tampered).unwrap_err();
+    assert_eq!(error.code(), ErrorCode::InvalidProfile);
+
+    let newer = content.replace(
+        &format!("format = {}", FORMAT_VERSION),
+        &format!("format = {}", FORMAT_VERSION + 1),
+    );
+    assert_eq!(
+        Profile::parse(&newer).unwrap_err().code(),
+        ErrorCode::InvalidProfile
+    );
+
+    for invalid in ["format = ", "format = 1", "[appearance]\nlayout = \"grid\""] {
+        assert_eq!(
+            Profile::parse(invalid).unwrap_err().code(),
+            ErrorCode::InvalidProfile,
+            "{}",
+            invalid
+        );
+    }
+}
+
+#[test]
+fn keeps_only_selected_sections() {
+    let data = sample().only(&[ProfileSection::Language, ProfileSection::Shortcuts]);
+    assert_eq!(
+        data.sections(),
+        vec![ProfileSection::Language, ProfileSection::Shortcuts]
+    );
+    assert!(data.appearance.is_none() && data.windows.is_none());
+    // 校验和只覆盖导出的分组
+    assert_ne!(data.checksum(), sample().checksum());
+}
+
+#[test]
+fn previews_field_changes() {
+    let current = ProfileData {
+        appearance: Some(AppearanceSettings::default()),
+        windows: Some(BTreeMap::from([
+            ("main".to_string(), geometry(100, 800)),
+            ("settings".to_string(), geometry(0, 400)),
+        ])),
+        ..ProfileData::default()
+    };
+    let imported = sample().only(&[ProfileSection::Appearance, ProfileSection::Windows]);
+    let preview = diff(&current, &imported);
+    assert_eq!(preview.len(), 2);
+
+    assert_eq!(preview[0].section, ProfileSection::Appearance);
+    let fields: Vec<&str> = preview[0]
+        .changes
+        .iter()
+        .map(|change| change.field.as_str())
+        .collect();
+    assert_eq!(fields, vec!["darkMode", "layout", "themeColor"]);
+
+    assert_eq!(preview[1].section, ProfileSection::Windows);
+    assert!(preview[1].changes.contains(&FieldChange {
+        field: "main.width".to_string(),
+        current: json!(800),
+        imported: json!(1024),
+    }));
+    // 导入后整个分组被替换，档案中没有的窗口记录会被删除
+    assert!(preview[1].changes.contains(&FieldChange {
+        field: "settings.width".to_string(),
+        current: json!(400),
+        imported: json!(null),
+    }));
+    assert!(!preview[1]
+        .changes
+        .iter()
+        .any(|change| change.field == "main.x"));
+
+    // 与当前设置相同的分组没有变化
+    assert!(diff(&imported, &imported)
+        .iter()
+        .all(|section| section.changes.is_empty()));
+}
+
+#[test]
+fn exports_and_imports_selected_sections() {
+    let source = mock_app();
+    settings::update(
+        source.handle(),
+        &json!({ "appearance": { "layout": "horizontal", "themeColor": "pink" } }),
+    )
+    .unwrap();
+    source.state::<UpdaterState>().0.lock().unwrap().channel = UpdateChannel::Nightly;
+    source
+        .state::<GeometryState>()
+        .0
+        .lock()
+        .unwrap()
+        .0
+        .insert("main".to_string(), geometry(10, 900));
+    source
+        .state::<TrayMenuState>()
+        .0
+        .lock()
+        .unwrap()
+        .set_accelerator("quit", Some("CmdOrCtrl+Q".to_string()));
+
+    let dir = TestDir::new("profile");
+    let path = dir.join("workstation.toml");
+    let sections = [
+        ProfileSection::Appearance,
+        ProfileSection::Updates,
+        ProfileSection::Windows,
+        ProfileSection::Shortcuts,
+    ];
+    let exported = profile::export_to(source.handle(), &path, &sections).unwrap();
+    assert_eq!(Profile::read(&path).unwrap(), exported);
+    assert_eq!(exported.data.sections(), sections.to_vec());
+
+    let target = mock_app();
+    let preview = profile::preview(target.handle(), &path).unwrap();
+    assert_eq!(preview.path, path.display().to_string());
+    assert_eq!(preview.sections.len(), 4);
+    assert!(preview
+        .sections
+        .iter()
+        .all(|section| !section.changes.is_empty()));
+
+    // 预览之后文件被修改，导入的仍然是预览时读取的档案
+    fs::write(&path, "format = 1").unwrap();
+
+    // 只导入选中的分组
+    let applied = profile::import(
+        target.handle(),
+        preview.id,
+        &[ProfileSection::Appearance, ProfileSection::Shortcuts],
+    )
+    .unwrap();
+    assert_eq!(
+        applied,
+        vec![ProfileSection::Appearance, ProfileSection::Shortcuts]
+    );
+    let appearance = target.state::<SettingsState>().get().appearance;
+    assert_eq!(appearance.layout, Layout::Horizontal);
+    assert_eq!(appearance.theme_color, ThemeColor::Pink);
+    assert_eq!(
+        target.state::<TrayMenuState>().get().accelerators(),
+        BTreeMap::from([("quit".to_string(), "CmdOrCtrl+Q".to_string())])
+    );
+    assert_eq!(
+        target.state::<UpdaterState>().get().channel,
+        UpdateChannel::default()
+    );
+    assert!(target.state::<GeometryState>().get("main").is_none());
+
+    // 导入的设置已经保存，重新加载后仍然有效
+    assert_eq!(
+        settings::Settings::load(target.handle()).appearance,
+        appearance
+    );
+    assert_eq!(
+        TrayMenuDefinition::load(target.handle()).accelerators(),
+        target.state::<TrayMenuState>().get().accelerators()
+    );
+
+    // 每个预览只能导入一次
+    let error =
+        profile::import(target.handle(), preview.id, &[ProfileSection::Updates]).unwrap_err();
+    assert_eq!(error.code(), ErrorCode::InvalidProfile);
+
+    // 再次预览时已导入的分组没有变化
+    fs::write(&path, exported.to_toml().unwrap()).unwrap();
+    let preview = profile::preview(target.handle(), &path).unwrap();
+    for section in preview.sections {
+        let imported = matches!(
+            section.section,
+            ProfileSection::Appearance | ProfileSection::Shortcuts
+        );
+        assert_eq!(section.changes.is_empty(), imported, "{:?}", section);
+    }
+}
+
+#[test]
+fn rejects_invalid_shortcuts_without_tray() {
+    let app = mock_app();
+    let data = ProfileData {
+        shortcuts: Some(BTreeMap::from([(
+            "quit".to_string(),
+            "CmdOrCtrl+Nope".to_string(),
+        )])),
+        ..ProfileData::default()
+    };
+    let error = profile::apply(app.handle(), data, &[ProfileSection::Shortcuts]).unwrap_err();
+    assert_eq!(error.code(), ErrorCode::InvalidArgument);
+    // 菜单和保存的文件都保持原样
+    assert!(app.state::<TrayMenuState>().get().accelerators().is_empty());
+    assert_eq!(
+        TrayMenuDefinition::load(app.handle()),
+        TrayMenuDefinition::default()
+    );
+}
@@ -0,0 +1,122 @@
+#![cfg(not(any(target_os = "android", target_os = "ios")))]
+
+use serde_json::json;
+use tauri_bun_vite_lib::core::updater::release_notes::{
+    aggregate_notes, download_size, releases_between, sanitize_markdown, ReleaseEntry,
+};
+
+#[test]
+fn collects_releases_between_current_and_target() {
+    let manifest = json!({
+        "version": "1.3.0",
+        "notes": "目标版本",
+        "pub_date": "2025-10-01T00:00:00Z",
+        "changelog": [
+            { "version": "1.1.0", "notes": "1.1.0 的说明", "pub_date": "2025-08-01T00:00:00Z" },
+            { "version": "1.0.0", "notes": "当前版本" },
+            { "version": "1.2.0", "notes": "1.2.0 的说明" },
+            { "version": "1.4.0", "notes": "未来版本" },
+            { "version": "not-a-version", "notes": "无法解析" },
+        ],
+    });
+
+    let releases = releases_between(&manifest, "1.0.0", "1.3.0");
+    let versions: Vec<&str> = releases.iter().map(|r| r.version.as_str()).collect();
+    assert_eq!(versions, vec!["1.3.0", "1.2.0", "1.1.0"]);
+    assert_eq!(releases[0].notes, "目标版本");
+    assert_eq!(
+        releases[0].pub_date.as_deref(),
+        Some("2025-10-01T00:00:00Z")
+    );
+    assert_eq!(
+        releases[2].pub_date.as_deref(),
+        Some("2025-08-01T00:00:00Z")
+    );
+}
+
+#[test]
+fn changelog_entry_for_target_takes_precedence() {
+    let manifest = json!({
+        "version": "1.1.0",
+        "notes": "简短说明",
+        "changelog": [{ "version": "1.1.0", "notes": "完整说明" }],
+    });
+
+    let releases = releases_between(&manifest, "1.0.0", "1.1.0");
+    assert_eq!(releases.len(), 1);
+    assert_eq!(releases[0].notes, "完整说明");
+}
+
+#[test]
+fn aggregates_notes_with_version_headings() {
+    let releases = vec![
+        ReleaseEntry {
+            version: "1.2.0".to_string(),
+            notes: "- 新功能\n".to_string(),
+            pub_date: None,
+        },
+        ReleaseEntry {
+            version: "1.1.0".to_string(),
+            notes: String::new(),
+            pub_date: None,
+        },
+    ];
+    assert_eq!(
+        aggregate_notes(&releases),
+        "## 1.2.0\n\n- 新功能\n\n## 1.1.0"
+    );
+}
+
+#[test]
+fn reads_download_size() {
+    assert_eq!(download_size(&json!({ "size": 1024 })), Some(1024));
+    assert_eq!(download_size(&json!({})), None);
+}
+
+#[test]
+fn escapes_raw_html() {
+    assert_eq!(
+        sanitize_markdown("**粗体** <script>alert(1)</script>"),
+        "**粗体** &lt;script>alert(1)&lt;/script>"
+    );
+    assert_eq!(
+        sanitize_markdown("<img src=x onerror=alert(1)>"),
+        "&lt;img src=x onerror=alert(1)>"
+    );
+    // 自动链接保留
+    assert_eq!(
+        sanitize_markdown("详见 <https://example.com/changelog>"),
+        "详见 <https://example.com/changelog>"
+    );
+}
+
+#[test]
+fn neutralizes_dangerous_links() {
+    assert_eq!(
+        sanitize_markdown("[点击](javascript:alert(1))"),
+        "[点击](#))"
+    );
+    assert_eq!(
+        sanitize_markdown("![图片](data:image/svg+xml;base64,AAAA)"),
+        "![图片](#)"
+    );
+    assert_eq!(
+        sanitize_markdown("[点击](&#106;avascript:alert)"),
+        "[点击](#)"
+    );
+    assert_eq!(
+        sanitize_markdown("[id]: JavaScript:alert(1)\n[ok]: https://example.com"),
+        "[id]: #\n[ok]: https://example.com"
+    );
+}
+
+#[test]
+fn keeps_safe_links() {
+    let markdown = "[文档](https://example.com/docs \"标题\") [邮件](mailto:a@example.com) [锚点](#changes) [相对](./a:b)";
+    assert_eq!(sanitize_markdown(markdown), markdown);
+}
+
+#[test]
+fn strips_control_characters() {
+    assert_eq!(sanitize_markdown("a\u{0}b\r\nc\td"), "ab\nc\td");
+}
@@ -0,0 +1,318 @@
+mod common;
+
+use common::app::{TestApp, TestDir};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::Manager;
+use tauri_bun_vite_lib::core::settings::migrations::{self, MIGRATIONS, SCHEMA_VERSION};
+use tauri_bun_vite_lib::core::settings::{
+    self, AppearanceSettings, Layout, LoadStatus, Settings, SettingsState, ThemeColor,
+    WindowSettings,
+};
+use tauri_bun_vite_lib::models::ErrorCode;
+
+fn mock_app() -> TestApp {
+    common::app::mock_app("settings")
+}
+
+/// 把 fixtures/settings 中的文件复制到独立的临时目录，返回临时目录和设置文件路径，
+/// 临时目录释放时删除
+fn fixture(name: &str) -> (TestDir, PathBuf) {
+    let dir = TestDir::new("settings");
+    let path = dir.join("settings.json");
+    let source = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/settings")
+        .join(name);
+    fs::copy(source, &path).unwrap();
+    (dir, path)
+}
+
+fn read_json(path: &Path) -> Value {
+    serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+}
+
+/// 设置文件旁边某一类的备份文件，例如 `settings.json.corrupt.<时间戳>.bak`
+fn backups(path: &Path, kind: &str) -> Vec<PathBuf> {
+    let prefix = format!("settings.json.{}.", kind);
+    let mut backups: Vec<PathBuf> = fs::read_dir(path.parent().unwrap())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|backup| {
+            let name = backup.file_name().unwrap().to_string_lossy();
+            name.starts_with(&prefix) && name.ends_with(".bak")
+        })
+        .collect();
+    backups.sort();
+    backups
+}
+
+fn appearance(layout: Layout, theme_color: ThemeColor, dark_mode: bool) -> Settings {
+    Settings {
+        appearance: AppearanceSettings {
+            layout,
+            theme_color,
+            dark_mode,
+        },
+        ..Settings::default()
+    }
+}
+
+#[test]
+fn serializes_in_camel_case() {
+    let settings = appearance(Layout::Sidebar, ThemeColor::Teal, true);
+    assert_eq!(
+        serde_json::to_value(&settings).unwrap(),
+        json!({
+            "appearance": { "layout": "sidebar", "themeColor": "teal", "darkMode": true },
+            "window": { "closeToTray": false, "minimizeToTray": false }
+        })
+    );
+    // 缺少的字段使用默认值
+    assert_eq!(
+        serde_json::from_value::<Settings>(json!({ "appearance": { "darkMode": true } })).unwrap(),
+        appearance(Layout::Classic, ThemeColor::Blue, true)
+    );
+}
+
+#[test]
+fn patches_only_given_fields() {
+    let settings = appearance(Layout::Horizontal, ThemeColor::Blue, false)
+        .patch(&json!({ "appearance": { "themeColor": "pink", "darkMode": true } }))
+        .unwrap();
+    assert_eq!(
+        settings,
+        appearance(Layout::Horizontal, ThemeColor::Pink, true)
+    );
+}
+
+#[test]
+fn patches_nested_window_fields() {
+    let settings = Settings::default()
+        .patch(&json!({ "window": { "minimizeToTray": true } }))
+        .unwrap()
+        .patch(&json!({ "window": { "closeToTray": true } }))
+        .unwrap();
+    assert_eq!(
+        settings.window,
+        WindowSettings {
+            close_to_tray: true,
+            minimize_to_tray: true,
+        }
+    );
+}
+
+#[test]
+fn rejects_invalid_patches() {
+    for patch in [
+        json!({ "appearance": { "layout": "grid" } }),
+        json!({ "appearance": { "darkMode": "yes" } }),
+        json!({ "appearance": { "unknown": 1 } }),
+        json!({ "unknown": 1 }),
+        json!({ "window": { "hideOnBlur": true } }),
+        json!({ "appearance": true }),
+        json!(["appearance"]),
+    ] {
+        let error = Settings::default().patch(&patch).unwrap_err();
+        assert_eq!(error.code(), ErrorCode::InvalidArgument, "{}", patch);
+    }
+}
+
+#[test]
+fn every_version_has_a_migration() {
+    assert_eq!(MIGRATIONS.len() as u32, SCHEMA_VERSION - 1);
+    assert_eq!(
+        migrations::version_of(json!({}).as_object().unwrap()),
+        Ok(1)
+    );
+    assert!(migrations::version_of(json!({ "version": 0 }).as_object().unwrap()).is_err());
+    assert!(migrations::version_of(json!({ "version": "2" }).as_object().unwrap()).is_err());
+}
+
+#[test]
+fn migrates_v1_and_backs_up_original() {
+    let (_dir, path) = fixture("v1.json");
+    let original = fs::read_to_string(&path).unwrap();
+
+    let (settings, status) = Settings::load_file(&path);
+    assert_eq!(
+        settings,
+        appearance(Layout::Sidebar, ThemeColor::Purple, true)
+    );
+    let backup = backups(&path, "v1").pop().expect("升级前的文件没有备份");
+    assert_eq!(
+        status,
+        LoadStatus::Migrated {
+            from: 1,
+            backup: backup.clone()
+        }
+    );
+    assert_eq!(fs::read_to_string(&backup).unwrap(), original);
+    assert_eq!(
+        read_json(&path),
+        json!({
+            "version": SCHEMA_VERSION,
+            "appearance": { "layout": "sidebar", "themeColor": "purple", "darkMode": true },
+            "window": { "closeToTray": false, "minimizeToTray": false }
+        })
+    );
+
+    // 升级后的文件再次加载时不需要升级
+    assert_eq!(Settings::load_file(&path), (settings, LoadStatus::Loaded));
+}
+
+#[test]
+fn migrates_partial_v1_with_defaults() {
+    let (_dir, path) = fixture("v1-partial.json");
+    let (settings, status) = Settings::load_file(&path);
+    assert_eq!(
+        settings,
+        appearance(Layout::Classic, ThemeColor::Green, false)
+    );
+    assert!(matches!(status, LoadStatus::Migrated { from: 1, .. }));
+}
+
+#[test]
+fn loads_current_version_without_rewriting() {
+    let (_dir, path) = fixture("v2.json");
+    let original = fs::read_to_string(&path).unwrap();
+    let (settings, status) = Settings::load_file(&path);
+    assert_eq!(
+        settings,
+        Settings {
+            window: WindowSettings {
+                close_to_tray: true,
+                minimize_to_tray: false,
+            },
+            ..appearance(Layout::Horizontal, ThemeColor::Orange, false)
+        }
+    );
+    assert_eq!(status, LoadStatus::Loaded);
+    assert_eq!(fs::read_to_string(&path).unwrap(), original);
+}
+
+#[test]
+fn resets_unreadable_files_to_defaults() {
+    for name in ["corrupt.json", "invalid-value.json"] {
+        let (_dir, path) = fixture(name);
+        let original = fs::read_to_string(&path).unwrap();
+
+        let (settings, status) = Settings::load_file(&path);
+        assert_eq!(settings, Settings::default(), "{}", name);
+        let LoadStatus::Reset { backup, .. } = &status else {
+            panic!("{} 应该被重置，实际为 {:?}", name, status);
+        };
+        assert_eq!(backups(&path, "corrupt"), vec![backup.clone()]);
+        assert_eq!(fs::read_to_string(backup).unwrap(), original);
+        assert_eq!(read_json(&path)["version"], json!(SCHEMA_VERSION));
+    }
+}
+
+#[test]
+fn keeps_files_from_newer_versions_untouched() {
+    let (_dir, path) = fixture("future.json");
+    let original = fs::read_to_string(&path).unwrap();
+
+    // 只读取认识的字段，文件保持原样，也不会产生备份
+    let (settings, status) = Settings::load_file(&path);
+    assert_eq!(
+        settings,
+        appearance(Layout::Sidebar, ThemeColor::Teal, false)
+    );
+    assert_eq!(status, LoadStatus::Newer { version: 99 });
+    assert_eq!(fs::read_to_string(&path).unwrap(), original);
+    assert!(backups(&path, "corrupt").is_empty());
+
+    // 保存设置时先备份新版本的文件
+    settings.save_file(&path).unwrap();
+    let backup = backups(&path, "v99").pop().expect("新版本的文件没有备份");
+    assert_eq!(fs::read_to_string(backup).unwrap(), original);
+    assert_eq!(read_json(&path)["version"], json!(SCHEMA_VERSION));
+}
+
+#[test]
+fn keeps_every_backup() {
+    let (_dir, path) = fixture("corrupt.json");
+    let original = fs::read(&path).unwrap();
+    // 同一毫秒内多次重置也不会覆盖之前的备份
+    for _ in 0..3 {
+        fs::write(&path, &original).unwrap();
+        Settings::load_file(&path);
+    }
+    let backups = backups(&path, "corrupt");
+    assert_eq!(backups.len(), 3);
+    for backup in backups {
+        assert_eq!(fs::read(backup).unwrap(), original);
+    }
+}
+
+#[test]
+fn missing_file_uses_defaults() {
+    let dir = TestDir::new("settings");
+    let path = dir.join("missing.json");
+    assert_eq!(
+        Settings::load_file(&path),
+        (Settings::default(), LoadStatus::Missing)
+    );
+    assert!(!path.exists());
+}
+
+#[test]
+fn persists_updates_and_resets() {
+    let app = mock_app();
+    settings::init(app.handle());
+    assert_eq!(Settings::load(app.handle()), Settings::default());
+
+    let updated = settings::update(
+        app.handle(),
+        &json!({ "appearance": { "layout": "horizontal" } }),
+    )
+    .unwrap();
+    assert_eq!(updated.appearance.layout, Layout::Horizontal);
+    assert_eq!(app.state::<SettingsState>().get(), updated);
+    assert_eq!(Settings::load(app.handle()), updated);
+
+    // 校验失败时保留原来的设置
+    assert!(settings::update(app.handle(), &json!({ "appearance": { "layout": 1 } })).is_err());
+    assert_eq!(Settings::load(app.handle()), updated);
+
+    assert_eq!(settings::reset(app.handle()).unwrap(), Settings::default());
+    assert_eq!(Settings::load(app.handle()), Settings::default());
+    assert_eq!(app.state::<SettingsState>().get(), Settings::default());
+}
+
+#[test]
+fn reloads_edited_file_without_resetting() {
+    let app = mock_app();
+    settings::init(app.handle());
+    let path = settings::config_path(app.handle()).unwrap();
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+
+    // 文件不存在或与当前设置相同时没有变化
+    assert_eq!(settings::reload(app.handle()), Ok(false));
+    Settings::default().save_file(&path).unwrap();
+    assert_eq!(settings::reload(app.handle()), Ok(false));
+
+    fs::write(
+        &path,
+        r#"{ "version": 2, "appearance": { "layout": "sidebar" } }"#,
+    )
+    .unwrap();
+    assert_eq!(settings::reload(app.handle()), Ok(true));
+    assert_eq!(
+        app.state::<SettingsState>().get(),
+        appearance(Layout::Sidebar, ThemeColor::Blue, false)
+    );
+
+    // 无效的修改被拒绝，文件保持原样，继续使用之前的设置
+    let invalid = r#"{ "version": 2, "appearance": { "layout": "grid" } }"#;
+    fs::write(&path, invalid).unwrap();
+    let error = settings::reload(app.handle()).unwrap_err();
+    assert_eq!(error.code(), ErrorCode::InvalidArgument);
+    assert_eq!(fs::read_to_string(&path).unwrap(), invalid);
+    assert!(backups(&path, "corrupt").is_empty());
+    assert_eq!(
+        app.state::<SettingsState>().get().appearance.layout,
+        Layout::Sidebar
+    );
+}
@@ -0,0 +1,182 @@
+use std::time::{Duration, Instant};
+use tauri_bun_vite_lib::core::startup::{
+    StartupError, StartupOrchestrator, StartupTask, TaskStatus,
+};
+use tauri_bun_vite_lib::models::{AppError, ErrorCode};
+
+#[test]
+fn completes_only_when_all_required_tasks_are_done() {
+    let mut orchestrator = StartupOrchestrator::new();
+    orchestrator.register(StartupTask::new("frontend")).unwrap();
+    orchestrator.register(StartupTask::new("backend")).unwrap();
+    orchestrator
+        .register(StartupTask::new("telemetry").optional())
+        .unwrap();
+
+    assert!(!orchestrator.complete("frontend").unwrap());
+    assert!(orchestrator.complete("backend").unwrap());
+    assert_eq!(
+        orchestrator.status("telemetry").unwrap(),
+        &TaskStatus::Pending
+    );
+
+    // 重复完成不是错误，但也不会再次报告全部完成
+    assert_eq!(orchestrator.complete("frontend"), Ok(false));
+    assert_eq!(orchestrator.status("frontend").unwrap(), &TaskStatus::Done);
+}
+
+#[test]
+fn unknown_task_returns_typed_error() {
+    let mut orchestrator = StartupOrchestrator::new();
+    orchestrator.register(StartupTask::new("frontend")).unwrap();
+
+    assert_eq!(
+        orchestrator.complete("database"),
+        Err(StartupError::UnknownTask("database".to_string()))
+    );
+    assert_eq!(
+        orchestrator.register(StartupTask::new("frontend")),
+        Err(StartupError::DuplicateTask("frontend".to_string()))
+    );
+}
+
+#[test]
+fn dependencies_must_finish_first() {
+    let mut orchestrator = StartupOrchestrator::new();
+    orchestrator.register(StartupTask::new("config")).unwrap();
+    orchestrator
+        .register(StartupTask::new("backend").depends_on(["config"]))
+        .unwrap();
+    orchestrator
+        .register(StartupTask::new("cache").depends_on(["missing"]))
+        .unwrap();
+
+    // 依赖未注册与任务未注册使用不同的错误码
+    let error = AppError::from(orchestrator.validate().unwrap_err());
+    assert_eq!(error.code(), ErrorCode::UnknownDependency);
+    assert!(matches!(
+        orchestrator.start("backend", Instant::now()),
+        Err(StartupError::DependencyNotReady { .. })
+    ));
+
+    orchestrator.complete("config").unwrap();
+    assert!(orchestrator.dependencies_ready("backend").unwrap());
+    orchestrator.start("backend", Instant::now()).unwrap();
+}
+
+#[test]
+fn failed_dependency_is_reported() {
+    let mut orchestrator = StartupOrchestrator::new();
+    orchestrator.register(StartupTask::new("config")).unwrap();
+    orchestrator
+        .register(StartupTask::new("backend").depends_on(["config"]))
+        .unwrap();
+
+    orchestrator.fail("config", "磁盘不可用").unwrap();
+    assert!(orchestrator.has_failed());
+    assert!(matches!(
+        orchestrator.dependencies_ready("backend"),
+        Err(StartupError::DependencyFailed { .. })
+    ));
+}
+
+#[test]
+fn running_tasks_time_out() {
+    let mut orchestrator = StartupOrchestrator::new();
+    orchestrator
+        .register(StartupTask::new("backend").timeout(Duration::from_secs(5)))
+        .unwrap();
+
+    orchestrator
+        .register(
+            StartupTask::new("telemetry")
+                .timeout(Duration::from_secs(1))
+                .optional(),
+        )
+        .unwrap();
+
+    let started = Instant::now();
+    orchestrator.start("backend", started).unwrap();
+    orchestrator.start("telemetry", started).unwrap();
+    assert!(orchestrator
+        .check_timeouts(started + Duration::from_secs(1))
+        .is_empty());
+    assert_eq!(
+        orchestrator.check_timeouts(started + Duration::from_secs(5)),
+        vec!["backend".to_string()]
+    );
+    assert_eq!(
+        orchestrator.status("backend").unwrap(),
+        &TaskStatus::TimedOut
+    );
+    // 可选任务同样被标记为超时，但不会作为启动失败返回
+    assert_eq!(
+        orchestrator.status("telemetry").unwrap(),
+        &TaskStatus::TimedOut
+    );
+    assert_eq!(
+        orchestrator.complete("backend"),
+        Err(StartupError::AlreadyFinished("backend".to_string()))
+    );
+}
+
+#[test]
+fn progress_is_weighted() {
+    let mut orchestrator = StartupOrchestrator::new();
+    orchestrator.register(StartupTask::new("frontend")).unwrap();
+    orchestrator
+        .register(StartupTask::new("backend").weight(3))
+        .unwrap();
+
+    assert_eq!(orchestrator.progress(), 0.0);
+    orchestrator.complete("backend").unwrap();
+    assert_eq!(orchestrator.progress(), 0.75);
+}
+
+#[test]
+fn reported_progress_counts_towards_overall() {
+    let mut orchestrator = StartupOrchestrator::new();
+    orchestrator.register(StartupTask::new("frontend")).unwrap();
+    orchestrator.register(StartupTask::new("backend")).unwrap();
+
+    assert_eq!(orchestrator.report("backend", 50).unwrap(), 0.25);
+    assert_eq!(orchestrator.report("backend", 250).unwrap(), 0.5);
+}
+
+#[test]
+fn failed_tasks_can_be_retried() {
+    let mut orchestrator = StartupOrchestrator::new();
+    orchestrator.register(StartupTask::new("frontend")).unwrap();
+    orchestrator.register(StartupTask::new("backend")).unwrap();
+
+    orchestrator.complete("frontend").unwrap();
+    orchestrator.fail("backend", "数据库连接失败").unwrap();
+    assert_eq!(
+        orchestrator.failure(),
+        Some(("backend", "数据库连接失败".to_string()))
+    );
+
+    assert_eq!(orchestrator.reset_failed(), vec!["backend".to_string()]);
+    assert_eq!(
+        orchestrator.status("backend").unwrap(),
+        &TaskStatus::Pending
+    );
+    assert_eq!(orchestrator.failure(), None);
+    assert!(orchestrator.complete("backend").unwrap());
+}
+
+#[test]
+fn retried_tasks_get_a_new_attempt() {
+    let mut orchestrator = StartupOrchestrator::new();
+    orchestrator.register(StartupTask::new("frontend")).unwrap();
+    orchestrator.register(StartupTask::new("backend")).unwrap();
+    assert_eq!(orchestrator.attempt("backend"), Ok(0));
+    assert_eq!(orchestrator.is_required("backend"), Ok(true));
+
+    orchestrator.start("backend", Instant::now()).unwrap();
+    orchestrator.fail("backend", "数据库连接失败").unwrap();
+    orchestrator.reset_failed();
+    // 重试之前的执行通过比较重试次数识别出过期的结果
+    assert_eq!(orchestrator.attempt("backend"), Ok(1));
+    assert_eq!(orchestrator.attempt("frontend"), Ok(0));
+}
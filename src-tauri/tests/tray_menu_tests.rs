@@ -0,0 +1,131 @@
+#![cfg(not(any(target_os = "android", target_os = "ios")))]
+
+use serde_json::json;
+use std::sync::Arc;
+use tauri::test::MockRuntime;
+use tauri_bun_vite_lib::core::locale::Locale;
+use tauri_bun_vite_lib::core::tray::{MenuNode, TrayHandlers, TrayMenuDefinition};
+use tauri_bun_vite_lib::models::ErrorCode;
+
+#[test]
+fn default_menu_is_valid() {
+    let definition = TrayMenuDefinition::default();
+    definition.validate().unwrap();
+    assert!(definition
+        .nodes()
+        .iter()
+        .any(|node| node.id() == Some("quit")));
+}
+
+#[test]
+fn parses_declarative_definition() {
+    let definition: TrayMenuDefinition = serde_json::from_value(json!({
+        "items": [
+            { "type": "item", "id": "open", "text": "打开", "icon": "app", "accelerator": "CmdOrCtrl+O" },
+            { "type": "separator" },
+            { "type": "submenu", "text": "更多", "items": [
+                { "type": "check", "id": "pin", "text": "置顶", "checked": true },
+                { "type": "item", "id": "disabled", "text": "不可用", "enabled": false }
+            ] }
+        ]
+    }))
+    .unwrap();
+    definition.validate().unwrap();
+
+    assert_eq!(
+        definition.items[0],
+        MenuNode::Item {
+            id: "open".to_string(),
+            text: "打开".to_string(),
+            text_key: None,
+            enabled: true,
+            accelerator: Some("CmdOrCtrl+O".to_string()),
+            icon: Some("app".to_string()),
+        }
+    );
+    assert_eq!(definition.items[1], MenuNode::Separator);
+    assert_eq!(definition.is_checked("pin"), Some(true));
+
+    // 序列化后可以原样读回
+    let value = serde_json::to_value(&definition).unwrap();
+    assert_eq!(
+        serde_json::from_value::<TrayMenuDefinition>(value).unwrap(),
+        definition
+    );
+}
+
+#[test]
+fn rejects_invalid_definitions() {
+    let invalid = [
+        json!({ "items": [] }),
+        json!({ "items": [{ "type": "item", "id": "", "text": "空 id" }] }),
+        json!({ "items": [{ "type": "item", "id": "a", "text": " " }] }),
+        json!({ "items": [{ "type": "submenu", "text": "空子菜单", "items": [] }] }),
+        json!({ "items": [
+            { "type": "check", "id": "a", "text": "A" },
+            { "type": "check", "id": "a", "text": "A" }
+        ] }),
+        json!({ "items": [
+            { "type": "item", "id": "a", "text": "A" },
+            { "type": "check", "id": "a", "text": "A" }
+        ] }),
+    ];
+    for value in invalid {
+        let definition: TrayMenuDefinition = serde_json::from_value(value.clone()).unwrap();
+        let error = definition.validate().unwrap_err();
+        assert_eq!(error.code(), ErrorCode::InvalidArgument, "{value}");
+    }
+}
+
+#[test]
+fn updates_check_state_in_submenus() {
+    let mut definition = TrayMenuDefinition::default();
+    assert_eq!(definition.is_checked("language.en-US"), Some(false));
+
+    assert!(definition.set_checked("language.en-US", true));
+    assert_eq!(definition.is_checked("language.en-US"), Some(true));
+    assert!(!definition.set_checked("missing", true));
+    assert_eq!(definition.is_checked("open"), None);
+}
+
+#[test]
+fn language_items_behave_as_radio_group() {
+    let mut definition = TrayMenuDefinition::default();
+    definition.set_checked("language.en-US", true);
+    definition.set_checked("language.zh-CN", true);
+
+    definition.sync_language(Locale::ZhCn);
+    assert_eq!(definition.is_checked("language.en-US"), Some(false));
+    assert_eq!(definition.is_checked("language.zh-CN"), Some(true));
+}
+
+#[test]
+fn localized_text_follows_locale() {
+    let definition = TrayMenuDefinition::default();
+    let quit = definition
+        .nodes()
+        .into_iter()
+        .find(|node| node.id() == Some("quit"))
+        .unwrap();
+    assert_eq!(quit.display_text(Locale::EnUs), "Quit Coco");
+    assert_eq!(quit.display_text(Locale::ZhCn), "退出 Coco");
+
+    // 没有翻译的键使用 text
+    let custom = MenuNode::item("custom", "自定义").localized("tray.missing");
+    assert_eq!(custom.display_text(Locale::ZhCn), "自定义");
+}
+
+#[test]
+fn handler_registry_prefers_exact_then_longest_prefix() {
+    let handlers = TrayHandlers::<MockRuntime>::default();
+    handlers.on("language.zh-CN", |_, _| {});
+    handlers.on_prefix("language.", |_, _| {});
+    handlers.on_prefix("language.en", |_, _| {});
+
+    let exact = handlers.find("language.zh-CN").unwrap();
+    let generic = handlers.find("language.ja-JP").unwrap();
+    let longest = handlers.find("language.en-US").unwrap();
+    assert!(!Arc::ptr_eq(&exact, &generic));
+    assert!(!Arc::ptr_eq(&longest, &generic));
+    assert!(handlers.find("quit").is_none());
+}
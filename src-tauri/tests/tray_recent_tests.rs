@@ -0,0 +1,203 @@
+#![cfg(not(any(target_os = "android", target_os = "ios")))]
+
+mod common;
+
+use common::app::{mock_app_with, TestApp};
+use tauri::ipc::{CallbackFn, InvokeBody};
+use tauri::test::{get_ipc_response, mock_builder, MockRuntime};
+use tauri::webview::InvokeRequest;
+use tauri::{Manager, WebviewWindowBuilder};
+use tauri_bun_vite_lib::core::locale::Locale;
+use tauri_bun_vite_lib::core::tray::recent::{
+    add_recent_item, clear_recent_items, get_recent_items, MAX_RECENT_ITEMS,
+};
+use tauri_bun_vite_lib::core::tray::{
+    MenuNode, QuickAction, RecentItem, RecentKind, RecentList, RecentState, TrayMenuDefinition,
+};
+use tauri_bun_vite_lib::models::ErrorCode;
+
+fn order(id: u32) -> RecentItem {
+    RecentItem {
+        id: format!("order-{}", id),
+        kind: RecentKind::Order,
+        title: format!("#{}", id),
+        route: format!("/orders/{}", id),
+    }
+}
+
+fn mock_app() -> TestApp {
+    let app = mock_app_with(
+        "recent",
+        mock_builder().invoke_handler(tauri::generate_handler![add_recent_item, get_recent_items]),
+    );
+    let list = RecentList::load(app.handle());
+    app.manage(RecentState::new(list));
+    app
+}
+
+#[test]
+fn keeps_most_recent_first_without_duplicates() {
+    let mut list = RecentList::default();
+    for id in 0..MAX_RECENT_ITEMS as u32 + 2 {
+        list.push(order(id)).unwrap();
+    }
+    list.push(order(5)).unwrap();
+
+    assert_eq!(list.items.len(), MAX_RECENT_ITEMS);
+    assert_eq!(list.items[0].id, "order-5");
+    assert_eq!(list.items[1].id, "order-11");
+    assert_eq!(
+        list.items
+            .iter()
+            .filter(|item| item.id == "order-5")
+            .count(),
+        1
+    );
+    // 最旧的条目被丢弃
+    assert!(!list.items.iter().any(|item| item.id == "order-0"));
+
+    assert!(list.remove("order-5"));
+    assert!(!list.remove("order-5"));
+}
+
+#[test]
+fn rejects_invalid_entries() {
+    let mut list = RecentList::default();
+    let mut item = order(1);
+    item.route = "orders/1".to_string();
+    assert_eq!(
+        list.push(item).unwrap_err().code(),
+        ErrorCode::InvalidArgument
+    );
+
+    let action = QuickAction {
+        id: "a".to_string(),
+        title: "A".to_string(),
+        title_key: None,
+        route: "/a".to_string(),
+    };
+    let error = list.set_pinned(vec![action.clone(), action]).unwrap_err();
+    assert_eq!(error.code(), ErrorCode::InvalidArgument);
+    assert_eq!(list.pinned, RecentList::default().pinned);
+}
+
+#[test]
+fn resolves_routes_from_menu_ids() {
+    let mut list = RecentList::default();
+    list.push(order(7)).unwrap();
+
+    assert_eq!(list.route_for("recent.item.order-7"), Some("/orders/7"));
+    assert_eq!(
+        list.route_for("recent.pinned.dashboard"),
+        Some("/dashboard")
+    );
+    assert_eq!(list.route_for("recent.item.order-8"), None);
+    assert_eq!(list.route_for("dashboard"), None);
+}
+
+#[test]
+fn expands_placeholder_submenu() {
+    let definition = TrayMenuDefinition::default();
+    let find_recent = |definition: &TrayMenuDefinition| {
+        definition
+            .nodes()
+            .into_iter()
+            .find_map(|node| match node {
+                MenuNode::Submenu {
+                    id: Some(id),
+                    items,
+                    ..
+                } if id == "recent" => Some(items.clone()),
+                _ => None,
+            })
+            .unwrap()
+    };
+
+    let empty = RecentList {
+        pinned: Vec::new(),
+        items: Vec::new(),
+    };
+    let items = find_recent(&empty.expand(&definition, Locale::EnUs));
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].id(), Some("recent.empty"));
+    assert!(!items[0].is_enabled());
+
+    let mut list = RecentList::default();
+    list.push(order(7)).unwrap();
+    let expanded = list.expand(&definition, Locale::ZhCn);
+    expanded.validate().unwrap();
+    let items = find_recent(&expanded);
+    let ids: Vec<_> = items.iter().map(|node| node.id()).collect();
+    assert_eq!(
+        ids,
+        vec![
+            Some("recent.pinned.dashboard"),
+            Some("recent.pinned.workbench"),
+            None,
+            Some("recent.item.order-7"),
+            None,
+            Some("recent.clear"),
+        ]
+    );
+    assert_eq!(items[0].display_text(Locale::ZhCn), "控制台");
+    assert_eq!(items[3].display_text(Locale::ZhCn), "订单 · #7");
+    // 菜单定义本身不变
+    assert_eq!(find_recent(&definition).len(), 1);
+}
+
+#[test]
+fn persists_recent_items() {
+    let app = mock_app();
+    add_recent_item(app.handle().clone(), order(1)).unwrap();
+    let list = add_recent_item(app.handle().clone(), order(2)).unwrap();
+    assert_eq!(list.items.len(), 2);
+    assert_eq!(RecentList::load(app.handle()), list);
+
+    let list = clear_recent_items(app.handle().clone()).unwrap();
+    assert!(list.items.is_empty());
+    assert_eq!(list.pinned, RecentList::default().pinned);
+    assert_eq!(RecentList::load(app.handle()), list);
+}
+
+/// 按前端 `recordRecent` 的方式调用命令
+fn invoke(
+    webview: &tauri::WebviewWindow<MockRuntime>,
+    cmd: &str,
+    body: serde_json::Value,
+) -> RecentList {
+    get_ipc_response(
+        webview,
+        InvokeRequest {
+            cmd: cmd.into(),
+            callback: CallbackFn(0),
+            error: CallbackFn(1),
+            url: "http://tauri.localhost".parse().unwrap(),
+            body: InvokeBody::Json(body),
+            headers: Default::default(),
+            invoke_key: tauri::test::INVOKE_KEY.to_string(),
+        },
+    )
+    .expect("命令调用失败")
+    .deserialize()
+    .unwrap()
+}
+
+#[test]
+fn order_detail_records_recent_item() {
+    let app = mock_app();
+    let webview = WebviewWindowBuilder::new(app.handle(), "order-42", Default::default())
+        .build()
+        .unwrap();
+
+    // 与 OrderDetailView 打开订单时提交的条目相同
+    let item = serde_json::json!({
+        "item": { "id": "order-42", "kind": "order", "title": "#42", "route": "/orders/42" }
+    });
+    invoke(&webview, "add_recent_item", item);
+
+    let list = invoke(&webview, "get_recent_items", serde_json::json!({}));
+    assert_eq!(list.items.len(), 1);
+    assert_eq!(list.items[0].id, "order-42");
+    assert_eq!(list.items[0].kind, RecentKind::Order);
+    assert_eq!(list.route_for("recent.item.order-42"), Some("/orders/42"));
+}
@@ -0,0 +1,148 @@
+#![cfg(not(any(target_os = "android", target_os = "ios")))]
+
+use serde_json::json;
+use tauri_bun_vite_lib::core::locale::Locale;
+use tauri_bun_vite_lib::core::tray::status::{draw_badge, ItemOverride};
+use tauri_bun_vite_lib::core::tray::{TrayIconVariant, TrayStatus, TrayStatusPatch};
+
+#[test]
+fn icon_follows_state_unless_overridden() {
+    let mut status = TrayStatus::default();
+    assert_eq!(status.icon_variant(), TrayIconVariant::Normal);
+
+    status.update_version = Some("1.2.0".to_string());
+    assert_eq!(status.icon_variant(), TrayIconVariant::Update);
+
+    // 待处理订单优先于可用更新
+    status.pending_orders = 2;
+    assert_eq!(status.icon_variant(), TrayIconVariant::Attention);
+
+    status.icon = Some(TrayIconVariant::Normal);
+    assert_eq!(status.icon_variant(), TrayIconVariant::Normal);
+}
+
+#[test]
+fn derives_item_overrides_from_state() {
+    let mut status = TrayStatus {
+        main_window_visible: false,
+        ..TrayStatus::default()
+    };
+    let items = status.item_overrides(Locale::EnUs);
+    assert_eq!(items["open"].enabled, Some(true));
+    assert_eq!(items["hide"].enabled, Some(false));
+    assert_eq!(
+        items["update"].text.as_deref(),
+        Some("No updates available")
+    );
+    assert_eq!(items["update"].enabled, Some(false));
+    assert_eq!(items["orders"].text.as_deref(), Some("No pending orders"));
+
+    status.main_window_visible = true;
+    status.update_version = Some("1.2.0".to_string());
+    status.pending_orders = 3;
+    let items = status.item_overrides(Locale::ZhCn);
+    assert_eq!(items["open"].enabled, Some(false));
+    assert_eq!(items["hide"].enabled, Some(true));
+    assert_eq!(items["update"].text.as_deref(), Some("有可用更新：1.2.0"));
+    assert_eq!(items["update"].enabled, Some(true));
+    assert_eq!(items["orders"].text.as_deref(), Some("3 个待处理订单"));
+    assert_eq!(items["orders"].enabled, Some(true));
+}
+
+#[test]
+fn tooltip_lists_pending_work() {
+    let mut status = TrayStatus::default();
+    assert_eq!(status.tooltip_text(Locale::EnUs), "Tauri App");
+
+    status.pending_orders = 1;
+    status.update_version = Some("1.2.0".to_string());
+    assert_eq!(
+        status.tooltip_text(Locale::EnUs),
+        "Tauri App\n1 pending order\nUpdate available: 1.2.0"
+    );
+
+    status.tooltip = Some("自定义".to_string());
+    assert_eq!(status.tooltip_text(Locale::EnUs), "自定义");
+}
+
+#[test]
+fn patch_distinguishes_missing_and_null_fields() {
+    let mut status = TrayStatus::default();
+    let patch: TrayStatusPatch = serde_json::from_value(json!({
+        "icon": "attention",
+        "tooltip": "忙碌中",
+        "pendingOrders": 5,
+        "items": [{ "id": "dashboard", "text": "订单看板", "enabled": false }]
+    }))
+    .unwrap();
+    status.apply_patch(patch);
+    assert_eq!(status.icon, Some(TrayIconVariant::Attention));
+    assert_eq!(status.tooltip.as_deref(), Some("忙碌中"));
+    assert_eq!(status.pending_orders, 5);
+    assert_eq!(
+        status.item_overrides(Locale::EnUs)["dashboard"],
+        ItemOverride {
+            text: Some("订单看板".to_string()),
+            enabled: Some(false),
+        }
+    );
+
+    // 缺失的字段保持不变，null 恢复默认
+    let patch: TrayStatusPatch = serde_json::from_value(json!({
+        "tooltip": null,
+        "items": [{ "id": "dashboard", "text": null }]
+    }))
+    .unwrap();
+    status.apply_patch(patch);
+    assert_eq!(status.icon, Some(TrayIconVariant::Attention));
+    assert_eq!(status.tooltip, None);
+    assert_eq!(status.pending_orders, 5);
+    assert_eq!(status.items["dashboard"].text, None);
+    assert_eq!(status.items["dashboard"].enabled, Some(false));
+
+    let patch: TrayStatusPatch = serde_json::from_value(json!({
+        "items": [{ "id": "dashboard", "enabled": null }]
+    }))
+    .unwrap();
+    status.apply_patch(patch);
+    assert!(status.items.is_empty());
+}
+
+#[test]
+fn frontend_overrides_win_over_state() {
+    let mut status = TrayStatus {
+        main_window_visible: false,
+        ..TrayStatus::default()
+    };
+    status.items.insert(
+        "open".to_string(),
+        ItemOverride {
+            text: Some("Show".to_string()),
+            enabled: None,
+        },
+    );
+    let open = &status.item_overrides(Locale::EnUs)["open"];
+    assert_eq!(open.text.as_deref(), Some("Show"));
+    assert_eq!(open.enabled, Some(true));
+}
+
+#[test]
+fn badge_is_drawn_in_top_right_corner() {
+    let (width, height) = (32, 32);
+    let mut rgba = vec![0u8; (width * height * 4) as usize];
+    let color = [0xE5, 0x39, 0x35, 0xFF];
+    draw_badge(&mut rgba, width, height, color);
+
+    let pixel = |x: u32, y: u32| {
+        let offset = ((y * width + x) * 4) as usize;
+        [
+            rgba[offset],
+            rgba[offset + 1],
+            rgba[offset + 2],
+            rgba[offset + 3],
+        ]
+    };
+    assert_eq!(pixel(24, 8), color);
+    assert_eq!(pixel(2, 30), [0, 0, 0, 0]);
+    assert_eq!(pixel(2, 2), [0, 0, 0, 0]);
+}
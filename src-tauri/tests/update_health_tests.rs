@@ -0,0 +1,121 @@
+#![cfg(not(any(target_os = "android", target_os = "ios")))]
+
+use tauri_bun_vite_lib::core::updater::health::{
+    HealthRecord, HealthStatus, RetainedPackage, MAX_UNCONFIRMED_LAUNCHES,
+};
+
+fn package(version: &str) -> RetainedPackage {
+    RetainedPackage {
+        version: version.to_string(),
+        signature: Some(format!("signature-{version}")),
+        file_name: format!("{version}.update"),
+    }
+}
+
+#[test]
+fn new_install_is_pending_until_confirmed() {
+    let mut record = HealthRecord::default();
+    assert_eq!(record.status(), HealthStatus::Confirmed);
+
+    record.installing("0.1.0", package("0.2.0"));
+    record.launched("0.2.0");
+    assert_eq!(record.status(), HealthStatus::PendingConfirmation);
+    assert_eq!(record.launches, 1);
+
+    record.confirm();
+    assert_eq!(record.status(), HealthStatus::Confirmed);
+    assert_eq!(record.previous_version.as_deref(), Some("0.1.0"));
+}
+
+#[test]
+fn repeated_unconfirmed_launches_are_unstable() {
+    let mut record = HealthRecord::default();
+    record.installing("0.1.0", package("0.2.0"));
+
+    for _ in 0..MAX_UNCONFIRMED_LAUNCHES {
+        record.launched("0.2.0");
+        assert_eq!(record.status(), HealthStatus::PendingConfirmation);
+    }
+    record.launched("0.2.0");
+    assert_eq!(record.status(), HealthStatus::Unstable);
+}
+
+#[test]
+fn launching_another_version_clears_pending_confirmation() {
+    let mut record = HealthRecord::default();
+    record.installing("0.1.0", package("0.2.0"));
+
+    // 安装没有成功，仍然运行旧版本
+    record.launched("0.1.0");
+    assert_eq!(record.status(), HealthStatus::Confirmed);
+    assert_eq!(record.launches, 0);
+}
+
+#[test]
+fn rollback_needs_package_of_previous_version() {
+    let mut record = HealthRecord::default();
+    // 0.1.0 不是通过更新安装的，没有保留更新包
+    record.installing("0.1.0", package("0.2.0"));
+    assert!(record.rollback_package().is_none());
+    assert!(!record.health("0.2.0").rollback_available);
+
+    record.confirm();
+    record.installing("0.2.0", package("0.3.0"));
+    assert_eq!(record.rollback_package(), Some(&package("0.2.0")));
+    assert!(record.health("0.3.0").rollback_available);
+}
+
+#[test]
+fn packaged_current_version_allows_first_rollback() {
+    let mut record = HealthRecord::default();
+    // 0.1.0 不是通过更新安装的，更新前把当前应用打包保留下来
+    let bundle = RetainedPackage {
+        version: "0.1.0".to_string(),
+        signature: None,
+        file_name: "0.1.0.bundle".to_string(),
+    };
+    record.packages.push(bundle.clone());
+    record.installing("0.1.0", package("0.2.0"));
+
+    assert_eq!(record.rollback_package(), Some(&bundle));
+    assert!(record.health("0.2.0").rollback_available);
+}
+
+#[test]
+fn only_current_and_previous_packages_are_retained() {
+    let mut record = HealthRecord::default();
+    record.installing("0.1.0", package("0.2.0"));
+    record.installing("0.2.0", package("0.3.0"));
+    record.installing("0.3.0", package("0.4.0"));
+
+    assert_eq!(record.packages, vec![package("0.3.0"), package("0.4.0")]);
+}
+
+#[test]
+fn rolled_back_version_is_remembered() {
+    let mut record = HealthRecord::default();
+    record.installing("0.1.0", package("0.2.0"));
+    record.installing("0.2.0", package("0.3.0"));
+
+    record.rolled_back("0.3.0");
+    assert_eq!(record.rolled_back_version.as_deref(), Some("0.3.0"));
+    assert_eq!(record.status(), HealthStatus::Confirmed);
+
+    // 回滚后运行的是上一个版本，不能再次回滚
+    record.launched("0.2.0");
+    assert_eq!(record.status(), HealthStatus::Confirmed);
+    assert!(!record.health("0.2.0").rollback_available);
+}
+
+#[test]
+fn rolling_back_confirmed_version_remembers_running_version() {
+    let mut record = HealthRecord::default();
+    record.installing("0.1.0", package("0.2.0"));
+    record.installing("0.2.0", package("0.3.0"));
+    record.launched("0.3.0");
+    record.confirm();
+    assert!(record.health("0.3.0").rollback_available);
+
+    record.rolled_back("0.3.0");
+    assert_eq!(record.rolled_back_version.as_deref(), Some("0.3.0"));
+}
@@ -0,0 +1,169 @@
+#![cfg(not(any(target_os = "android", target_os = "ios")))]
+
+use serde_json::json;
+use tauri_bun_vite_lib::core::updater::policy::{
+    evaluate, rollout_bucket, version_at_least, ClientInfo, PolicyDecision, ReleasePolicy,
+};
+
+fn client(install_id: &str) -> ClientInfo {
+    ClientInfo {
+        install_id: install_id.to_string(),
+        os_version: "10.0.19045".to_string(),
+        arch: "x86_64".to_string(),
+    }
+}
+
+#[test]
+fn manifest_without_policy_fields_uses_defaults() {
+    let policy = ReleasePolicy::from_manifest(&json!({ "version": "1.0.0" }));
+    assert_eq!(policy, ReleasePolicy::default());
+    assert_eq!(policy.rollout, 100);
+
+    let policy = ReleasePolicy::from_manifest(&json!({
+        "version": "1.0.0",
+        "rollout": 25,
+        "minOsVersion": "10.0.22000",
+        "architectures": ["aarch64"],
+        "critical": true,
+    }));
+    assert_eq!(policy.rollout, 25);
+    assert_eq!(policy.min_os_version.as_deref(), Some("10.0.22000"));
+    assert_eq!(policy.architectures, vec!["aarch64"]);
+    assert!(policy.critical);
+}
+
+#[test]
+fn invalid_policy_fields_fall_back_individually() {
+    let policy = ReleasePolicy::from_manifest(&json!({
+        "version": "1.0.0",
+        "rollout": "half",
+        "minOsVersion": 10,
+        "architectures": ["x86_64"],
+        "critical": true,
+    }));
+    // 格式错误的字段使用默认值，其他字段不受影响
+    assert_eq!(policy.rollout, 100);
+    assert_eq!(policy.min_os_version, None);
+    assert_eq!(policy.architectures, vec!["x86_64"]);
+    assert!(policy.critical);
+
+    let policy = ReleasePolicy::from_manifest(&json!({ "rollout": 250 }));
+    assert_eq!(policy.rollout, 100);
+}
+
+#[test]
+fn rollout_bucket_is_stable_and_spread() {
+    assert_eq!(
+        rollout_bucket("install", "1.0.0"),
+        rollout_bucket("install", "1.0.0")
+    );
+
+    let mut buckets = [0u32; 10];
+    for index in 0..1000 {
+        buckets[rollout_bucket(&format!("install-{index}"), "1.0.0") as usize / 10] += 1;
+    }
+    // 每 10% 的分桶大致落入 100 个安装
+    assert!(buckets.iter().all(|count| (50..150).contains(count)));
+}
+
+#[test]
+fn rollout_percentage_limits_offered_installs() {
+    let skipped = Vec::new();
+    let mut policy = ReleasePolicy {
+        rollout: 0,
+        ..Default::default()
+    };
+    assert_eq!(
+        evaluate(&policy, &client("a"), "1.0.0", &skipped),
+        PolicyDecision::NotInRollout
+    );
+
+    policy.rollout = 100;
+    assert_eq!(
+        evaluate(&policy, &client("a"), "1.0.0", &skipped),
+        PolicyDecision::Offer
+    );
+
+    policy.rollout = 30;
+    let offered = (0..1000)
+        .filter(|index| {
+            evaluate(
+                &policy,
+                &client(&format!("install-{index}")),
+                "1.0.0",
+                &skipped,
+            )
+            .is_offered()
+        })
+        .count();
+    assert!((200..400).contains(&offered));
+}
+
+#[test]
+fn skipped_version_is_not_offered() {
+    let skipped = vec!["1.0.0".to_string()];
+    let policy = ReleasePolicy::default();
+    assert_eq!(
+        evaluate(&policy, &client("a"), "1.0.0", &skipped),
+        PolicyDecision::Skipped
+    );
+    assert_eq!(
+        evaluate(&policy, &client("a"), "1.0.1", &skipped),
+        PolicyDecision::Offer
+    );
+}
+
+#[test]
+fn critical_update_ignores_skip_and_rollout() {
+    let skipped = vec!["1.0.0".to_string()];
+    let policy = ReleasePolicy {
+        rollout: 0,
+        critical: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        evaluate(&policy, &client("a"), "1.0.0", &skipped),
+        PolicyDecision::Force
+    );
+}
+
+#[test]
+fn unsupported_platform_is_not_offered_even_if_critical() {
+    let policy = ReleasePolicy {
+        min_os_version: Some("10.0.22000".to_string()),
+        critical: true,
+        ..Default::default()
+    };
+    assert!(matches!(
+        evaluate(&policy, &client("a"), "1.0.0", &[]),
+        PolicyDecision::Unsupported(_)
+    ));
+
+    let policy = ReleasePolicy {
+        architectures: vec!["aarch64".to_string()],
+        ..Default::default()
+    };
+    assert!(matches!(
+        evaluate(&policy, &client("a"), "1.0.0", &[]),
+        PolicyDecision::Unsupported(_)
+    ));
+
+    let policy = ReleasePolicy {
+        architectures: vec!["X86_64".to_string()],
+        min_os_version: Some("10.0".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(
+        evaluate(&policy, &client("a"), "1.0.0", &[]),
+        PolicyDecision::Offer
+    );
+}
+
+#[test]
+fn compares_dotted_versions() {
+    assert!(version_at_least("10.0.19045", "10.0.19041"));
+    assert!(version_at_least("14.1", "14.1.0"));
+    assert!(version_at_least("6.5.0-arch1", "6.5"));
+    assert!(!version_at_least("10.0.19041", "10.0.22000"));
+    assert!(!version_at_least("13", "13.0.1"));
+}
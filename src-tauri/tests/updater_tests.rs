@@ -0,0 +1,480 @@
+#![cfg(not(any(target_os = "android", target_os = "ios")))]
+
+mod common;
+
+use common::app::{build_app, identifier, TestApp, TestDir};
+use common::update_server::{self, UpdateServer, ARTIFACT, LATEST_VERSION};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::async_runtime::block_on;
+use tauri::ipc::{Channel, InvokeResponseBody};
+use tauri::test::{mock_builder, MockRuntime};
+use tauri::{App, Manager};
+use tauri_bun_vite_lib::core::updater::app_updates::{
+    download_update, fetch_update, get_staged_update, install_update, DownloadEvent, PendingUpdate,
+};
+use tauri_bun_vite_lib::core::updater::config::set_updater_settings;
+use tauri_bun_vite_lib::core::updater::health::{HealthRecord, RetainedPackage};
+use tauri_bun_vite_lib::core::updater::{
+    self as updater, staging, StagedUpdate, UpdateMode, UpdaterSettings, UpdaterState,
+};
+use tauri_bun_vite_lib::models::ErrorCode;
+
+/// 构建一个使用模拟运行时的应用，更新端点指向模拟更新服务器
+fn mock_app(endpoint: String) -> TestApp {
+    mock_app_with(UpdaterSettings {
+        endpoints: vec![endpoint],
+        ..Default::default()
+    })
+}
+
+/// 每个测试使用独立的应用标识，避免并行测试共用同一个下载目录
+fn mock_app_with(settings: UpdaterSettings) -> TestApp {
+    mock_app_named(identifier("updater"), settings)
+}
+
+/// 使用指定的应用标识构建应用，相同的标识共用数据目录，用于模拟应用重启
+fn mock_app_named(identifier: String, settings: UpdaterSettings) -> TestApp {
+    let builder = mock_builder()
+        .plugin(tauri_plugin_updater::Builder::new().build())
+        .manage(UpdaterState::new(settings))
+        .manage(PendingUpdate::default())
+        .manage(StagedUpdate::default());
+    build_app(identifier, builder, |config| {
+        config.plugins.0.insert(
+            "updater".to_string(),
+            json!({
+                "pubkey": update_server::pubkey(),
+                "dangerousInsecureTransportProtocol": true,
+            }),
+        );
+    })
+}
+
+/// 收集通过通道发送给前端的下载事件
+fn event_channel() -> (Channel<DownloadEvent>, Arc<Mutex<Vec<Value>>>) {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = events.clone();
+    let channel = Channel::new(move |body| {
+        if let InvokeResponseBody::Json(json) = body {
+            sink.lock()
+                .unwrap()
+                .push(serde_json::from_str(&json).unwrap());
+        }
+        Ok(())
+    });
+    (channel, events)
+}
+
+fn fetch(app: &App<MockRuntime>) -> tauri_bun_vite_lib::models::AppResult<Option<String>> {
+    block_on(fetch_update(
+        app.handle().clone(),
+        app.state::<PendingUpdate>(),
+    ))
+    .map(|metadata| metadata.map(|metadata| metadata.version))
+}
+
+#[test]
+fn fetch_update_reports_new_version() {
+    let server = UpdateServer::start();
+    let app = mock_app(server.endpoint("latest"));
+
+    assert_eq!(fetch(&app).unwrap().as_deref(), Some(LATEST_VERSION));
+    assert!(app.state::<PendingUpdate>().0.lock().unwrap().is_some());
+}
+
+#[test]
+fn fetch_update_returns_release_notes() {
+    let server = UpdateServer::start();
+    let app = mock_app(server.endpoint("latest"));
+
+    let metadata = block_on(fetch_update(
+        app.handle().clone(),
+        app.state::<PendingUpdate>(),
+    ))
+    .unwrap()
+    .unwrap();
+    assert_eq!(metadata.notes.as_deref(), Some("修复了若干问题"));
+    assert_eq!(metadata.pub_date.as_deref(), Some("2025-10-01T00:00:00Z"));
+    assert_eq!(metadata.download_size, Some(ARTIFACT.len() as u64));
+
+    // 模拟应用的当前版本为 0.1.0，早于它的说明不会出现
+    let versions: Vec<&str> = metadata
+        .releases
+        .iter()
+        .map(|release| release.version.as_str())
+        .collect();
+    assert_eq!(versions, vec![LATEST_VERSION, "0.2.0"]);
+    assert_eq!(
+        metadata.aggregated_notes,
+        "## 1.0.0\n\n修复了若干问题\n\n## 0.2.0\n\n新增订单导出"
+    );
+}
+
+#[test]
+fn fetch_update_returns_none_on_204() {
+    let server = UpdateServer::start();
+    let app = mock_app(server.endpoint("none"));
+
+    assert_eq!(fetch(&app).unwrap(), None);
+    assert!(app.state::<PendingUpdate>().0.lock().unwrap().is_none());
+}
+
+#[test]
+fn fetch_update_fails_on_server_error() {
+    let server = UpdateServer::start();
+    let app = mock_app(server.endpoint("error"));
+
+    let error = fetch(&app).unwrap_err();
+    assert_eq!(error.code(), ErrorCode::UpdateFailed);
+}
+
+#[test]
+fn fetch_update_ignores_skipped_version() {
+    let server = UpdateServer::start();
+    let app = mock_app_with(UpdaterSettings {
+        endpoints: vec![server.endpoint("latest")],
+        skipped_versions: vec![LATEST_VERSION.to_string()],
+        ..Default::default()
+    });
+
+    assert_eq!(fetch(&app).unwrap(), None);
+}
+
+#[test]
+fn fetch_update_offers_skipped_critical_update() {
+    let server = UpdateServer::start();
+    let app = mock_app_with(UpdaterSettings {
+        endpoints: vec![server.endpoint("critical")],
+        skipped_versions: vec![LATEST_VERSION.to_string()],
+        ..Default::default()
+    });
+
+    let metadata = block_on(fetch_update(
+        app.handle().clone(),
+        app.state::<PendingUpdate>(),
+    ))
+    .unwrap()
+    .unwrap();
+    assert_eq!(metadata.version, LATEST_VERSION);
+    assert!(metadata.critical);
+}
+
+#[test]
+fn critical_update_is_staged_instead_of_notified() {
+    let server = UpdateServer::start();
+    let app = mock_app(server.endpoint("critical"));
+
+    // 关键更新在后台暂存，下次退出时安装，不会重启正在运行的应用
+    block_on(updater::update(app.handle().clone())).unwrap();
+    let manifest = get_staged_update(app.state::<StagedUpdate>()).unwrap();
+    assert_eq!(manifest.version, LATEST_VERSION);
+    assert!(app.state::<PendingUpdate>().0.lock().unwrap().is_none());
+}
+
+#[test]
+fn fetch_update_respects_rollout_percentage() {
+    let server = UpdateServer::start();
+    let app = mock_app(server.endpoint("no-rollout"));
+
+    assert_eq!(fetch(&app).unwrap(), None);
+}
+
+#[test]
+fn download_streams_started_progress_finished() {
+    let server = UpdateServer::start();
+    let app = mock_app(server.endpoint("latest"));
+    fetch(&app).unwrap();
+
+    let update = app
+        .state::<PendingUpdate>()
+        .0
+        .lock()
+        .unwrap()
+        .take()
+        .unwrap();
+    let (channel, events) = event_channel();
+    let bytes = block_on(download_update(app.handle(), &update, &channel)).unwrap();
+    assert_eq!(bytes, ARTIFACT);
+
+    let events = events.lock().unwrap();
+    assert_eq!(
+        events.first().unwrap(),
+        &json!({ "event": "Started", "data": { "contentLength": ARTIFACT.len(), "resumedFrom": 0 } })
+    );
+    assert_eq!(events.last().unwrap(), &json!({ "event": "Finished" }));
+    let downloaded: u64 = events
+        .iter()
+        .filter(|event| event["event"] == "Progress")
+        .map(|event| event["data"]["chunkLength"].as_u64().unwrap())
+        .sum();
+    assert_eq!(downloaded, ARTIFACT.len() as u64);
+
+    let last_progress = events
+        .iter()
+        .rev()
+        .find(|event| event["event"] == "Progress")
+        .unwrap();
+    assert_eq!(last_progress["data"]["downloaded"], ARTIFACT.len());
+    assert_eq!(last_progress["data"]["total"], ARTIFACT.len());
+    assert_eq!(last_progress["data"]["etaSeconds"], 0);
+}
+
+#[test]
+fn download_rejects_bad_signature() {
+    let server = UpdateServer::start();
+    let app = mock_app(server.endpoint("bad-signature"));
+    fetch(&app).unwrap();
+
+    let update = app
+        .state::<PendingUpdate>()
+        .0
+        .lock()
+        .unwrap()
+        .take()
+        .unwrap();
+    let (channel, _events) = event_channel();
+    let error = block_on(download_update(app.handle(), &update, &channel)).unwrap_err();
+    assert_eq!(error.code(), ErrorCode::UpdateVerificationFailed);
+}
+
+#[test]
+fn download_rejects_truncated_package() {
+    let server = UpdateServer::start();
+    let app = mock_app(server.endpoint("truncated"));
+    fetch(&app).unwrap();
+
+    let update = app
+        .state::<PendingUpdate>()
+        .0
+        .lock()
+        .unwrap()
+        .take()
+        .unwrap();
+    let (channel, events) = event_channel();
+    assert!(block_on(download_update(app.handle(), &update, &channel)).is_err());
+    assert_eq!(
+        events.lock().unwrap().last().unwrap(),
+        &json!({ "event": "Finished" })
+    );
+}
+
+#[test]
+fn download_resumes_after_dropped_connection() {
+    let server = UpdateServer::start();
+    let app = mock_app(server.endpoint("flaky"));
+    fetch(&app).unwrap();
+
+    let update = app
+        .state::<PendingUpdate>()
+        .0
+        .lock()
+        .unwrap()
+        .take()
+        .unwrap();
+    let (channel, events) = event_channel();
+    let bytes = block_on(download_update(app.handle(), &update, &channel)).unwrap();
+    assert_eq!(bytes, ARTIFACT);
+
+    // 第二次请求从第一次断开时已写入的位置继续
+    let ranges = server.ranges();
+    assert_eq!(ranges.len(), 2);
+    assert_eq!(ranges[0], None);
+    let resumed_from: u64 = ranges[1]
+        .as_deref()
+        .and_then(|range| range.strip_prefix("bytes="))
+        .and_then(|range| range.strip_suffix('-'))
+        .and_then(|start| start.parse().ok())
+        .unwrap();
+    assert!(resumed_from > 0);
+
+    // 续传时不会重复发送 Started 事件，已下载字节数连续增长到总大小
+    let events = events.lock().unwrap();
+    let started = events
+        .iter()
+        .filter(|event| event["event"] == "Started")
+        .count();
+    assert_eq!(started, 1);
+    let downloaded: u64 = events
+        .iter()
+        .filter(|event| event["event"] == "Progress")
+        .map(|event| event["data"]["chunkLength"].as_u64().unwrap())
+        .sum();
+    assert_eq!(downloaded, ARTIFACT.len() as u64);
+}
+
+#[test]
+fn download_respects_speed_limit() {
+    let server = UpdateServer::start();
+    let app = mock_app_with(UpdaterSettings {
+        endpoints: vec![server.endpoint("latest")],
+        max_download_speed: Some(32 * 1024),
+        ..Default::default()
+    });
+    fetch(&app).unwrap();
+
+    let update = app
+        .state::<PendingUpdate>()
+        .0
+        .lock()
+        .unwrap()
+        .take()
+        .unwrap();
+    let (channel, _events) = event_channel();
+    let started_at = Instant::now();
+    let bytes = block_on(download_update(app.handle(), &update, &channel)).unwrap();
+    assert_eq!(bytes, ARTIFACT);
+
+    // 48 KiB 的更新包以 32 KiB/s 下载至少需要 1.5 秒
+    assert!(started_at.elapsed() >= Duration::from_millis(1400));
+}
+
+#[test]
+fn set_updater_settings_rejects_zero_speed_limit() {
+    let server = UpdateServer::start();
+    let app = mock_app(server.endpoint("latest"));
+
+    let settings = UpdaterSettings {
+        max_download_speed: Some(0),
+        ..Default::default()
+    };
+    let error = set_updater_settings(app.handle().clone(), app.state::<UpdaterState>(), settings)
+        .unwrap_err();
+    assert_eq!(error.code(), ErrorCode::InvalidArgument);
+}
+
+#[test]
+fn install_update_without_pending_update_fails() {
+    let server = UpdateServer::start();
+    let app = mock_app(server.endpoint("latest"));
+
+    let (channel, events) = event_channel();
+    let error = block_on(install_update(
+        app.handle().clone(),
+        app.state::<PendingUpdate>(),
+        channel,
+    ))
+    .unwrap_err();
+    assert_eq!(error.code(), ErrorCode::NoPendingUpdate);
+    assert!(events.lock().unwrap().is_empty());
+}
+
+/// 下载最新版本并暂存，返回暂存清单
+fn stage_latest(app: &App<MockRuntime>) -> staging::StagedManifest {
+    fetch(app).unwrap();
+    let update = app
+        .state::<PendingUpdate>()
+        .0
+        .lock()
+        .unwrap()
+        .take()
+        .unwrap();
+    let (channel, _) = event_channel();
+    let bytes = block_on(download_update(app.handle(), &update, &channel)).unwrap();
+    staging::stage(app.handle(), &update, &bytes).unwrap()
+}
+
+#[test]
+fn staged_update_is_restored_without_network() {
+    let server = UpdateServer::start();
+    let app = mock_app(server.endpoint("latest"));
+    let manifest = stage_latest(&app);
+    assert_eq!(manifest.version, LATEST_VERSION);
+    let endpoint = server.endpoint("latest");
+    drop(server);
+    let identifier = app.shutdown();
+
+    // 重启后更新服务器已经无法访问，暂存的更新仍然可以直接安装
+    let restarted = mock_app_named(
+        identifier,
+        UpdaterSettings {
+            endpoints: vec![endpoint],
+            ..Default::default()
+        },
+    );
+    assert!(fetch(&restarted).is_err());
+    assert_eq!(staging::restore(restarted.handle()), Some(manifest.clone()));
+    assert_eq!(
+        get_staged_update(restarted.state::<StagedUpdate>()),
+        Some(manifest)
+    );
+}
+
+#[test]
+fn restore_discards_tampered_staged_update() {
+    let server = UpdateServer::start();
+    let app = mock_app(server.endpoint("latest"));
+    let manifest = stage_latest(&app);
+    app.state::<StagedUpdate>().set(None);
+
+    let path = app
+        .path()
+        .app_local_data_dir()
+        .unwrap()
+        .join("updates")
+        .join(&manifest.file_name);
+    let mut bytes = std::fs::read(&path).unwrap();
+    bytes[0] ^= 0xff;
+    std::fs::write(&path, bytes).unwrap();
+
+    assert_eq!(staging::restore(app.handle()), None);
+    assert_eq!(staging::load_manifest(app.handle()), None);
+    assert_eq!(get_staged_update(app.state::<StagedUpdate>()), None);
+}
+
+#[test]
+fn rolled_back_confirmed_version_is_only_notified() {
+    let server = UpdateServer::start();
+    let app = mock_app_with(UpdaterSettings {
+        endpoints: vec![server.endpoint("latest")],
+        mode: UpdateMode::Staged,
+        ..Default::default()
+    });
+
+    // 已确认的 1.0.0 被手动回滚到当前版本
+    let mut record = HealthRecord::default();
+    record.installing(
+        "0.1.0",
+        RetainedPackage {
+            version: LATEST_VERSION.to_string(),
+            signature: None,
+            file_name: format!("{LATEST_VERSION}.update"),
+        },
+    );
+    record.launched(LATEST_VERSION);
+    record.confirm();
+    record.rolled_back(LATEST_VERSION);
+    let dir = app.path().app_local_data_dir().unwrap().join("rollback");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("health.json"),
+        serde_json::to_string(&record).unwrap(),
+    )
+    .unwrap();
+
+    // 服务器再次提供被回滚的版本时只通知前端，不会下载暂存
+    block_on(updater::update(app.handle().clone())).unwrap();
+    assert!(app.state::<PendingUpdate>().0.lock().unwrap().is_some());
+    assert_eq!(staging::load_manifest(app.handle()), None);
+    assert_eq!(get_staged_update(app.state::<StagedUpdate>()), None);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn replaces_appimage_in_place() {
+    use std::os::unix::fs::PermissionsExt;
+    use tauri_bun_vite_lib::core::updater::install::replace_appimage;
+
+    let dir = TestDir::new("appimage");
+    let target = dir.join("app.AppImage");
+    std::fs::write(&target, b"old").unwrap();
+    std::fs::set_permissions(&target, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    replace_appimage(&target, b"new").unwrap();
+    assert_eq!(std::fs::read(&target).unwrap(), b"new");
+    let mode = std::fs::metadata(&target).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o755);
+    // 临时文件已经被重命名为 AppImage
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+}
@@ -0,0 +1,349 @@
+#![cfg(not(any(target_os = "android", target_os = "ios")))]
+
+mod common;
+
+use common::app::TestApp;
+use serde_json::json;
+use tauri_bun_vite_lib::core::window::{
+    self, registry, transition, GeometryStore, MonitorArea, OpenWindowRequest, WindowAction,
+    WindowGeometry, WindowRegistry, WindowSettings, WindowSpec, WindowVisibility,
+};
+use tauri_bun_vite_lib::models::ErrorCode;
+
+fn mock_app() -> TestApp {
+    common::app::mock_app("window")
+}
+
+#[test]
+fn show_and_hide_ignore_settings() {
+    let settings = WindowSettings::default();
+    for current in [
+        WindowVisibility::Visible,
+        WindowVisibility::Minimized,
+        WindowVisibility::Hidden,
+    ] {
+        assert_eq!(
+            transition(current, WindowAction::Show, &settings),
+            WindowVisibility::Visible
+        );
+        assert_eq!(
+            transition(current, WindowAction::Hide, &settings),
+            WindowVisibility::Hidden
+        );
+    }
+}
+
+#[test]
+fn toggle_restores_minimized_windows() {
+    let settings = WindowSettings::default();
+    assert_eq!(
+        transition(WindowVisibility::Visible, WindowAction::Toggle, &settings),
+        WindowVisibility::Hidden
+    );
+    assert_eq!(
+        transition(WindowVisibility::Hidden, WindowAction::Toggle, &settings),
+        WindowVisibility::Visible
+    );
+    assert_eq!(
+        transition(WindowVisibility::Minimized, WindowAction::Toggle, &settings),
+        WindowVisibility::Visible
+    );
+}
+
+#[test]
+fn close_and_minimize_follow_settings() {
+    let to_tray = WindowSettings {
+        close_to_tray: true,
+        minimize_to_tray: true,
+    };
+    let native = WindowSettings {
+        close_to_tray: false,
+        minimize_to_tray: false,
+    };
+    let current = WindowVisibility::Visible;
+
+    assert_eq!(
+        transition(current, WindowAction::Close, &to_tray),
+        WindowVisibility::Hidden
+    );
+    assert_eq!(
+        transition(current, WindowAction::Close, &native),
+        WindowVisibility::Missing
+    );
+    assert_eq!(
+        transition(current, WindowAction::Minimize, &to_tray),
+        WindowVisibility::Hidden
+    );
+    assert_eq!(
+        transition(current, WindowAction::Minimize, &native),
+        WindowVisibility::Minimized
+    );
+}
+
+#[test]
+fn missing_window_stays_missing() {
+    let settings = WindowSettings::default();
+    for action in [
+        WindowAction::Show,
+        WindowAction::Hide,
+        WindowAction::Toggle,
+        WindowAction::Minimize,
+        WindowAction::Close,
+    ] {
+        assert_eq!(
+            transition(WindowVisibility::Missing, action, &settings),
+            WindowVisibility::Missing
+        );
+    }
+}
+
+#[test]
+fn settings_use_defaults_for_missing_fields() {
+    let settings: WindowSettings = serde_json::from_value(json!({})).unwrap();
+    assert_eq!(settings, WindowSettings::default());
+    // 默认与系统行为一致：关闭即退出，最小化到任务栏
+    assert!(!settings.close_to_tray);
+    assert!(!settings.minimize_to_tray);
+
+    let settings: WindowSettings =
+        serde_json::from_value(json!({ "minimizeToTray": true })).unwrap();
+    assert!(!settings.close_to_tray);
+    assert!(settings.minimize_to_tray);
+}
+
+#[test]
+fn missing_window_returns_error() {
+    let app = mock_app();
+    assert_eq!(
+        window::visibility(app.handle(), "missing"),
+        WindowVisibility::Missing
+    );
+    let error = window::show(app.handle(), "missing").unwrap_err();
+    assert_eq!(error.code(), ErrorCode::WindowNotFound);
+    // 主窗口不存在时只记录日志，不会崩溃
+    window::show_main(app.handle());
+    window::hide_main(app.handle());
+}
+
+fn monitor(name: &str, x: i32, y: i32, width: u32, height: u32) -> MonitorArea {
+    MonitorArea {
+        name: Some(name.to_string()),
+        x,
+        y,
+        width,
+        height,
+    }
+}
+
+fn geometry(x: i32, y: i32, width: u32, height: u32, monitor: &str) -> WindowGeometry {
+    WindowGeometry {
+        x,
+        y,
+        width,
+        height,
+        maximized: false,
+        fullscreen: false,
+        monitor: Some(monitor.to_string()),
+    }
+}
+
+#[test]
+fn keeps_geometry_on_existing_monitor() {
+    let monitors = [
+        monitor("primary", 0, 0, 1920, 1080),
+        monitor("external", 1920, 0, 2560, 1440),
+    ];
+    let saved = geometry(2100, 100, 1200, 800, "external");
+    assert_eq!(saved.clamp(&monitors, None), saved);
+}
+
+#[test]
+fn moves_window_back_when_monitor_is_gone() {
+    let monitors = [monitor("primary", 0, 0, 1920, 1080)];
+    let saved = geometry(2100, 100, 1200, 800, "external");
+    let restored = saved.clamp(&monitors, None);
+    assert_eq!(restored.monitor.as_deref(), Some("primary"));
+    assert_eq!((restored.width, restored.height), (1200, 800));
+    assert_eq!((restored.x, restored.y), (360, 140));
+}
+
+#[test]
+fn moves_window_to_primary_monitor_not_first_listed() {
+    // 系统列出的第一个显示器不一定是主显示器
+    let monitors = [
+        monitor("side", -1280, 0, 1280, 720),
+        monitor("primary", 0, 0, 1920, 1080),
+    ];
+    let saved = geometry(5000, 100, 1200, 800, "external");
+    let restored = saved.clamp(&monitors, Some(&monitors[1]));
+    assert_eq!(restored.monitor.as_deref(), Some("primary"));
+    assert_eq!((restored.x, restored.y), (360, 140));
+
+    // 没有主显示器信息时使用第一个显示器
+    let restored = saved.clamp(&monitors, None);
+    assert_eq!(restored.monitor.as_deref(), Some("side"));
+}
+
+#[test]
+fn recenters_offscreen_window_and_shrinks_to_monitor() {
+    let monitors = [monitor("primary", 0, 0, 1280, 720)];
+
+    // 标题栏在屏幕上方，无法拖动
+    let restored = geometry(100, -500, 800, 600, "primary").clamp(&monitors, None);
+    assert_eq!((restored.x, restored.y), (240, 60));
+
+    // 几乎完全移出屏幕
+    let restored = geometry(1250, 100, 800, 600, "primary").clamp(&monitors, None);
+    assert_eq!((restored.x, restored.y), (240, 60));
+
+    // 窗口比显示器大
+    let restored = geometry(0, 0, 2560, 1440, "primary").clamp(&monitors, None);
+    assert_eq!((restored.width, restored.height), (1280, 720));
+    assert_eq!((restored.x, restored.y), (0, 0));
+}
+
+#[test]
+fn keeps_geometry_without_monitor_information() {
+    let saved = geometry(-5000, -5000, 800, 600, "primary");
+    assert_eq!(saved.clamp(&[], None), saved);
+}
+
+#[test]
+fn geometry_store_round_trips() {
+    let app = mock_app();
+    assert!(GeometryStore::load(app.handle()).0.is_empty());
+
+    let mut store = GeometryStore::default();
+    let mut main = geometry(10, 20, 1000, 700, "primary");
+    main.maximized = true;
+    store.0.insert("main".to_string(), main);
+    store.save(app.handle()).unwrap();
+    assert_eq!(GeometryStore::load(app.handle()), store);
+
+    // 旧版本的记录没有状态字段
+    let saved: WindowGeometry =
+        serde_json::from_value(json!({ "x": 1, "y": 2, "width": 300, "height": 400 })).unwrap();
+    assert!(!saved.maximized && !saved.fullscreen && saved.monitor.is_none());
+}
+
+fn request(kind: &str, key: Option<&str>, route: Option<&str>) -> OpenWindowRequest {
+    OpenWindowRequest {
+        kind: kind.to_string(),
+        key: key.map(str::to_string),
+        route: route.map(str::to_string),
+    }
+}
+
+#[test]
+fn resolves_singleton_windows() {
+    let registry = WindowRegistry::default();
+    let (spec, resolved) = registry.resolve(&request("settings", None, None)).unwrap();
+    assert_eq!(spec.kind(), "settings");
+    assert_eq!(resolved.label, "settings");
+    assert_eq!(resolved.route, "/settings");
+
+    // 单例窗口忽略 key，但可以跳转到其他路由
+    let (_, resolved) = registry
+        .resolve(&request("settings", Some("1"), Some("/settings/tray")))
+        .unwrap();
+    assert_eq!(resolved.label, "settings");
+    assert_eq!(resolved.route, "/settings/tray");
+}
+
+#[test]
+fn resolves_multiple_windows_by_key_or_route() {
+    let registry = WindowRegistry::default();
+    let (_, resolved) = registry
+        .resolve(&request("order-detail", Some("1024"), None))
+        .unwrap();
+    assert_eq!(resolved.label, "order-detail-1024");
+    assert_eq!(resolved.route, "/orders/1024");
+
+    let (_, resolved) = registry
+        .resolve(&request("page", None, Some("/dashboard/workbench")))
+        .unwrap();
+    assert_eq!(resolved.label, "page-dashboard-workbench");
+    assert_eq!(resolved.route, "/dashboard/workbench");
+
+    let (_, resolved) = registry.resolve(&request("page", None, Some("/"))).unwrap();
+    assert_eq!(resolved.label, "page-_");
+
+    assert_eq!(
+        registry
+            .spec_for_label("order-detail-1024")
+            .map(WindowSpec::kind),
+        Some("order-detail")
+    );
+    assert_eq!(
+        registry.spec_for_label("about").map(WindowSpec::kind),
+        Some("about")
+    );
+    assert!(registry.spec_for_label("main").is_none());
+}
+
+#[test]
+fn route_keys_do_not_collide() {
+    let registry = WindowRegistry::default();
+    let routes = [
+        "/", "/a/b", "/a-b", "/a_b", "/a_2Db", "/a//b", "/a/b/", "/a?b=1", "/订单", "/_",
+    ];
+    let labels: Vec<String> = routes
+        .iter()
+        .map(|route| {
+            let (_, resolved) = registry
+                .resolve(&request("page", None, Some(route)))
+                .unwrap();
+            assert_eq!(resolved.route, *route);
+            resolved.label
+        })
+        .collect();
+    for (index, label) in labels.iter().enumerate() {
+        assert!(
+            !labels[index + 1..].contains(label),
+            "{} 与其他路由冲突",
+            label
+        );
+        assert_eq!(
+            registry.spec_for_label(label).map(WindowSpec::kind),
+            Some("page")
+        );
+    }
+}
+
+#[test]
+fn closes_only_registered_windows() {
+    let app = mock_app();
+    window::init(app.handle());
+    for label in ["main", "splashscreen", "unknown", "order-detail"] {
+        let error = registry::close(app.handle(), label).unwrap_err();
+        assert_eq!(error.code(), ErrorCode::InvalidArgument, "{}", label);
+    }
+    // 已登记但没有打开的窗口直接忽略
+    registry::close(app.handle(), "about").unwrap();
+    registry::close(app.handle(), "order-detail-1024").unwrap();
+}
+
+#[test]
+fn rejects_invalid_window_requests() {
+    let registry = WindowRegistry::default();
+    for request in [
+        request("unknown", None, None),
+        request("order-detail", None, None),
+        request("order-detail", Some("../1"), None),
+        request("page", None, Some("dashboard")),
+    ] {
+        let error = registry.resolve(&request).unwrap_err();
+        assert_eq!(error.code(), ErrorCode::InvalidArgument, "{:?}", request);
+    }
+}
+
+#[test]
+fn registers_custom_windows() {
+    let mut registry = WindowRegistry::default();
+    registry.register(WindowSpec::new("report", "/reports/{key}").multiple());
+    let (_, resolved) = registry
+        .resolve(&request("report", Some("2024_q1"), None))
+        .unwrap();
+    assert_eq!(resolved.label, "report-2024_q1");
+    assert_eq!(resolved.route, "/reports/2024_q1");
+}
@@ -1,27 +1,47 @@
 // i18n 多语言配置
 import { createI18n } from 'vue-i18n'
 import type { I18nOptions } from 'vue-i18n'
+import { invoke } from '@tauri-apps/api/core'
+import { listen } from '@tauri-apps/api/event'
 
-// 自动加载 locales 下的所有 json 文件
-const messages = Object.fromEntries(
-    Object.entries(
-        import.meta.glob<{ default: any }>('../locales/*.json', { eager: true })
-    ).map(([key, value]) => {
-        const matched = key.match(/([A-Za-z0-9-_]+)\./i)?.[1]
-        return [matched, value.default]
-    })
-)
+// 支持的语言，与 Rust 端 core::locale::Locale 保持一致
+export const SUPPORTED_LOCALES = ['en-US', 'zh-CN'] as const
+
+// 语言类型
+export type I18nLanguage = (typeof SUPPORTED_LOCALES)[number]
+
+// 切换语言后 Rust 端发送的事件
+const LOCALE_CHANGED_EVENT = 'locale://changed'
+
+// 自动加载 locales/<语言>/ 下的所有 json 文件
+// <语言>.json 合并到根节点，其他文件按路径嵌套，例如 pages/home.json 对应 pages.home
+const messages: Record<string, Record<string, any>> = {}
+for (const [key, value] of Object.entries(
+    import.meta.glob<{ default: Record<string, any> }>('./*/**/*.json', { eager: true })
+)) {
+    const [lang, ...path] = key.replace(/^\.\//, '').replace(/\.json$/, '').split('/')
+    const target = (messages[lang] ??= {})
+    if (path.length === 1 && path[0] === lang) {
+        Object.assign(target, value.default)
+        continue
+    }
+    let node = target
+    for (const part of path.slice(0, -1)) {
+        node = node[part] ??= {}
+    }
+    node[path[path.length - 1]] = value.default
+}
 
 // i18n 配置
 const i18nOptions: I18nOptions = {
     legacy: false, // 使用 Composition API 模式
-    locale: 'en', // 默认语言
-    fallbackLocale: 'en', // 回退语言
+    locale: 'en-US', // 默认语言
+    fallbackLocale: 'en-US', // 回退语言
     messages, // 语言包
     globalInjection: true, // 全局注入 $t 函数
     // 日期时间本地化配置
     datetimeFormats: {
-        en: {
+        'en-US': {
             short: {
                 year: 'numeric',
                 month: 'short',
@@ -36,7 +56,7 @@ const i18nOptions: I18nOptions = {
                 minute: 'numeric'
             }
         },
-        zh: {
+        'zh-CN': {
             short: {
                 year: 'numeric',
                 month: 'short',
@@ -55,13 +75,13 @@ const i18nOptions: I18nOptions = {
     },
     // 数字本地化配置
     numberFormats: {
-        en: {
+        'en-US': {
             currency: {
                 style: 'currency',
                 currency: 'USD'
             }
         },
-        zh: {
+        'zh-CN': {
             currency: {
                 style: 'currency',
                 currency: 'CNY'
@@ -72,23 +92,39 @@ const i18nOptions: I18nOptions = {
 
 export const i18n = createI18n(i18nOptions)
 
-// 语言类型
-export type I18nLanguage = keyof typeof messages
+function isSupported(lang: string): lang is I18nLanguage {
+    return (SUPPORTED_LOCALES as readonly string[]).includes(lang)
+}
 
-// 切换语言
-export function setI18nLanguage(lang: I18nLanguage) {
+// 只在前端应用语言，不通知 Rust 端
+function applyI18nLanguage(lang: I18nLanguage) {
     i18n.global.locale.value = lang
-    // 可以在这里添加 localStorage 保存用户语言偏好
-    localStorage.setItem('lang', lang)
     // 设置 html 的 lang 属性
     document.querySelector('html')?.setAttribute('lang', lang)
 }
 
-// 初始化语言
-export function initI18n() {
-    // 从 localStorage 或 navigator 中获取用户语言偏好
-    const savedLang = localStorage.getItem('lang')
-    const userLang = navigator.language.split('-')[0]
-    const defaultLang = savedLang || (Object.keys(messages).includes(userLang) ? userLang : 'en')
-    setI18nLanguage(defaultLang as I18nLanguage)
-}
\ No newline at end of file
+// 切换语言，由 Rust 端保存用户的选择并同步托盘等原生界面
+export async function setI18nLanguage(lang: I18nLanguage) {
+    applyI18nLanguage(lang)
+    await invoke('set_locale', { locale: lang })
+}
+
+// 初始化语言，并跟随托盘等其他位置的语言切换
+export async function initI18n() {
+    try {
+        const lang = await invoke<string>('get_locale')
+        applyI18nLanguage(isSupported(lang) ? lang : 'en-US')
+    } catch {
+        // 不在 Tauri 环境中运行时根据浏览器语言选择
+        const userLang = SUPPORTED_LOCALES.find((lang) =>
+            lang.startsWith(navigator.language.split('-')[0])
+        )
+        applyI18nLanguage(userLang ?? 'en-US')
+        return
+    }
+    await listen<{ locale: string }>(LOCALE_CHANGED_EVENT, (event) => {
+        if (isSupported(event.payload.locale)) {
+            applyI18nLanguage(event.payload.locale)
+        }
+    })
+}
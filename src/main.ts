@@ -7,12 +7,7 @@ import router from "@/router";
 // UnoCSS 样式
 import 'virtual:uno.css'
 // import '@unocss/reset/tailwind.css'
-import {createI18n} from "vue-i18n";
-
-
-const i18n = createI18n({
-    // something vue-i18n options here ...
-})
+import { i18n, initI18n } from "@/locales";
 
 // 创建vue实例
 const app = createApp(App);
@@ -24,8 +19,8 @@ app.use(pinia)
 // 注册路由
 app.use(router)
 
-// 等待路由准备就绪后再挂载应用
-router.isReady().then(() => {
+// 等待语言和路由准备就绪后再挂载应用
+Promise.all([initI18n(), router.isReady()]).then(() => {
     // 路由初始化完成后挂载Vue根实例
     app.mount('#app')
 })
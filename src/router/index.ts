@@ -1,6 +1,7 @@
 import {createRouter, createWebHistory, type RouteRecordRaw} from 'vue-router'
 import { moduleRoutes } from './modules'
 import { setupRouterGuards } from './guards'
+import { setupNativeNavigation } from './navigation'
 
 /**
  * 创建路由实例
@@ -30,4 +31,7 @@ export function resetRouter() {
 // 安装路由守卫
 setupRouterGuards(router)
 
+// 响应托盘等原生界面发起的路由跳转
+setupNativeNavigation(router)
+
 export default router
\ No newline at end of file
@@ -2,6 +2,7 @@ import type { AppRouteRecordRaw } from '../types'
 import authRoutes from './auth.router'
 import exceptionRoutes from './exception.router'
 import dashboardRoutes from './dashboard.router'
+import windowRoutes from './window.router'
 
 /**
  * 聚合所有模块路由
@@ -9,6 +10,7 @@ import dashboardRoutes from './dashboard.router'
 export const moduleRoutes: AppRouteRecordRaw[] = [
     ...authRoutes,
     ...dashboardRoutes,
+    ...windowRoutes,
     ...exceptionRoutes
 ]
 
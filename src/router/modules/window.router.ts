@@ -0,0 +1,37 @@
+import {type AppRouteRecordRaw, RouteNames} from '../types'
+
+/**
+ * 独立窗口中打开的页面路由
+ * 窗口由 Rust 端的窗口注册表创建，见 src-tauri/src/core/window/registry.rs
+ */
+const windowRouter: AppRouteRecordRaw[] = [
+    {
+        path: '/settings',
+        name: RouteNames.SETTINGS,
+        component: () => import('@/views/system/settings/SettingsView.vue'),
+        meta: {
+            title: '设置',
+            hidden: true
+        }
+    },
+    {
+        path: '/about',
+        name: RouteNames.ABOUT,
+        component: () => import('@/views/common/AboutView.vue'),
+        meta: {
+            title: '关于',
+            hidden: true
+        }
+    },
+    {
+        path: '/orders/:id',
+        name: RouteNames.ORDER_DETAIL,
+        component: () => import('@/views/business/order/OrderDetailView.vue'),
+        meta: {
+            title: '订单详情',
+            hidden: true
+        }
+    }
+]
+
+export default windowRouter
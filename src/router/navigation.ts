@@ -0,0 +1,125 @@
+import type { Router } from 'vue-router'
+import { invokeCommand } from '@/services/api/http.ts'
+import { listen } from '@tauri-apps/api/event'
+import { getCurrentWindow } from '@tauri-apps/api/window'
+import { openUrl } from '@tauri-apps/plugin-opener'
+
+// Rust 端请求路由跳转的事件，例如点击托盘中的最近使用或快捷操作
+const NAVIGATE_EVENT = 'app://navigate'
+
+export type RecentKind = 'order' | 'product' | 'file' | 'page'
+
+export interface RecentItem {
+    id: string
+    kind: RecentKind
+    title: string
+    route: string
+}
+
+/**
+ * 响应原生界面（托盘、深度链接等）发起的路由跳转
+ * @param router - 路由实例
+ */
+export async function setupNativeNavigation(router: Router) {
+    const navigate = (route: string) => {
+        router.push(route).catch((error) => console.error(error))
+    }
+    try {
+        await listen<{ route: string }>(NAVIGATE_EVENT, (event) => navigate(event.payload.route))
+        // 主窗口加载完成之前打开的深度链接由 Rust 端暂存，开始监听后再取走
+        if (getCurrentWindow().label === 'main') {
+            const pending = await invokeCommand<string[]>('take_deep_links')
+            pending.forEach(navigate)
+        }
+    } catch {
+        // 不在 Tauri 环境中运行时忽略
+    }
+}
+
+/**
+ * 记录最近打开的订单、商品、文件等，显示在托盘的“最近使用”子菜单中
+ * @param item - 最近打开的条目
+ */
+export async function recordRecent(item: RecentItem) {
+    await invokeCommand('add_recent_item', { item })
+}
+
+export type WindowKind = 'settings' | 'about' | 'order-detail' | 'page'
+
+export interface OpenWindowRequest {
+    kind: WindowKind
+    /** 多实例窗口的标识，例如订单号 */
+    key?: string
+    /** 要打开的路由，不指定时使用窗口类型的默认路由 */
+    route?: string
+}
+
+/**
+ * 打开设置、关于、订单详情等独立窗口，已经打开的窗口会被聚焦
+ * @param request - 窗口类型、标识和路由
+ * @returns 窗口标签
+ */
+export async function openWindow(request: OpenWindowRequest) {
+    return await invokeCommand<string>('open_window', { request })
+}
+
+/**
+ * 在新窗口中打开路由，同一路由只会打开一个窗口
+ * @param route - 路由地址，例如 /dashboard/workbench
+ * @returns 窗口标签
+ */
+export async function openRouteWindow(route: string) {
+    return await invokeCommand<string>('open_route_window', { route })
+}
+
+// 应用已经运行时再次启动，Rust 端转发新进程的命令行参数
+const SECOND_INSTANCE_EVENT = 'app://second-instance'
+
+export interface LaunchArgs {
+    /** 原始的命令行参数，不包含可执行文件路径 */
+    args: string[]
+    /** 启动进程的工作目录 */
+    cwd: string
+    /** 参数中的文件路径，已解析为绝对路径 */
+    files: string[]
+    /** 参数中的 URL */
+    urls: string[]
+    /** 以 - 开头的命令行选项 */
+    flags: string[]
+}
+
+/**
+ * 处理启动参数，包括本次启动以及之后再次启动应用时传入的文件和 URL
+ * @param handler - 处理一次启动的参数
+ */
+export async function setupLaunchArgs(handler: (launch: LaunchArgs) => void) {
+    try {
+        // 先监听事件再取走暂存的参数，避免两者之间收到的参数丢失
+        await listen<LaunchArgs>(SECOND_INSTANCE_EVENT, (event) => handler(event.payload))
+        const pending = await invokeCommand<LaunchArgs[]>('take_launch_args')
+        pending.forEach(handler)
+    } catch {
+        // 不在 Tauri 环境中运行时忽略
+    }
+}
+
+// 设置档案的扩展名，与 Rust 端 core::profile::FILE_EXTENSION 保持一致
+const PROFILE_EXTENSION = '.toml'
+
+/**
+ * 处理一次启动的参数：网页链接在浏览器中打开，设置档案在设置窗口中预览导入
+ * @param launch - 启动参数
+ */
+export function handleLaunchArgs(launch: LaunchArgs) {
+    for (const url of launch.urls) {
+        if (/^https?:\/\//i.test(url)) {
+            openUrl(url).catch((error) => console.error(error))
+        }
+    }
+    for (const file of launch.files) {
+        if (file.toLowerCase().endsWith(PROFILE_EXTENSION)) {
+            const route = `/settings?profile=${encodeURIComponent(file)}`
+            openWindow({ kind: 'settings', route }).catch((error) => console.error(error))
+        }
+    }
+}
@@ -37,6 +37,9 @@ export const RouteNames = {
     HOME: 'Home',
     LOGIN: 'Login',
     DASHBOARD: 'Dashboard',
+    SETTINGS: 'Settings',
+    ABOUT: 'About',
+    ORDER_DETAIL: 'OrderDetail',
     FORBIDDEN: 'Forbidden',
     NOT_FOUND: 'NotFound',
     SERVER_ERROR: 'ServerError',
@@ -1,6 +1,8 @@
 import axios from 'axios'
 import type { AxiosInstance, AxiosRequestConfig } from 'axios'
-import { setupInterceptors } from './interceptors.ts'
+import { invoke } from '@tauri-apps/api/core'
+import { listen } from '@tauri-apps/api/event'
+import { handleCommandError, setupInterceptors } from './interceptors.ts'
 
 /**
  * 创建Axios实例
@@ -26,4 +28,43 @@ export function createAxiosInstance(config?: AxiosRequestConfig): AxiosInstance
 // 默认导出的axios实例
 const http = createAxiosInstance()
 
+/**
+ * 调用 Tauri 命令，失败时先按错误码统一处理再抛出 AppError
+ * @param command - 命令名称
+ * @param args - 命令参数
+ */
+export function invokeCommand<T>(command: string, args?: Record<string, unknown>): Promise<T> {
+    return invoke<T>(command, args).catch(handleCommandError)
+}
+
+// Rust 端分层配置中前端用到的部分，见 src-tauri/src/core/config/mod.rs
+interface RuntimeConfig {
+    api: { baseUrl: string | null }
+}
+
+/**
+ * 使用 Rust 端分层配置中的接口地址（配置文件、TAURI_BUN_VITE_API_BASE_URL 环境变量或 --api-base-url 参数），
+ * 没有配置时保留构建时的 VITE_API_BASE_URL
+ */
+export async function applyRuntimeConfig(instance: AxiosInstance = http) {
+    const apply = (config: RuntimeConfig) => {
+        instance.defaults.baseURL = config.api.baseUrl || import.meta.env.VITE_API_BASE_URL
+    }
+    try {
+        // 应用运行期间修改配置文件后 Rust 端重新加载配置并发出 config://changed 事件
+        await listen<{ changed: string[], config: RuntimeConfig }>('config://changed', (event) => {
+            if (event.payload.changed.includes('api.baseUrl')) {
+                apply(event.payload.config)
+            }
+        })
+        const { config } = await invoke<{ config: RuntimeConfig }>('get_effective_config')
+        apply(config)
+    } catch (error) {
+        // 不在 Tauri 环境中运行，或者窗口没有读取配置的权限时使用构建时的配置
+        console.warn('读取运行时配置失败，使用构建时的接口地址:', error)
+    }
+}
+
+applyRuntimeConfig()
+
 export default http
\ No newline at end of file
@@ -1,6 +1,7 @@
 import type { AxiosInstance, AxiosResponse, InternalAxiosRequestConfig } from 'axios'
 import router from '@/router'
 import {useAuthStore} from "@/stores/modules/auth.store.ts";
+import { isAppError } from './types.ts'
 
 /**
  * 配置拦截器
@@ -89,6 +90,41 @@ function handleHttpError(error: any) {
     return Promise.reject(error)
 }
 
+/**
+ * 处理 Tauri 命令返回的错误，根据 Rust 端 AppError 的错误码分支处理
+ * @returns 始终拒绝，调用方可以继续处理或直接展示 message
+ */
+export function handleCommandError(error: unknown): Promise<never> {
+    if (!isAppError(error)) {
+        // 不在 Tauri 环境中运行或命令未注册
+        return Promise.reject(error)
+    }
+    switch (error.code) {
+        case 'INTERNAL':
+            // 未预期的内部错误，details 只用于排查问题
+            console.error(error.details ?? error.message)
+            router.push('/500')
+            break
+        case 'WINDOW_NOT_FOUND':
+        case 'TRAY_NOT_FOUND':
+            // 窗口或托盘已经关闭，不影响当前操作
+            console.warn(error.message)
+            break
+        case 'DEPENDENCY_NOT_READY':
+        case 'DEPENDENCY_FAILED':
+        case 'UNKNOWN_DEPENDENCY':
+            // 启动任务编排出错，启动画面会显示失败状态
+            console.error(`${error.code}: ${error.message}`)
+            break
+        default:
+            if (error.category === 'validation') {
+                // 参数或文件内容无效，message 可以直接展示给用户
+                console.warn(error.message)
+            }
+    }
+    return Promise.reject(error)
+}
+
 /**
  * 处理未授权情况
  */
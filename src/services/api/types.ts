@@ -97,4 +97,32 @@ export interface CustomRequestConfig extends AxiosRequestConfig {
      * @default false
      */
     skipInterceptors?: boolean
-}
\ No newline at end of file
+}
+/**
+ * Tauri 命令返回的结构化错误，与 Rust 端的 `AppError` 保持一致
+ */
+export interface AppError {
+    /**
+     * 错误码，例如 UNKNOWN_TASK、NO_PENDING_UPDATE
+     */
+    code: string
+    /**
+     * 错误分类，例如 validation、startup、updater
+     */
+    category: 'validation' | 'startup' | 'updater' | 'window' | 'tray' | 'io' | 'internal'
+    /**
+     * 面向用户的错误信息
+     */
+    message: string
+    /**
+     * 面向开发者的详细信息
+     */
+    details?: string
+}
+
+/**
+ * 判断 invoke 抛出的错误是否为 Rust 端的 AppError
+ */
+export function isAppError(error: unknown): error is AppError {
+    return typeof error === 'object' && error !== null && 'code' in error && 'category' in error
+}
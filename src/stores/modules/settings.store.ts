@@ -1,5 +1,7 @@
 import {defineStore} from 'pinia'
-import {ref, watch} from 'vue'
+import {ref} from 'vue'
+import {invokeCommand} from '@/services/api/http.ts'
+import {listen} from '@tauri-apps/api/event'
 
 // 定义布局类型
 type LayoutType = 'classic' | 'horizontal' | 'sidebar'
@@ -7,57 +9,153 @@ type LayoutType = 'classic' | 'horizontal' | 'sidebar'
 // 定义主题颜色
 type ThemeColor = 'blue' | 'green' | 'red' | 'purple' | 'orange' | 'teal' | 'pink'
 
+// 界面外观
+interface AppearanceSettings {
+    layout: LayoutType
+    themeColor: ThemeColor
+    darkMode: boolean
+}
+
+// 窗口行为
+interface WindowSettings {
+    closeToTray: boolean
+    minimizeToTray: boolean
+}
+
+// Rust 端保存的用户偏好设置，见 src-tauri/src/core/settings/mod.rs
+interface Settings {
+    appearance: AppearanceSettings
+    window: WindowSettings
+}
+
+// 只修改需要修改的字段
+interface SettingsPatch {
+    appearance?: Partial<AppearanceSettings>
+    window?: Partial<WindowSettings>
+}
+
+// 设置档案中的分组，见 src-tauri/src/core/profile/mod.rs
+export type ProfileSection = 'appearance' | 'language' | 'updates' | 'windows' | 'shortcuts'
+
+// 导入设置档案前的预览：每个分组中将要变化的字段
+export interface ProfilePreview {
+    /** 预览的标识，导入时传给 importProfile */
+    id: number
+    path: string
+    appVersion: string
+    exportedAt: number
+    sections: {
+        section: ProfileSection
+        changes: { field: string, current: unknown, imported: unknown }[]
+    }[]
+}
+
+// 设置变化后 Rust 端向所有窗口发出的事件
+const SETTINGS_CHANGED_EVENT = 'settings-changed'
+
+// 旧版本保存在 localStorage 中的设置
+const LEGACY_STORAGE_KEYS = ['layout', 'theme', 'darkMode', 'vue3-admin-settings']
+
 export const useSettingsStore = defineStore(
     'settings',
     () => {
-        // 从 localStorage 加载设置或使用默认值
-        const defaultLayout = (localStorage.getItem('layout') as LayoutType) || 'classic'
-        const defaultTheme = (localStorage.getItem('theme') as ThemeColor) || 'blue'
-        const defaultDarkMode = localStorage.getItem('darkMode') === 'true'
-
-        // 响应式状态 state
-        const layout = ref<LayoutType>(defaultLayout)
-        const themeColor = ref<ThemeColor>(defaultTheme)
-        const darkMode = ref<boolean>(defaultDarkMode)
+        // 响应式状态 state，加载 Rust 端的设置之前使用默认值
+        const layout = ref<LayoutType>('classic')
+        const themeColor = ref<ThemeColor>('blue')
+        const darkMode = ref<boolean>(false)
+        const closeToTray = ref<boolean>(false)
+        const minimizeToTray = ref<boolean>(false)
         const settingsDrawerVisible = ref<boolean>(false)
 
-        // 切换布局
-        const switchLayout = (newLayout: LayoutType) => {
-            layout.value = newLayout
+        const apply = ({appearance, window: windowSettings}: Settings) => {
+            layout.value = appearance.layout
+            themeColor.value = appearance.themeColor
+            darkMode.value = appearance.darkMode
+            closeToTray.value = windowSettings.closeToTray
+            minimizeToTray.value = windowSettings.minimizeToTray
         }
 
-        // 切换主题颜色
-        const switchTheme = (color: ThemeColor) => {
-            themeColor.value = color
+        // 修改设置，其他窗口通过 settings-changed 事件同步
+        const update = async (patch: SettingsPatch) => {
+            apply(await invokeCommand<Settings>('update_settings', {patch}))
         }
 
-        // 切换暗黑模式
-        const toggleDarkMode = () => {
-            darkMode.value = !darkMode.value
+        // 恢复默认设置
+        const resetSettings = async () => {
+            apply(await invokeCommand<Settings>('reset_settings'))
         }
 
-        // 持久化设置到 localStorage
-        watch([layout, themeColor, darkMode], ([newLayout, newTheme, newDarkMode]) => {
-            localStorage.setItem('layout', newLayout)
-            localStorage.setItem('theme', newTheme)
-            localStorage.setItem('darkMode', String(newDarkMode))
-        })
+        // 选择保存位置后导出设置档案，返回文件路径，取消时返回 null
+        const exportProfile = (sections: ProfileSection[]) =>
+            invokeCommand<string | null>('export_profile', {sections})
+
+        // 选择设置档案并预览将要变化的字段，取消时返回 null
+        const previewProfile = (path?: string) =>
+            invokeCommand<ProfilePreview | null>('preview_profile', {path})
+
+        // 导入预览过的设置档案中选中的分组，界面外观和语言通过事件同步到所有窗口
+        const importProfile = (id: number, sections: ProfileSection[]) =>
+            invokeCommand<ProfileSection[]>('import_profile', {id, sections})
+
+        // 把旧版本保存在 localStorage 中的设置迁移到 Rust 端，只迁移一次
+        const migrateLegacySettings = async () => {
+            const patch: Partial<AppearanceSettings> = {}
+            const legacyLayout = localStorage.getItem('layout')
+            const legacyTheme = localStorage.getItem('theme')
+            const legacyDarkMode = localStorage.getItem('darkMode')
+            if (legacyLayout) patch.layout = legacyLayout as LayoutType
+            if (legacyTheme) patch.themeColor = legacyTheme as ThemeColor
+            if (legacyDarkMode) patch.darkMode = legacyDarkMode === 'true'
+            if (Object.keys(patch).length > 0) {
+                await update({appearance: patch}).catch((error) => console.error(error))
+            }
+            LEGACY_STORAGE_KEYS.forEach((key) => localStorage.removeItem(key))
+        }
+
+        // 加载 Rust 端的设置，并监听其他窗口的修改
+        const init = async () => {
+            try {
+                await listen<Settings>(SETTINGS_CHANGED_EVENT, (event) => apply(event.payload))
+                apply(await invokeCommand<Settings>('get_settings'))
+                await migrateLegacySettings()
+            } catch {
+                // 不在 Tauri 环境中运行时使用默认设置
+            }
+        }
+
+        // 切换布局
+        const switchLayout = (newLayout: LayoutType) => update({appearance: {layout: newLayout}})
+
+        // 切换主题颜色
+        const switchTheme = (color: ThemeColor) => update({appearance: {themeColor: color}})
+
+        // 切换暗黑模式
+        const toggleDarkMode = () => update({appearance: {darkMode: !darkMode.value}})
+
+        // 点击关闭按钮时隐藏到托盘
+        const setCloseToTray = (enabled: boolean) => update({window: {closeToTray: enabled}})
+
+        // 最小化时隐藏到托盘
+        const setMinimizeToTray = (enabled: boolean) => update({window: {minimizeToTray: enabled}})
+
+        init()
 
         return {
             layout,
             themeColor,
             darkMode,
+            closeToTray,
+            minimizeToTray,
             settingsDrawerVisible,
             switchLayout,
             switchTheme,
-            toggleDarkMode
-        }
-    },
-    {
-        persist: {
-            key: 'vue3-admin-settings',
-            storage: localStorage,
-            pick: ['layout', 'themeColor', 'darkMode']
+            toggleDarkMode,
+            setCloseToTray,
+            setMinimizeToTray,
+            resetSettings,
+            exportProfile,
+            previewProfile,
+            importProfile
         }
     }
-)
\ No newline at end of file
+)